@@ -0,0 +1,73 @@
+//! `vitae bench [--board <w>x<h>] [--seconds <s>]`: times the engine on a few fixed workloads under Conway's
+//! rule and prints how many generations per second it manages on each.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::time::{Duration, Instant};
+
+use crate::conway::{self, Board, Cell, Conway};
+use crate::library;
+use crate::session;
+
+const DEFAULT_SIZE: (usize, usize) = (512, 512);
+const DEFAULT_SECONDS: f32 = 2.0;
+/// Shares of live cells in the random soups, in percent
+const SOUP_DENSITIES: [u64; 3] = [10, 30, 50];
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<()> {
+	let (mut size, mut seconds) = (DEFAULT_SIZE, DEFAULT_SECONDS);
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or_else(|| anyhow!("{} expects a value", arg));
+		match arg.as_str() {
+			"--board" => size = session::parse_size(&value()?).context("invalid --board")?,
+			"--seconds" => seconds = value()?.parse().context("invalid --seconds")?,
+			_ => bail!("unknown bench option `{}`", arg),
+		}
+	}
+	if !seconds.is_finite() || seconds <= 0.0 {
+		bail!("--seconds must be positive");
+	}
+	let budget = Duration::from_secs_f32(seconds);
+	let workloads = workloads(size)?;
+	println!("{}x{} board, {} seconds per workload", size.0, size.1, seconds);
+	println!("{:<20} {:>12} {:>12} {:>16}", "workload", "generations", "gens/sec", "cells/sec");
+	for (name, board) in workloads {
+		let (generations, elapsed) = time(board, budget);
+		let per_second = generations as f64 / elapsed.as_secs_f64();
+		println!("{:<20} {:>12} {:>12.1} {:>16.0}", name, generations, per_second, per_second * (size.0 * size.1) as f64);
+	}
+	Ok(())
+}
+
+fn workloads((width, height): (usize, usize)) -> Result<Vec<(String, Board)>> {
+	let empty = vec![vec![Cell::Dead; width]; height];
+	let mut workloads = vec![];
+	for density in SOUP_DENSITIES.to_vec().into_iter() {
+		let mut board = empty.clone();
+		for (y, row) in board.iter_mut().enumerate() {
+			for (x, cell) in row.iter_mut().enumerate() {
+				if conway::mix((y * width + x) as u64) % 100 < density {
+					*cell = Cell::Alive;
+				}
+			}
+		}
+		workloads.push((format!("soup {}%", density), board));
+	}
+	let index = library::PATTERNS.iter().position(|(name, _)| *name == "gosper glider gun").expect("the gun is built in");
+	let mut gun = empty.clone();
+	library::pattern(index).place(&mut gun, 1, 1).context("the board is too small for the glider gun")?;
+	workloads.push(("glider gun".to_string(), gun));
+	workloads.push(("empty".to_string(), empty));
+	Ok(workloads)
+}
+
+/// Simulates `board` until `budget` runs out, returns the generations and the time they took
+fn time(board: Board, budget: Duration) -> (u64, Duration) {
+	let mut game = Conway::from_board(board);
+	let start = Instant::now();
+	let mut generations = 0;
+	while start.elapsed() < budget {
+		game.update_board_state();
+		generations += 1;
+	}
+	(generations, start.elapsed())
+}
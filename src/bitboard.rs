@@ -0,0 +1,275 @@
+//! Bit-packed copy of the board for two-state birth/survival rules on the Moore neighborhood.
+//!
+//! Every row is stored as 64 cells per `u64` word and a generation is computed a word at a time: the eight
+//! shifted neighbor words are summed with bitwise adders, so every bit position of the sum counts the live
+//! neighbors of one cell. With the `simd` feature four words are summed at once on CPUs with AVX2.
+
+use crate::conway::{Board, Cell};
+use crate::rule::Ruleset;
+
+#[derive(Debug, Clone)]
+pub struct BitBoard {
+	width: usize,
+	/// Words in every row, the bits past `width` in the last one are always zero
+	stride: usize,
+	words: Vec<u64>,
+}
+
+impl BitBoard {
+	pub fn from_board(board: &Board) -> Self {
+		let width = board.first().map_or(0, Vec::len);
+		let stride = width.div_ceil(64);
+		let mut words = vec![0; stride * board.len()];
+		for (y, row) in board.iter().enumerate() {
+			for (x, cell) in row.iter().enumerate() {
+				if cell.is_alive() {
+					words[y * stride + x / 64] |= 1 << (x % 64);
+				}
+			}
+		}
+		Self { width, stride, words }
+	}
+
+	fn height(&self) -> usize {
+		self.words.len().checked_div(self.stride).unwrap_or_default()
+	}
+
+	fn row(&self, y: usize) -> &[u64] {
+		&self.words[y * self.stride..(y + 1) * self.stride]
+	}
+
+	/// Sets every cell of `board` to the cell stored here
+	pub fn write(&self, board: &mut Board) {
+		for (y, row) in board.iter_mut().enumerate() {
+			let words = self.row(y);
+			for (x, cell) in row.iter_mut().enumerate() {
+				let alive = words[x / 64] >> (x % 64) & 1 == 1;
+				*cell = if alive { Cell::Alive } else { Cell::Dead };
+			}
+		}
+	}
+
+	/// Cells that differ from `previous`
+	pub fn changes<'a>(&'a self, previous: &'a Self) -> impl Iterator<Item = (usize, usize)> + 'a {
+		self.positions(previous, |now, before| now ^ before)
+	}
+
+	/// Cells alive here that are dead in `previous`
+	pub fn born<'a>(&'a self, previous: &'a Self) -> impl Iterator<Item = (usize, usize)> + 'a {
+		self.positions(previous, |now, before| now & !before)
+	}
+
+	/// Cells whose bit is set in `select` of the words here and in `previous`
+	fn positions<'a>(
+		&'a self,
+		previous: &'a Self,
+		select: impl Fn(u64, u64) -> u64 + 'a,
+	) -> impl Iterator<Item = (usize, usize)> + 'a {
+		let stride = self.stride;
+		self.words.iter().zip(&previous.words).enumerate().flat_map(move |(i, (now, before))| {
+			let mut bits = select(*now, *before);
+			std::iter::from_fn(move || {
+				(bits != 0).then(|| {
+					let bit = bits.trailing_zeros() as usize;
+					bits &= bits - 1;
+					(i % stride * 64 + bit, i / stride)
+				})
+			})
+		})
+	}
+
+	/// Next generation under `rule`, everything past the edges counts as dead
+	pub fn step(&self, rule: &Ruleset) -> Self {
+		let rule = Masks { born: count_mask(|n| rule.born(n)), survives: count_mask(|n| rule.survives(n)) };
+		let empty = vec![0; self.stride];
+		let height = self.height();
+		let mut words = vec![0; self.words.len()];
+		// NOTE(Simon): the six diagonal and sideways neighbor rows are shifted once per row, so both paths below
+		// only combine whole words
+		let mut shifted = vec![vec![0; self.stride]; 6];
+		for y in 0..height {
+			let above = if y > 0 { self.row(y - 1) } else { &empty };
+			let below = if y + 1 < height { self.row(y + 1) } else { &empty };
+			let row = self.row(y);
+			for (i, neighbors) in [above, row, below].iter().enumerate() {
+				shift_west(neighbors, &mut shifted[2 * i]);
+				shift_east(neighbors, &mut shifted[2 * i + 1]);
+			}
+			let neighbors = [
+				above,
+				below,
+				&shifted[0][..],
+				&shifted[1][..],
+				&shifted[2][..],
+				&shifted[3][..],
+				&shifted[4][..],
+				&shifted[5][..],
+			];
+			let next = &mut words[y * self.stride..(y + 1) * self.stride];
+			let done = simd::next_words(neighbors, row, rule, next);
+			for i in done..self.stride {
+				let mut counts = [0; 4];
+				for neighbor in &neighbors {
+					add(&mut counts, neighbor[i]);
+				}
+				next[i] = (!row[i] & matching(&counts, rule.born)) | (row[i] & matching(&counts, rule.survives));
+			}
+			if !self.width.is_multiple_of(64) {
+				next[self.stride - 1] &= (1 << (self.width % 64)) - 1;
+			}
+		}
+		Self { words, ..*self }
+	}
+}
+
+/// Neighbor counts under which cells are born and survive, see `count_mask`
+#[derive(Debug, Copy, Clone)]
+struct Masks {
+	born: u16,
+	survives: u16,
+}
+
+/// Bit `b` of every word of `shifted` becomes the western neighbor of the cell at bit `b` of `row`
+fn shift_west(row: &[u64], shifted: &mut [u64]) {
+	let mut carry = 0;
+	for (word, shifted) in row.iter().zip(shifted) {
+		*shifted = word << 1 | carry;
+		carry = word >> 63;
+	}
+}
+
+/// Bit `b` of every word of `shifted` becomes the eastern neighbor of the cell at bit `b` of `row`
+fn shift_east(row: &[u64], shifted: &mut [u64]) {
+	let mut carry = 0;
+	for (word, shifted) in row.iter().zip(shifted).rev() {
+		*shifted = word >> 1 | carry;
+		carry = word << 63;
+	}
+}
+
+/// Adds one to the bit-sliced counters wherever `bits` is set, `counts[k]` holds bit `k` of every count
+fn add(counts: &mut [u64; 4], bits: u64) {
+	let mut carry = bits;
+	for count in counts.iter_mut() {
+		let next = *count & carry;
+		*count ^= carry;
+		carry = next;
+	}
+}
+
+/// Bit `n` is set for the neighbor counts `n` that satisfy `condition`
+fn count_mask(condition: impl Fn(usize) -> bool) -> u16 {
+	(0..=8).filter(|n| condition(*n)).fold(0, |mask, n| mask | 1 << n)
+}
+
+/// Positions whose count is one of the counts in `mask`
+fn matching(counts: &[u64; 4], mask: u16) -> u64 {
+	(0..=8).filter(|n| mask >> n & 1 == 1).fold(0, |result, n| {
+		result | (0..4).fold(!0, |equal, k| equal & if n >> k & 1 == 1 { counts[k] } else { !counts[k] })
+	})
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+mod simd {
+	use std::arch::x86_64::*;
+
+	use super::Masks;
+
+	/// Computes the leading words of `next` four at a time when the CPU has AVX2, returns how many it did
+	pub fn next_words(neighbors: [&[u64]; 8], alive: &[u64], rule: Masks, next: &mut [u64]) -> usize {
+		if !is_x86_feature_detected!("avx2") {
+			return 0;
+		}
+		// SAFETY: AVX2 is available, checked above
+		unsafe { next_words_avx2(neighbors, alive, rule, next) }
+	}
+
+	#[target_feature(enable = "avx2")]
+	unsafe fn next_words_avx2(neighbors: [&[u64]; 8], alive: &[u64], rule: Masks, next: &mut [u64]) -> usize {
+		let load = |words: &[u64], i: usize| _mm256_loadu_si256(words[i..i + 4].as_ptr().cast());
+		let chunks = next.len() / 4;
+		for chunk in 0..chunks {
+			let i = chunk * 4;
+			let mut counts = [_mm256_setzero_si256(); 4];
+			for neighbor in &neighbors {
+				let mut carry = load(neighbor, i);
+				for count in &mut counts {
+					let next = _mm256_and_si256(*count, carry);
+					*count = _mm256_xor_si256(*count, carry);
+					carry = next;
+				}
+			}
+			let alive = load(alive, i);
+			let born = _mm256_andnot_si256(alive, matching(&counts, rule.born));
+			let survives = _mm256_and_si256(alive, matching(&counts, rule.survives));
+			_mm256_storeu_si256(next[i..i + 4].as_mut_ptr().cast(), _mm256_or_si256(born, survives));
+		}
+		chunks * 4
+	}
+
+	#[target_feature(enable = "avx2")]
+	fn matching(counts: &[__m256i; 4], mask: u16) -> __m256i {
+		let mut result = _mm256_setzero_si256();
+		for n in (0..=8).filter(|n| mask >> n & 1 == 1) {
+			let mut equal = _mm256_set1_epi64x(-1);
+			for (k, count) in counts.iter().enumerate() {
+				let bit = if n >> k & 1 == 1 { *count } else { _mm256_xor_si256(*count, _mm256_set1_epi64x(-1)) };
+				equal = _mm256_and_si256(equal, bit);
+			}
+			result = _mm256_or_si256(result, equal);
+		}
+		result
+	}
+}
+
+#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+mod simd {
+	use super::Masks;
+
+	/// Scalar builds leave every word to `BitBoard::step`
+	pub const fn next_words(_: [&[u64]; 8], _: &[u64], _: Masks, _: &mut [u64]) -> usize {
+		0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::conway::mix;
+
+	/// Next generation of `board` one cell at a time, like `BitBoard::step` everything past the edges is dead
+	fn naive_step(board: &Board, rule: &Ruleset) -> Board {
+		let (width, height) = (board[0].len() as isize, board.len() as isize);
+		let alive = |x: isize, y: isize| (0..width).contains(&x) && (0..height).contains(&y) && board[y as usize][x as usize].is_alive();
+		(0..height)
+			.map(|y| {
+				(0..width)
+					.map(|x| {
+						let n = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))).filter(|&(dx, dy)| (dx, dy) != (0, 0) && alive(x + dx, y + dy)).count();
+						rule.next(board[y as usize][x as usize], n)
+					})
+					.collect()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn matches_naive() {
+		let rules = [Ruleset::CONWAY, "B36/S23".parse().unwrap(), "B1357/S1357".parse().unwrap(), "B2/S".parse().unwrap()];
+		// NOTE(Simon): rows of 256 cells and more are long enough for the simd path, run with `--features simd`
+		for (i, &width) in [1_u64, 5, 63, 64, 65, 127, 200, 256, 301].iter().enumerate() {
+			for (j, rule) in rules.iter().enumerate() {
+				let seed = (i * rules.len() + j) as u64;
+				let mut board: Board = (0..17)
+					.map(|y: u64| (0..width).map(|x| if mix(seed << 40 ^ y << 20 ^ x).is_multiple_of(3) { Cell::Alive } else { Cell::Dead }).collect())
+					.collect();
+				for generation in 0..8 {
+					let mut packed = board.clone();
+					BitBoard::from_board(&board).step(rule).write(&mut packed);
+					board = naive_step(&board, rule);
+					assert!(packed == board, "{} wide board under {} differs in generation {}", width, rule, generation + 1);
+				}
+			}
+		}
+	}
+}
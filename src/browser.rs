@@ -0,0 +1,83 @@
+//! List of the built-in patterns with a thumbnail of each, drawn over the left side of the window while
+//! picking a pattern to stamp. The arrow keys move the selection, the list scrolls to keep it in view.
+
+use coffee::graphics::{Frame, Mesh, Point, Rectangle, Shape};
+
+use crate::hud::Hud;
+use crate::library;
+use crate::pattern::Pattern;
+use crate::theme::Theme;
+
+/// Height of a row of the list in window pixels, the thumbnail fits in a square a little smaller than that
+const ROW_HEIGHT: f32 = 56.0;
+const WIDTH: f32 = 320.0;
+/// Distance of the list from the top left corner of the window, and of the thumbnails from the row edges
+const INSET: f32 = 8.0;
+
+#[derive(Debug)]
+pub struct Browser {
+	patterns: Vec<Pattern>,
+}
+
+impl Default for Browser {
+	fn default() -> Self {
+		Self { patterns: (0..library::PATTERNS.len()).map(library::pattern).collect() }
+	}
+}
+
+/// Index of the first row shown of a list `rows` rows high with `selected` in view, at least one row
+const fn first_row(selected: usize, rows: usize) -> usize {
+	(selected + 1).saturating_sub(rows)
+}
+
+impl Browser {
+	/// Draws the list with the pattern at `selected` highlighted
+	pub fn draw(&self, frame: &mut Frame, hud: &mut Hud, theme: &Theme, selected: usize) {
+		let rows = (2.0f32.mul_add(-INSET, frame.height()) / ROW_HEIGHT).floor().max(1.0) as usize;
+		let first = first_row(selected, rows);
+		let shown = self.patterns.len().min(first + rows) - first;
+		let mut mesh = Mesh::new();
+		let mut names = vec![];
+		let panel = Rectangle { x: INSET, y: INSET, width: WIDTH, height: ROW_HEIGHT * shown as f32 };
+		mesh.fill(Shape::Rectangle(panel), theme.panel);
+		mesh.stroke(Shape::Rectangle(panel), theme.grid, 1.0);
+		for (row, pattern) in self.patterns.iter().enumerate().skip(first).take(shown) {
+			let top = ROW_HEIGHT.mul_add((row - first) as f32, INSET);
+			if row == selected {
+				let highlight = Rectangle { x: INSET, y: top, width: WIDTH, height: ROW_HEIGHT };
+				mesh.stroke(Shape::Rectangle(highlight), theme.text, 2.0);
+			}
+			// NOTE(Simon): every thumbnail is scaled to the same square, a gun is as large as a glider
+			let side = 2.0f32.mul_add(-INSET, ROW_HEIGHT);
+			let cell = side / pattern.width.max(pattern.height).max(1) as f32;
+			let origin = Point::new(2.0 * INSET, top + INSET);
+			for (x, y) in &pattern.alive {
+				let square = Rectangle {
+					x: cell.mul_add(*x as f32, origin.x),
+					y: cell.mul_add(*y as f32, origin.y),
+					width: cell,
+					height: cell,
+				};
+				mesh.fill(Shape::Rectangle(square), theme.cell);
+			}
+			names.push((library::name(row), Point::new(4.0f32.mul_add(INSET, side), top + ROW_HEIGHT / 2.0)));
+		}
+		mesh.draw(&mut frame.as_target());
+		for (name, position) in names {
+			hud.draw_at(frame, name, position);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn scrolls_to_selection() {
+		assert_eq!(first_row(0, 3), 0);
+		assert_eq!(first_row(2, 3), 0);
+		assert_eq!(first_row(3, 3), 1);
+		assert_eq!(first_row(6, 1), 6);
+	}
+}
@@ -0,0 +1,146 @@
+//! Which part of the board the window shows, and how large.
+//!
+//! Everything on the board is laid out in board pixels, `CELL_SIZE` per cell, and the camera maps them
+//! into window pixels.
+
+use coffee::graphics::{Point, Rectangle, Transformation, Vector};
+
+use crate::conway::{CELL_SIZE, WINDOW_SIZE_X, WINDOW_SIZE_Y};
+
+pub const MIN_ZOOM: f32 = 0.125;
+pub const MAX_ZOOM: f32 = 8.0;
+/// Zoom factor of a single step of the mouse wheel or the zoom keys
+pub const ZOOM_STEP: f32 = std::f32::consts::SQRT_2;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+	/// Board pixel in the top left corner of the window
+	pub position: Point,
+	/// Window pixels per board pixel
+	pub zoom: f32,
+	/// Size of the window in window pixels
+	pub window: (f32, f32),
+}
+
+impl Camera {
+	/// Camera at zoom 1 showing the middle of a board of `size` cells
+	pub fn centered(size: (usize, usize)) -> Self {
+		let mut camera = Self {
+			position: Point::new(0.0, 0.0),
+			zoom: 1.0,
+			window: (WINDOW_SIZE_X as f32, WINDOW_SIZE_Y as f32),
+		};
+		let (width, height) = camera.extent();
+		let (x, y) = board_pixels(size);
+		camera.position = Point::new((x - width) / 2.0, (y - height) / 2.0);
+		camera
+	}
+
+	/// Camera at `zoom` whose window is centered on the cell `center`, which may be fractional
+	pub fn framing(zoom: f32, center: (f32, f32)) -> Self {
+		let mut camera = Self {
+			position: Point::new(0.0, 0.0),
+			zoom,
+			window: (WINDOW_SIZE_X as f32, WINDOW_SIZE_Y as f32),
+		};
+		let (width, height) = camera.extent();
+		let cell = CELL_SIZE as f32;
+		camera.position = Point::new(center.0.mul_add(cell, -width / 2.0), center.1.mul_add(cell, -height / 2.0));
+		camera
+	}
+
+	/// Whether the window shows any part of a board of `size` cells
+	pub fn overlaps(&self, size: (usize, usize)) -> bool {
+		let (width, height) = self.extent();
+		let (x, y) = board_pixels(size);
+		self.position.x < x && self.position.y < y && self.position.x + width > 0.0 && self.position.y + height > 0.0
+	}
+
+	/// Maps board pixels to window pixels
+	pub fn transformation(&self) -> Transformation {
+		Transformation::scale(self.zoom) * Transformation::translate(Vector::new(-self.position.x, -self.position.y))
+	}
+
+	/// Board pixel under the window pixel `p`
+	pub fn board_point(&self, p: Point) -> Point {
+		Point::new(self.position.x + p.x / self.zoom, self.position.y + p.y / self.zoom)
+	}
+
+	/// Middle of the window in window pixels
+	pub fn window_center(&self) -> Point {
+		Point::new(self.window.0 / 2.0, self.window.1 / 2.0)
+	}
+
+	/// Size of the window in board pixels
+	fn extent(&self) -> (f32, f32) {
+		(self.window.0 / self.zoom, self.window.1 / self.zoom)
+	}
+
+	/// Whether all of a board of `size` cells fits in the window
+	pub fn fits(&self, size: (usize, usize)) -> bool {
+		let (width, height) = self.extent();
+		let (x, y) = board_pixels(size);
+		x <= width && y <= height
+	}
+
+	/// Adapts to a resized window: the zoom scales with it, so the board shows at least as much as before
+	/// along both axes, and the middle of the window stays on the same board pixel
+	pub fn resize(&mut self, window: (f32, f32)) {
+		if window.0 < 1.0 || window.1 < 1.0 {
+			// NOTE(Simon): minimized, there is nothing to show and scaling down to zero would lose the zoom
+			return;
+		}
+		let scale = (window.0 / self.window.0).min(window.1 / self.window.1);
+		let middle = self.board_point(self.window_center());
+		self.window = window;
+		self.zoom = (self.zoom * scale).clamp(MIN_ZOOM, MAX_ZOOM);
+		let center = self.window_center();
+		self.position = Point::new(middle.x - center.x / self.zoom, middle.y - center.y / self.zoom);
+	}
+
+	/// Cells of a board of `size` that are at least partly inside the window
+	pub fn visible(&self, (width, height): (usize, usize)) -> Rectangle<usize> {
+		// NOTE(Simon): one more cell on every side, hex rows stick out by half a cell
+		let (w, h) = self.extent();
+		let cell = CELL_SIZE as f32;
+		let span = |start: f32, length: f32, size: usize| {
+			let first = ((start / cell).floor() - 1.0).max(0.0) as usize;
+			let last = (((start + length) / cell).ceil() + 1.0).max(0.0) as usize;
+			(first.min(size), last.min(size))
+		};
+		let (left, right) = span(self.position.x, w, width);
+		let (top, bottom) = span(self.position.y, h, height);
+		Rectangle { x: left, y: top, width: right - left, height: bottom - top }
+	}
+
+	/// Keeps the window on a board of `size` cells, or centers the board once all of it fits. Returns how far
+	/// the camera had to be moved back, in board pixels.
+	pub fn clamp(&mut self, size: (usize, usize)) -> Vector {
+		let clamp = |position: f32, extent: f32, board: f32| {
+			let clamped = if board <= extent { (board - extent) / 2.0 } else { position.clamp(0.0, board - extent) };
+			(clamped, position - clamped)
+		};
+		let (width, height) = self.extent();
+		let (board_x, board_y) = board_pixels(size);
+		let (x, overflow_x) = clamp(self.position.x, width, board_x);
+		let (y, overflow_y) = clamp(self.position.y, height, board_y);
+		self.position = Point::new(x, y);
+		Vector::new(overflow_x, overflow_y)
+	}
+
+	/// Zooms by `steps` of `ZOOM_STEP`, the board pixel under the window pixel `anchor` stays put
+	pub fn zoom_by(&mut self, anchor: Point, steps: i32) {
+		self.zoom_to(anchor, self.zoom * ZOOM_STEP.powi(steps));
+	}
+
+	/// Sets the zoom, the board pixel under the window pixel `anchor` stays put
+	pub fn zoom_to(&mut self, anchor: Point, zoom: f32) {
+		let fixed = self.board_point(anchor);
+		self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+		self.position = Point::new(fixed.x - anchor.x / self.zoom, fixed.y - anchor.y / self.zoom);
+	}
+}
+
+const fn board_pixels((width, height): (usize, usize)) -> (f32, f32) {
+	((width * CELL_SIZE) as f32, (height * CELL_SIZE) as f32)
+}
@@ -0,0 +1,522 @@
+use anyhow::{anyhow, bail, Context, Result};
+use coffee::graphics::Color;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::camera::{Camera, MAX_ZOOM, MIN_ZOOM};
+use crate::conway::{Boundary, Cell, Conway, Topology, DEFAULT_BOARD_SIZE, DEFAULT_DRAG_THRESHOLD, DEFAULT_GRID_WIDTH, DEFAULT_JUMP, DEFAULT_MAX_GENS_PER_FRAME, DEFAULT_RECORD_GENERATIONS, DEFAULT_RECORD_PATH, DEFAULT_SAVE_PATH, DEFAULT_SCREENSHOT_PATH, DEFAULT_SNAP_RADIUS, DEFAULT_SOUP_DENSITY, MAX_BOARD_SIDE, MAX_JUMP, MAX_LIFE_COLORS, MAX_SCREENSHOT_PIXELS, MAX_SCREENSHOT_SCALE};
+use crate::golly::GollyRule;
+use crate::hashlife;
+use crate::pattern::{self, Macrocell, Pattern};
+use crate::rule::{self, Kernel, LargerThanLife, RuleTable, Ruleset};
+use crate::session::{self, RuleSources, SavedSession};
+use crate::share;
+use crate::theme::{self, Theme, THEMES};
+use crate::turmite::Turmite;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Startup options parsed from the command line.
+#[derive(Debug, Clone)]
+pub struct Config {
+	/// Width of the border (in cells) that is always dead and never simulated
+	pub margin: usize,
+	/// Width and height of the board in cells, the session's or what fits in the window when not given
+	pub board_size: Option<(usize, usize)>,
+	/// Window pixels per board pixel to start with
+	pub zoom: f32,
+	/// Cell the window starts centered on, the middle of the board along an axis that is not given
+	pub pan: (Option<f32>, Option<f32>),
+	/// Simulate on an unbounded plane the board is a window onto
+	pub infinite: bool,
+	/// Store the unbounded plane as a HashLife quadtree, implies `infinite`
+	pub hashlife: bool,
+	/// Generations a jump skips
+	pub jump: u64,
+	/// Simulate this many generations without opening a window, then print the board and the report
+	pub headless: Option<u64>,
+	/// Board to start with, as printed by the share key
+	pub share_code: Option<String>,
+	/// Pattern placed on the starting board
+	pub pattern: Option<Pattern>,
+	/// Macrocell pattern the HashLife plane starts with instead, it may be far larger than the board
+	pub macrocell: Option<Macrocell>,
+	/// Top left corner of the pattern, centered when not given
+	pub pattern_at: Option<(usize, usize)>,
+	/// Print the board checksum to stdout after every generation
+	pub print_checksums: bool,
+	/// Draw dead cells on a faint checkerboard instead of a flat background
+	pub checkerboard: bool,
+	/// Draw the cells as a single texture, uploaded every frame, instead of a shape per cell
+	pub texture: bool,
+	/// Briefly draw newborn cells larger than full size
+	pub pop: bool,
+	/// Color live cells by how long they have been alive
+	pub ages: bool,
+	/// Draw fading trails where cells died in the last few generations
+	pub trails: bool,
+	/// Colors of the board and the text, one of `THEMES`
+	pub theme: &'static Theme,
+	/// Start with the theme's colors inverted
+	pub dark: bool,
+	/// Draw blocks of cells colored by their share of live cells instead of the cells themselves
+	pub heatmap: bool,
+	/// Draw the grid lines between cells
+	pub grid: bool,
+	/// Color of the grid lines instead of the theme's
+	pub grid_color: Option<Color>,
+	/// Width of the grid lines in window pixels
+	pub grid_width: f32,
+	/// Birth/survival rule to start with, the neighborhood's default rule when not given
+	pub rule: Option<Ruleset>,
+	/// Lookup-table rule used instead of the birth/survival rule
+	pub rule_table: Option<RuleTable>,
+	/// Neighbor offsets used instead of the topology's neighborhood
+	pub kernel: Option<Kernel>,
+	/// Neighborhood to start with, also set by a `--rule` ending in `H` or `V`
+	pub topology: Option<Topology>,
+	/// Edge handling to start with, the session's or dead when not set
+	pub boundary: Option<Boundary>,
+	/// Number of colors live cells come in, 2 for Immigration and 4 for QuadLife
+	pub colors: u8,
+	/// Chance that a cell the rule gives birth to is actually born
+	pub birth_chance: f32,
+	/// Chance that a cell the rule keeps alive actually survives
+	pub survive_chance: f32,
+	/// Seed for the birth and survival rolls, picked from the clock when not given
+	pub seed: Option<u64>,
+	/// Multi-state rule from a Golly `.rule` file, used instead of the birth/survival rule
+	pub rule_file: Option<GollyRule>,
+	/// Where `rule_table`, `rule_file` and `kernel` were read from
+	pub rule_sources: RuleSources,
+	/// Larger than Life rule used instead of the birth/survival rule, also given with `--rule`
+	pub ltl: Option<LargerThanLife>,
+	/// Requested multisample count for the window
+	pub msaa: Option<u16>,
+	/// How far (in cells) the snapping cursor looks for a live cell
+	pub snap_radius: usize,
+	/// How far (in pixels) the cursor may move during a click before it paints a stroke
+	pub drag_threshold: f32,
+	/// Share of live cells in random fills and soups
+	pub soup_density: f32,
+	/// Most generations simulated in a single update, however high the speed
+	pub max_gens_per_frame: u64,
+	/// Where to write the run report, which is then also written on exit
+	pub report_path: Option<PathBuf>,
+	/// Where the save key writes the board, the extension picks the format
+	pub save_path: PathBuf,
+	/// Where the screenshot key writes a PNG of the board
+	pub screenshot_path: PathBuf,
+	/// Pixels along each side of a cell in screenshots and recordings
+	pub screenshot_scale: usize,
+	/// Rows and columns of copies of the board a screenshot is made of
+	pub tile_export: Option<(usize, usize)>,
+	/// Where the record key writes an animated GIF of the simulation
+	pub record_path: PathBuf,
+	/// Generations a recording captures before it stops by itself
+	pub record_generations: u64,
+	/// Keep painting enabled while the simulation runs
+	pub sandbox: bool,
+	/// Start in Wireworld instead of the birth/survival rule
+	pub wireworld: bool,
+	/// Start with one ant following this turmite rule in the middle of the board
+	pub turmite: Option<Turmite>,
+	/// File the session is saved to periodically and on exit
+	pub session_path: Option<PathBuf>,
+	/// Session read from `session_path` at startup, if the file existed
+	pub restored: Option<SavedSession>,
+	/// Socket, named pipe or file every generation is streamed to
+	pub stream_path: Option<PathBuf>,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			margin: 0,
+			board_size: None,
+			zoom: 1.0,
+			pan: (None, None),
+			infinite: false,
+			hashlife: false,
+			jump: DEFAULT_JUMP,
+			headless: None,
+			share_code: None,
+			pattern: None,
+			macrocell: None,
+			pattern_at: None,
+			print_checksums: false,
+			checkerboard: false,
+			texture: false,
+			pop: false,
+			ages: false,
+			trails: false,
+			theme: &THEMES[0],
+			dark: false,
+			heatmap: false,
+			grid: true,
+			grid_color: None,
+			grid_width: DEFAULT_GRID_WIDTH,
+			rule: None,
+			rule_table: None,
+			kernel: None,
+			topology: None,
+			boundary: None,
+			colors: 1,
+			birth_chance: 1.0,
+			survive_chance: 1.0,
+			seed: None,
+			rule_file: None,
+			rule_sources: RuleSources::default(),
+			ltl: None,
+			msaa: None,
+			snap_radius: DEFAULT_SNAP_RADIUS,
+			drag_threshold: DEFAULT_DRAG_THRESHOLD,
+			soup_density: DEFAULT_SOUP_DENSITY,
+			max_gens_per_frame: DEFAULT_MAX_GENS_PER_FRAME,
+			report_path: None,
+			save_path: PathBuf::from(DEFAULT_SAVE_PATH),
+			screenshot_path: PathBuf::from(DEFAULT_SCREENSHOT_PATH),
+			screenshot_scale: 1,
+			tile_export: None,
+			record_path: PathBuf::from(DEFAULT_RECORD_PATH),
+			record_generations: DEFAULT_RECORD_GENERATIONS,
+			sandbox: false,
+			wireworld: false,
+			turmite: None,
+			session_path: None,
+			restored: None,
+			stream_path: None,
+		}
+	}
+}
+
+impl Config {
+	pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self> {
+		let mut config = Self::default();
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"--margin" => config.margin = parse_value(&arg, args.next())?,
+				"--board" => {
+					let size: String = parse_value(&arg, args.next())?;
+					config.board_size = Some(session::parse_size(&size).context("invalid --board")?);
+				}
+				"--zoom" => config.zoom = parse_value(&arg, args.next())?,
+				"--pan-x" => config.pan.0 = Some(parse_value(&arg, args.next())?),
+				"--pan-y" => config.pan.1 = Some(parse_value(&arg, args.next())?),
+				"--infinite" => config.infinite = true,
+				"--hashlife" => config.hashlife = true,
+				"--jump" => config.jump = parse_value(&arg, args.next())?,
+				"--headless" => config.headless = Some(parse_value(&arg, args.next())?),
+				"--checkerboard" => config.checkerboard = true,
+				"--texture" => config.texture = true,
+				"--hex" => config.topology = Some(Topology::Hex),
+				"--von-neumann" => config.topology = Some(Topology::VonNeumann),
+				"--wrap" => config.boundary = Some(Boundary::Wrap),
+				"--mirror" => config.boundary = Some(Boundary::Mirror),
+				"--pop" => config.pop = true,
+				"--ages" => config.ages = true,
+				"--trails" => config.trails = true,
+				"--dark" => config.dark = true,
+				"--heatmap" => config.heatmap = true,
+				"--no-grid" => config.grid = false,
+				"--grid-color" => {
+					let color: String = parse_value(&arg, args.next())?;
+					config.grid_color = Some(theme::parse_color(&color).context("invalid --grid-color")?);
+				}
+				"--grid-width" => config.grid_width = parse_value(&arg, args.next())?,
+				"--theme" => {
+					let name: String = parse_value(&arg, args.next())?;
+					config.theme = Theme::named(&name).ok_or_else(|| {
+						let names: Vec<_> = THEMES.iter().map(|theme| theme.name).collect();
+						anyhow!("unknown --theme `{}`, the themes are {}", name, names.join(", "))
+					})?;
+				}
+				"--rule" => {
+					let rule: String = parse_value(&arg, args.next())?;
+					let (rule, topology) = rule::strip_topology(&rule);
+					config.topology = topology.or(config.topology);
+					if LargerThanLife::matches(rule) {
+						config.ltl = Some(rule.parse().with_context(|| format!("invalid rule `{}`", rule))?);
+					} else {
+						config.rule = Some(rule.parse().with_context(|| format!("invalid rule `{}`", rule))?);
+					}
+				}
+				"--rule-table" => {
+					let (path, table) = session::read(&parse_value::<String>(&arg, args.next())?, "rule table")?;
+					config.rule_table = Some(table);
+					config.rule_sources.rule_table = Some(path);
+				}
+				"--colors" => config.colors = parse_value(&arg, args.next())?,
+				"--birth-chance" => config.birth_chance = parse_value(&arg, args.next())?,
+				"--survive-chance" => config.survive_chance = parse_value(&arg, args.next())?,
+				"--seed" => config.seed = Some(parse_value(&arg, args.next())?),
+				"--rule-file" => {
+					let (path, rule) = session::read(&parse_value::<String>(&arg, args.next())?, "rule file")?;
+					config.rule_file = Some(rule);
+					config.rule_sources.rule_file = Some(path);
+				}
+				"--kernel" => {
+					let (path, kernel) = session::read(&parse_value::<String>(&arg, args.next())?, "kernel")?;
+					config.kernel = Some(kernel);
+					config.rule_sources.kernel = Some(path);
+				}
+				"--msaa" => config.msaa = Some(parse_value(&arg, args.next())?),
+				"--snap-radius" => config.snap_radius = parse_value(&arg, args.next())?,
+				"--drag-threshold" => config.drag_threshold = parse_value(&arg, args.next())?,
+				"--soup-density" => config.soup_density = parse_value(&arg, args.next())?,
+				"--max-gens-per-frame" => config.max_gens_per_frame = parse_value(&arg, args.next())?,
+				"--report" => config.report_path = Some(parse_value(&arg, args.next())?),
+				"--save" => config.save_path = parse_value(&arg, args.next())?,
+				"--screenshot" => config.screenshot_path = parse_value(&arg, args.next())?,
+				"--screenshot-scale" => config.screenshot_scale = parse_value(&arg, args.next())?,
+				"--tile-export" => {
+					let tiles: String = parse_value(&arg, args.next())?;
+					let (rows, columns) =
+						tiles.split_once('x').ok_or_else(|| anyhow!("--tile-export expects `<rows>x<columns>`, got `{}`", tiles))?;
+					config.tile_export = Some((parse_value(&arg, Some(rows.into()))?, parse_value(&arg, Some(columns.into()))?));
+				}
+				"--record" => config.record_path = parse_value(&arg, args.next())?,
+				"--record-generations" => config.record_generations = parse_value(&arg, args.next())?,
+				"--sandbox" => config.sandbox = true,
+				"--wireworld" => config.wireworld = true,
+				"--turmite" => {
+					let rule: String = parse_value(&arg, args.next())?;
+					config.turmite = Some(rule.parse().with_context(|| format!("invalid turmite rule `{}`", rule))?);
+				}
+				"--session" => {
+					let path: PathBuf = parse_value(&arg, args.next())?;
+					if path.exists() {
+						let session = std::fs::read_to_string(&path).with_context(|| format!("could not read {}", path.display()))?;
+						config.restored = Some(session.parse().with_context(|| format!("invalid session {}", path.display()))?);
+					}
+					config.session_path = Some(path);
+				}
+				"--stream" => config.stream_path = Some(parse_value(&arg, args.next())?),
+				"--print-checksums" => config.print_checksums = true,
+				"--load" => {
+					let path: PathBuf = parse_value(&arg, args.next())?;
+					(config.pattern, config.macrocell) = match pattern::read_macrocell(&path)? {
+						Some(tree) => (None, Some(tree)),
+						None => (Some(pattern::read(&path)?), None),
+					};
+				}
+				"--at" => {
+					let at: String = parse_value(&arg, args.next())?;
+					let (x, y) = at.split_once(',').ok_or_else(|| anyhow!("--at expects `<x>,<y>`, got `{}`", at))?;
+					config.pattern_at = Some((parse_value(&arg, Some(x.into()))?, parse_value(&arg, Some(y.into()))?));
+				}
+				"--share" => config.share_code = Some(parse_value(&arg, args.next())?),
+				_ => bail!("unknown argument `{}`", arg),
+			}
+		}
+		if let Some(tree) = config.macrocell.take() {
+			// NOTE(Simon): HashLife takes over the quadtree as it is, everything else needs its cells on the board
+			match (config.hashlife, Conway::plane_blocker(&config)) {
+				(true, None) if tree.level() > hashlife::MAX_LEVEL => {
+					bail!("invalid --load: the pattern is level {}, HashLife takes at most level {}", tree.level(), hashlife::MAX_LEVEL);
+				}
+				(true, None) => config.macrocell = Some(tree),
+				(true, Some(reason)) => {
+					let context = || format!("invalid --load, HashLife does not support {} so the pattern has to fit on the board", reason);
+					config.pattern = Some(tree.to_pattern().with_context(context)?);
+				}
+				(false, _) => config.pattern = Some(tree.to_pattern().context("invalid --load, --hashlife loads larger patterns")?),
+			}
+		}
+		config.validate()?;
+		Ok(config)
+	}
+
+	fn validate(&self) -> Result<()> {
+		let (width, height) = self.board_size();
+		if width.max(height) > MAX_BOARD_SIDE {
+			bail!("a {}x{} board is too large, sides can be at most {} cells", width, height, MAX_BOARD_SIDE);
+		}
+		if 2 * self.margin >= width.min(height) {
+			bail!("--margin {} leaves no cells to simulate", self.margin);
+		}
+		if !(MIN_ZOOM..=MAX_ZOOM).contains(&self.zoom) {
+			bail!("--zoom must be between {} and {}", MIN_ZOOM, MAX_ZOOM);
+		}
+		if !self.camera().overlaps((width, height)) {
+			bail!("--pan-x and --pan-y move the window off the board");
+		}
+		if self.snap_radius > width.max(height) {
+			bail!("--snap-radius can be at most {}, the longer side of the board", width.max(height));
+		}
+		if self.drag_threshold.is_nan() || self.drag_threshold < 0.0 {
+			bail!("--drag-threshold must not be negative");
+		}
+		if !(1..=MAX_SCREENSHOT_SCALE).contains(&self.screenshot_scale) {
+			bail!("--screenshot-scale must be between 1 and {}", MAX_SCREENSHOT_SCALE);
+		}
+		if let Some((rows, columns)) = self.tile_export {
+			if rows == 0 || columns == 0 {
+				bail!("--tile-export needs at least one row and one column");
+			}
+			if self.boundary.or_else(|| self.restored.as_ref().map(|session| session.boundary)) != Some(Boundary::Wrap) {
+				bail!("--tile-export needs --wrap, copies of a board with other edges don't line up");
+			}
+			let pixels = (width * self.screenshot_scale).saturating_mul(height * self.screenshot_scale);
+			if pixels.saturating_mul(rows).saturating_mul(columns) > MAX_SCREENSHOT_PIXELS {
+				bail!("--tile-export {}x{} makes an image too large to fit in memory", rows, columns);
+			}
+		}
+		if self.record_generations == 0 {
+			bail!("--record-generations must be at least 1");
+		}
+		if !self.grid_width.is_finite() || self.grid_width <= 0.0 {
+			bail!("--grid-width must be positive");
+		}
+		if (self.rule.is_some() || self.ltl.is_some()) && self.rule_table.is_some() {
+			bail!("--rule and --rule-table can't be combined");
+		}
+		if self.rule_file.is_some() && (self.rule.is_some() || self.ltl.is_some() || self.rule_table.is_some()) {
+			bail!("--rule-file can't be combined with --rule or --rule-table");
+		}
+		for (flag, chance) in [
+			("--birth-chance", self.birth_chance),
+			("--survive-chance", self.survive_chance),
+			("--soup-density", self.soup_density),
+		] {
+			if !(0.0..=1.0).contains(&chance) {
+				bail!("{} must be between 0 and 1", flag);
+			}
+		}
+		if !(1..=MAX_LIFE_COLORS as u8).contains(&self.colors) {
+			bail!("--colors must be between 1 and {}", MAX_LIFE_COLORS);
+		}
+		if !(1..=MAX_JUMP).contains(&self.jump) {
+			bail!("--jump must be between 1 and {}", MAX_JUMP);
+		}
+		if self.max_gens_per_frame == 0 {
+			bail!("--max-gens-per-frame must be at least 1");
+		}
+		if let Some(samples) = self.msaa {
+			if !samples.is_power_of_two() || samples > 16 {
+				bail!("--msaa expects 1, 2, 4, 8 or 16 samples, got {}", samples);
+			}
+		}
+		if let Some(code) = &self.share_code {
+			Conway::from_share_code(code, (width, height)).context("invalid --share code")?;
+		}
+		if let Some(session) = &self.restored {
+			share::decode_states(&session.board, (width, height)).context("invalid board in --session")?;
+		}
+		pattern::check_format(&self.save_path).context("invalid --save")?;
+		if self.headless.is_some() && self.wireworld {
+			bail!("--headless can't print Wireworld boards, patterns only keep live cells");
+		}
+		if self.pattern_at.is_some() && self.pattern.is_none() && self.macrocell.is_none() {
+			bail!("--at needs a pattern to place, pass one with --load");
+		}
+		if let (Some(pattern), Some((x, y))) = (&self.pattern, self.pattern_origin()) {
+			pattern.place(&mut vec![vec![Cell::Dead; width]; height], x, y).context("invalid --load")?;
+		}
+		Ok(())
+	}
+
+	/// Camera to start with, without `--zoom` and `--pan-*` the same as `Camera::centered`
+	pub fn camera(&self) -> Camera {
+		let (width, height) = self.board_size();
+		let center = (self.pan.0.unwrap_or(width as f32 / 2.0), self.pan.1.unwrap_or(height as f32 / 2.0));
+		Camera::framing(self.zoom, center)
+	}
+
+	/// Size of the starting board
+	pub fn board_size(&self) -> (usize, usize) {
+		self.board_size.or_else(|| self.restored.as_ref().map(|session| session.size)).unwrap_or(DEFAULT_BOARD_SIZE)
+	}
+
+	/// Where the top left corner of `--load`'s pattern goes
+	pub fn pattern_origin(&self) -> Option<(usize, usize)> {
+		let pattern = self.pattern.as_ref()?;
+		let (width, height) = self.board_size();
+		Some(self.pattern_at.unwrap_or_else(|| (width.saturating_sub(pattern.width) / 2, height.saturating_sub(pattern.height) / 2)))
+	}
+
+	/// Where the top left corner of `--load`'s macrocell pattern goes on the HashLife plane, the board's top left
+	/// corner being 0,0. Unlike `pattern_origin` a pattern larger than the board stays centered.
+	pub fn macrocell_origin(&self) -> Option<(i64, i64)> {
+		let (width, height) = self.macrocell.as_ref()?.size();
+		let board = self.board_size();
+		let centered = ((board.0 as i64 - width as i64) / 2, (board.1 as i64 - height as i64) / 2);
+		Some(self.pattern_at.map_or(centered, |(x, y)| (x as i64, y as i64)))
+	}
+
+	/// Makes the config available to `Conway::load`, which coffee calls without arguments
+	pub fn install(self) {
+		CONFIG.set(self).expect("config installed twice");
+	}
+
+	pub fn get() -> &'static Self {
+		CONFIG.get_or_init(Self::default)
+	}
+}
+
+fn parse_value<T>(flag: &str, value: Option<String>) -> Result<T>
+where
+	T: std::str::FromStr,
+	T::Err: std::error::Error + Send + Sync + 'static,
+{
+	let value = value.ok_or_else(|| anyhow!("{} expects a value", flag))?;
+	value
+		.parse()
+		.with_context(|| format!("invalid value `{}` for {}", value, flag))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::conway::CELL_SIZE;
+
+	fn args(args: &[&str]) -> impl Iterator<Item = String> {
+		args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter()
+	}
+
+	#[test]
+	fn large_macrocell() {
+		let far = 1 << 16;
+		let pattern = Pattern { width: far + 1, height: far + 1, alive: vec![(0, 0), (1, 0), (0, 1), (1, 1), (far, far)] };
+		let path = std::env::temp_dir().join(format!("vitae-large-{}.mc", std::process::id()));
+		std::fs::write(&path, pattern.to_macrocell(None)).unwrap();
+		let path = path.to_str().unwrap();
+		let config = Config::from_args(args(&["--hashlife", "--load", path])).unwrap();
+		assert!(config.macrocell.is_some() && config.pattern.is_none());
+		assert_eq!(Conway::from_config(&config).population(), 5);
+		// NOTE(Simon): without HashLife, or with a rule it can't run, the cells have to fit on the board
+		assert!(Config::from_args(args(&["--load", path])).is_err());
+		assert!(Config::from_args(args(&["--hashlife", "--rule", "B36/S23/C3", "--load", path])).is_err());
+		std::fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn snap_radius() {
+		assert_eq!(Config::from_args(args(&["--board", "40x30", "--snap-radius", "40"])).unwrap().snap_radius, 40);
+		assert!(Config::from_args(args(&["--board", "40x30", "--snap-radius", "41"])).is_err());
+		assert!(Config::from_args(args(&["--snap-radius", &usize::MAX.to_string()])).is_err());
+	}
+
+	#[test]
+	fn tile_export() {
+		let config = Config::from_args(args(&["--wrap", "--tile-export", "2x3"])).unwrap();
+		assert_eq!(config.tile_export, Some((2, 3)));
+		assert!(Config::from_args(args(&["--tile-export", "2x3"])).is_err());
+		assert!(Config::from_args(args(&["--wrap", "--tile-export", "0x3"])).is_err());
+		assert!(Config::from_args(args(&["--wrap", "--tile-export", "2"])).is_err());
+		assert!(Config::from_args(args(&["--wrap", "--board", "1000x1000", "--tile-export", "100x100"])).is_err());
+	}
+
+	#[test]
+	fn zoom_and_pan() {
+		let config = Config::from_args(args(&["--board", "100x80"])).unwrap();
+		assert_eq!(config.camera(), Camera::centered((100, 80)));
+		let config = Config::from_args(args(&["--board", "100x80", "--zoom", "2", "--pan-x", "10", "--pan-y", "20.5"])).unwrap();
+		let camera = config.camera();
+		let middle = camera.board_point(camera.window_center());
+		assert_eq!((camera.zoom, middle.x, middle.y), (2.0, 10.0 * CELL_SIZE as f32, 20.5 * CELL_SIZE as f32));
+		assert!(Config::from_args(args(&["--zoom", "100"])).is_err());
+		assert!(Config::from_args(args(&["--board", "100x80", "--zoom", "8", "--pan-x", "-10"])).is_err());
+		assert!(Config::from_args(args(&["--board", "100x80", "--pan-y", "1000"])).is_err());
+	}
+}
@@ -1,20 +1,252 @@
-use coffee::graphics::{Color, Frame, Mesh, Point, Rectangle, Shape, Window};
+use coffee::graphics::{Color, Frame, Image, Mesh, Point, Quad, Rectangle, Shape, Transformation, Vector, Window};
 use coffee::input::keyboard::KeyCode;
 use coffee::input::{self, keyboard, mouse, ButtonState, Input};
-use coffee::load::Task;
+use coffee::load::{Join, Task};
 use coffee::{Game, Timer};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::bitboard::BitBoard;
+use crate::camera::Camera;
+use crate::config::Config;
+use crate::export::{self, Recording};
+use crate::golly::GollyRule;
+use crate::hud::Hud;
+use crate::library;
+use crate::meter::RateMeter;
+use crate::browser::Browser;
+use crate::minimap::{self, Minimap};
+use crate::pattern::{self, Pattern};
+use crate::report::{RunStats, Session};
+use crate::rule::{self, Kernel, LargerThanLife, Reach, RuleTable, Ruleset};
+use crate::session::{RuleSources, SavedSession};
+use crate::share;
+use crate::hashlife::HashLife;
+use crate::sparse::{Plane, SparseBoard};
+use crate::stream::Stream;
+use crate::theme::{self, Theme};
+use crate::transform::{Symmetry, Transform};
+use crate::turmite::{self, Ant, Turmite};
 
 pub const WINDOW_SIZE_X: usize = 1024;
 pub const WINDOW_SIZE_Y: usize = WINDOW_SIZE_X;
 
 pub const CELL_SIZE: usize = 10;
 
-const CELL_COUNT_X: usize = WINDOW_SIZE_X / CELL_SIZE;
-const CELL_COUNT_Y: usize = WINDOW_SIZE_Y / CELL_SIZE;
+/// Whole cells that fit in the window, the board behind it can be any size
+pub const VIEW_CELLS_X: usize = WINDOW_SIZE_X / CELL_SIZE;
+pub const VIEW_CELLS_Y: usize = WINDOW_SIZE_Y / CELL_SIZE;
+
+/// Board size without `--board`, exactly what fits in the window
+pub const DEFAULT_BOARD_SIZE: (usize, usize) = (VIEW_CELLS_X, VIEW_CELLS_Y);
+/// Longest side `--board` accepts, a bigger board takes too long to simulate anyway
+pub const MAX_BOARD_SIDE: usize = 4096;
+
+/// Generations a jump skips without `--jump`
+pub const DEFAULT_JUMP: u64 = 1_000_000;
+/// Longest jump `--jump` accepts, the plane's coordinates would overflow far beyond it
+pub const MAX_JUMP: u64 = 1 << 40;
+
+/// Largest brush, in cells from its center to its edge
+const MAX_BRUSH_RADIUS: usize = 12;
+/// Most edits that can be undone
+const UNDO_LIMIT: usize = 100;
+/// Cells all undo snapshots together may hold, large boards keep fewer of them
+const UNDO_CELLS: usize = 1 << 25;
+/// Most generations that can be rewound
+const HISTORY_LIMIT: usize = 256;
+/// Cells the rewind history may hold, large boards can rewind fewer generations
+const HISTORY_CELLS: usize = 1 << 25;
+/// Window pixels moved by one press of an arrow key
+const PAN_STEP: f32 = 100.0;
+/// The active cells are only simulated on their own while their neighborhoods cover less than this share of
+/// the board, see `Conway::remember_changes`
+const ACTIVE_SHARE: usize = 4;
+/// Side of the squares of cells whose meshes are kept between frames
+const RENDER_CHUNK: usize = 32;
+/// Grid lines are left out once cells are drawn smaller than this many window pixels
+const MIN_GRID_CELL: f32 = 4.0;
+/// Cells drawn at least this many window pixels large get a fully opaque grid, below it fades out towards
+/// `MIN_GRID_CELL`
+const GRID_FADE_CELL: f32 = 12.0;
+/// Smallest side of the blocks the heatmap counts live cells in, in window pixels
+const HEATMAP_BLOCK: f32 = 8.0;
+/// Width of the grid lines in window pixels without `--grid-width`
+pub const DEFAULT_GRID_WIDTH: f32 = 1.0;
+/// Generations the population graph goes back, one window pixel each
+const GRAPH_LENGTH: usize = 240;
+/// Top left corner and height of the population graph in window pixels, below the status line
+const GRAPH_ORIGIN: (f32, f32) = (8.0, 36.0);
+const GRAPH_HEIGHT: f32 = 60.0;
+
+/// Share of each tick spent simulating, the rest is left for input and drawing
+const TICK_BUDGET: f32 = 0.8;
+/// Generations per second to start with
+const DEFAULT_SPEED: f32 = 10.0;
+/// Longest stretch of time a single update catches up on, so the simulation doesn't race ahead after the window
+/// was dragged or the process stopped
+const MAX_CATCH_UP: Duration = Duration::from_millis(250);
+/// Slowest and fastest simulation the speed keys reach, in generations per second
+const MIN_SPEED: f32 = 0.625;
+const MAX_SPEED: f32 = 10240.0;
+
+/// Upper bound on generations per update, so a cheap board can't keep a single update busy for the whole budget
+pub const DEFAULT_MAX_GENS_PER_FRAME: u64 = 10_000;
+
+pub const DEFAULT_SNAP_RADIUS: usize = 2;
+pub const DEFAULT_DRAG_THRESHOLD: f32 = 3.0;
+/// Share of live cells in random fills and soups without `--soup-density`
+pub const DEFAULT_SOUP_DENSITY: f32 = 0.5;
+pub const DEFAULT_REPORT_PATH: &str = "report.json";
+pub const DEFAULT_SAVE_PATH: &str = "pattern.rle";
+pub const DEFAULT_SCREENSHOT_PATH: &str = "board.png";
+pub const MAX_SCREENSHOT_SCALE: usize = 16;
+pub const DEFAULT_RECORD_PATH: &str = "evolution.gif";
+pub const DEFAULT_RECORD_GENERATIONS: u64 = 100;
+/// Shortest time a recorded frame is shown for in hundredths of a second, many viewers slow down shorter ones
+const MIN_FRAME_DELAY: u16 = 2;
+/// Screenshots larger than this many pixels are refused, they would not fit in memory
+pub const MAX_SCREENSHOT_PIXELS: usize = 1 << 26;
+
+/// How many generations a marker for a cell lost at the edge stays visible
+const LOST_MARKER_GENERATIONS: u64 = 8;
+
+/// How often `--session` is saved while running, it is also saved on exit
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a notice replaces the status line
+const NOTICE_DURATION: Duration = Duration::from_secs(2);
+
+/// One bookmark per number key
+const MAX_BOOKMARKS: usize = 9;
+/// Cells all bookmarks together may hold, large boards keep fewer of them
+const BOOKMARK_CELLS: usize = 1 << 25;
+
+/// How much larger than a regular cell a newborn cell is drawn at the start of its tick
+const POP_SCALE: f32 = 0.4;
+
+const REGION_COLOR: Color = Color {
+	r: 0.2,
+	g: 0.4,
+	b: 0.9,
+	a: 1.0,
+};
+const SNAP_COLOR: Color = Color {
+	r: 0.9,
+	g: 0.3,
+	b: 0.2,
+	a: 1.0,
+};
+const DYING_COLOR: Color = Color {
+	r: 0.8,
+	g: 0.3,
+	b: 0.1,
+	a: 1.0,
+};
+const WIRE_COLOR: Color = Color {
+	r: 0.95,
+	g: 0.75,
+	b: 0.1,
+	a: 1.0,
+};
+const HEAD_COLOR: Color = Color {
+	r: 0.1,
+	g: 0.3,
+	b: 0.9,
+	a: 1.0,
+};
+const TAIL_COLOR: Color = Color {
+	r: 0.9,
+	g: 0.2,
+	b: 0.1,
+	a: 1.0,
+};
+const FILL_COLOR: Color = Color {
+	r: 0.2,
+	g: 0.7,
+	b: 0.3,
+	a: 1.0,
+};
+const SELECTION_COLOR: Color = Color {
+	r: 0.6,
+	g: 0.2,
+	b: 0.8,
+	a: 1.0,
+};
+/// Opacity of the cells previewed where the clipboard would be pasted
+const GHOST_ALPHA: f32 = 0.4;
+const SYMMETRY_COLOR: Color = Color {
+	r: 0.9,
+	g: 0.5,
+	b: 0.1,
+	a: 0.6,
+};
+const GRAPH_COLOR: Color = Color {
+	r: 0.1,
+	g: 0.5,
+	b: 0.2,
+	a: 1.0,
+};
+const ERASE_COLOR: Color = Color {
+	r: 0.6,
+	g: 0.6,
+	b: 0.6,
+	a: 1.0,
+};
+const ANT_COLOR: Color = Color {
+	r: 0.9,
+	g: 0.1,
+	b: 0.1,
+	a: 1.0,
+};
+/// Colors of the states past 0 (dead) and 1 (alive) for turmites and rule files without `@COLORS`
+const STATE_COLORS: [Color; turmite::MAX_COLORS - 2] = [
+	Color { r: 0.1, g: 0.4, b: 0.9, a: 1.0 },
+	Color { r: 0.1, g: 0.7, b: 0.3, a: 1.0 },
+	Color { r: 0.9, g: 0.7, b: 0.1, a: 1.0 },
+	Color { r: 0.6, g: 0.2, b: 0.8, a: 1.0 },
+	Color { r: 0.1, g: 0.7, b: 0.8, a: 1.0 },
+	Color { r: 0.9, g: 0.4, b: 0.6, a: 1.0 },
+];
+/// Most colors live cells can have, QuadLife has four
+pub const MAX_LIFE_COLORS: usize = 4;
+/// Shades live cells are drawn in by age, see `Conway::age_shade`
+const AGE_SHADES: u8 = 8;
+/// Colors of the youngest and the oldest live cells when coloring by age
+const AGE_YOUNG: Color = Color { r: 0.95, g: 0.65, b: 0.1, a: 1.0 };
+const AGE_OLD: Color = Color { r: 0.15, g: 0.1, b: 0.5, a: 1.0 };
+/// Generations a dead cell keeps fading out for after it died, see `Conway::trail_shade`
+const TRAIL_LENGTH: u8 = 8;
+/// Color of the freshest trails, older ones fade towards the background
+const TRAIL_COLOR: Color = Color { r: 0.45, g: 0.6, b: 0.85, a: 0.7 };
+/// Colors of the heatmap from the sparsest to the densest blocks
+const HEAT_COLORS: [Color; 4] = [
+	Color { r: 0.1, g: 0.2, b: 0.8, a: 1.0 },
+	Color { r: 0.1, g: 0.7, b: 0.4, a: 1.0 },
+	Color { r: 0.95, g: 0.8, b: 0.1, a: 1.0 },
+	Color { r: 0.9, g: 0.15, b: 0.1, a: 1.0 },
+];
+/// Colors of live cells past the first in Immigration and QuadLife, the first is the theme's cell color
+const LIFE_COLORS: [Color; MAX_LIFE_COLORS - 1] = [
+	Color { r: 0.85, g: 0.15, b: 0.1, a: 1.0 },
+	Color { r: 0.1, g: 0.35, b: 0.9, a: 1.0 },
+	Color { r: 0.1, g: 0.6, b: 0.2, a: 1.0 },
+];
+const LOST_COLOR: Color = Color {
+	r: 0.9,
+	g: 0.1,
+	b: 0.1,
+	a: 1.0,
+};
 
-const GRID_COLOR: Color = Color::BLACK;
+pub type Board = Vec<Vec<Cell>>;
 
-type Board = Vec<Vec<Cell>>;
+/// Von Neumann neighborhood, the four orthogonal neighbors
+pub const VON_NEUMANN_KERNEL: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
 
 pub const KERNEL: [(isize, isize); 8] = [
 	(-1, -1),
@@ -27,31 +259,644 @@ pub const KERNEL: [(isize, isize); 8] = [
 	(1, 1),
 ];
 
+// NOTE(Simon): hex cells live on the square grid in "odd-r" layout, every odd row is shifted half a cell to the right
+pub const HEX_KERNEL_EVEN: [(isize, isize); 6] = [
+	(-1, -1),
+	(0, -1),
+	(-1, 0),
+	(1, 0),
+	(-1, 1),
+	(0, 1),
+];
+
+pub const HEX_KERNEL_ODD: [(isize, isize); 6] = [
+	(0, -1),
+	(1, -1),
+	(-1, 0),
+	(1, 0),
+	(0, 1),
+	(1, 1),
+];
+
+// NOTE(Simon): `clear_new_board` zeroes the board, the primitive representation makes sure that is `Dead`
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
 pub enum Cell {
 	Dead,
 	Alive,
+	/// Decaying under a Generations rule, the stage counts up from 1. In Wireworld `Dying(1)` is an electron tail.
+	Dying(u8),
+	/// Wireworld conductor, inert under every other rule
+	Wire,
+	/// Live cell of color 1 and up in Immigration and QuadLife, `Alive` is color 0
+	Colored(u8),
+}
+
+impl Cell {
+	/// Number of the cell's state in multi-state rules, 0 is dead, 1 alive and 2 onwards the dying stages
+	/// from `Dying(1)`. Wire counts as dead.
+	pub const fn state(self) -> u8 {
+		match self {
+			Self::Dead | Self::Wire => 0,
+			Self::Alive | Self::Colored(_) => 1,
+			Self::Dying(stage) => stage + 1,
+		}
+	}
+
+	pub const fn is_alive(self) -> bool {
+		matches!(self, Self::Alive | Self::Colored(_))
+	}
+
+	/// Color of a live cell
+	pub const fn color(self) -> Option<u8> {
+		match self {
+			Self::Alive => Some(0),
+			Self::Colored(color) => Some(color),
+			_ => None,
+		}
+	}
+
+	/// Live cell of `color`
+	pub const fn colored(color: u8) -> Self {
+		match color {
+			0 => Self::Alive,
+			color => Self::Colored(color),
+		}
+	}
+
+	pub const fn from_state(state: u8) -> Self {
+		match state {
+			0 => Self::Dead,
+			1 => Self::Alive,
+			state => Self::Dying(state - 1),
+		}
+	}
 }
 
 #[derive(Debug)]
 pub struct CustomInput {
 	mode: Mode,
-	mouse_points: Vec<Point>,
+	/// Cursor positions to paint, together with the cell the brush held at that moment and whether the
+	/// position continues the line from the previous one
+	strokes: Vec<(Point, Cell, bool)>,
+	/// Cursor in board pixels, i.e. mapped through the camera
+	cursor: Point,
+	/// Cursor in window pixels
+	window_cursor: Point,
+	camera: Camera,
+	board_size: (usize, usize),
+	left_held: bool,
+	right_held: bool,
+	/// Cell painted by the most recently pressed button, wins while both buttons are held
+	latest: Cell,
+	/// Cell painted by the left button, wire in Wireworld and a live cell otherwise
+	live: Cell,
+	tool: Tool,
+	/// Where the left button went down while dragging out a region
+	drag_start: Option<Point>,
+	shift_held: bool,
+	/// Either Control key is down, C, X and V then copy, cut and paste
+	ctrl_held: bool,
+	/// Where the left button went down while Shift was held, dragging out a rectangle to clear
+	erase_start: Option<Point>,
+	/// Where the left button went down while dragging out a rectangle for the fill tool
+	fill_start: Option<Point>,
+	/// Where the left button went down while dragging out a selection
+	select_start: Option<Point>,
+	/// Last cursor position, in window pixels, while dragging the board in the moving mode
+	grab: Option<Point>,
+	/// How far the board was dragged since the last `Conway::interact`, in window pixels
+	dragged: Vector,
+	/// Where the left button went down on the selection while dragging it in the moving mode
+	move_start: Option<Point>,
+	/// Copy of `Conway::selection`, the moving mode drags it instead of the board when grabbed
+	selection: Option<Rectangle<usize>>,
+	/// The left button went down on the minimap, dragging moves the view instead of painting
+	on_minimap: bool,
+	/// Pattern highlighted in the pattern browser while it is open
+	browser: Option<usize>,
+	/// Where a brush button went down, until the cursor has moved more than `drag_threshold` from it
+	press_at: Option<Point>,
+	/// How far (in pixels) the cursor has to move before a click turns into a stroke
+	drag_threshold: f32,
+	/// Text being typed and what it is for, keys edit it instead of triggering their action
+	entry: Option<(Prompt, String)>,
+	/// Turmites are walking, middle click places an ant instead of a cell
+	ants: bool,
 	action: InputAction,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum InputAction {
-	PlaceAlive,
-	PlaceDead,
 	Pause,
+	ToggleTopology,
+	ToggleVonNeumann,
+	Share,
+	SetRegion(Option<Rectangle<usize>>),
+	Bookmark,
+	Recall(usize),
+	ToggleRendering,
+	ToggleSnap,
+	WriteReport,
+	Save,
+	ToggleLosses,
+	Transform(Transform),
+	Erase(Rectangle<usize>),
+	Fill(Rectangle<usize>, Fill),
+	SelectFill(Option<Fill>),
+	Select(Option<Rectangle<usize>>),
+	Copy,
+	Cut,
+	Undo,
+	Redo,
+	NextSymmetry,
+	/// Advances a single generation while editing
+	Step,
+	/// Goes back a generation, pausing the simulation
+	Rewind,
+	/// Doubles the simulation speed, halves it when false
+	ChangeSpeed(bool),
+	/// Switches the editor between drawing and moving
+	ToggleMoving,
+	/// Moves the selected cells by the difference between the two cells
+	MoveSelection((usize, usize), (usize, usize)),
+	/// Fills the selection, or the whole board, with random cells
+	Soup,
+	Clear,
+	/// Goes back to the board the simulation was last started from
+	Reset,
+	/// Switches to pasting the clipboard, if there is anything on it
+	BeginPaste,
+	/// Pastes the clipboard centered on this cell, or at the paste origin
+	Paste((usize, usize)),
+	/// Moves the paste origin to this cell, or removes it if it is there already
+	SetPasteOrigin((usize, usize)),
+	SelectStamp(Option<usize>),
+	SetRule(String),
+	/// Simulates this many generations, as typed, as fast as possible and pauses
+	RunFor(String),
+	NextPreset,
+	ToggleWireworld,
+	ToggleAnts,
+	ToggleBoundary,
+	NextColors,
+	NextPaintColor,
+	PlaceAnt((usize, usize)),
+	Stamp(usize, (usize, usize)),
+	ToggleStatus,
+	ToggleRates,
+	ToggleAges,
+	ToggleTrails,
+	ToggleGrid,
+	ToggleHeatmap,
+	Screenshot,
+	ToggleRecording,
+	NextTheme,
+	ToggleDark,
+	Pan(isize, isize),
+	/// Zooms in by this many steps, out when negative, around the cursor or else the middle of the window
+	Zoom { steps: i32, at_cursor: bool },
+	ResetZoom,
+	/// Centers the view on this cell
+	CenterOn((usize, usize)),
+	/// Grows the brush by this many cells on every side, shrinks it when negative
+	ResizeBrush(isize),
+	ToggleBrushShape,
+	Jump,
 	None,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Tool {
+	Brush,
+	Region,
+	/// Clicking places the library pattern with this index
+	Stamp(usize),
+	/// Dragging out a rectangle fills it
+	Fill(Fill),
+	/// Dragging out a rectangle selects it for copying
+	Select,
+	/// Clicking pastes the clipboard
+	Paste,
+}
+
+/// What the fill tool puts into a rectangle
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fill {
+	/// The cell the left button paints
+	Alive,
+	Dead,
+	/// Every cell has a chance of `--soup-density` to be the cell the left button paints
+	Random,
+}
+
+impl Fill {
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Alive => "alive",
+			Self::Dead => "dead",
+			Self::Random => "random",
+		}
+	}
+
+	/// The tool after `self` when cycling through them, `None` goes back to the brush
+	const fn next(self) -> Option<Self> {
+		match self {
+			Self::Alive => Some(Self::Random),
+			Self::Random => Some(Self::Dead),
+			Self::Dead => None,
+		}
+	}
+}
+
+impl CustomInput {
+	const fn brush(&self) -> Option<Cell> {
+		match (self.left_held, self.right_held) {
+			(true, true) => Some(self.latest),
+			(true, false) => Some(self.live),
+			(false, true) => Some(Cell::Dead),
+			(false, false) => None,
+		}
+	}
+
+	/// Left drag sets the active region, a right click removes it. Either way we go back to the brush.
+	fn region_input(&mut self, state: ButtonState, button: mouse::Button) {
+		if self.mode == Mode::Simulation {
+			return;
+		}
+		match (button, state) {
+			(mouse::Button::Left, ButtonState::Pressed) => self.drag_start = Some(self.cursor),
+			(mouse::Button::Left, ButtonState::Released) => {
+				if let Some(start) = self.drag_start.take() {
+					self.action = InputAction::SetRegion(Some(cell_rect(start, self.cursor, self.board_size)));
+					self.tool = Tool::Brush;
+				}
+			}
+			(mouse::Button::Right, ButtonState::Pressed) => {
+				self.action = InputAction::SetRegion(None);
+				self.tool = Tool::Brush;
+			}
+			_ => {},
+		}
+	}
+
+	/// Region currently being dragged out, for previewing it
+	fn region_drag(&self) -> Option<Rectangle<usize>> {
+		self.drag_start.map(|start| cell_rect(start, self.cursor, self.board_size))
+	}
+
+	/// A left click places the selected pattern centered on the cursor, a right click puts it away
+	fn stamp_input(&mut self, state: ButtonState, button: mouse::Button, index: usize) {
+		match (button, state) {
+			(mouse::Button::Left, ButtonState::Pressed) => self.action = InputAction::Stamp(index, cell_at(self.cursor, self.board_size)),
+			(mouse::Button::Right, ButtonState::Pressed) => {
+				self.tool = Tool::Brush;
+				self.action = InputAction::SelectStamp(None);
+			}
+			_ => {},
+		}
+	}
+
+	/// Shift and left drag clears a rectangle instead of painting
+	fn erase_input(&mut self, state: ButtonState) {
+		match state {
+			ButtonState::Pressed => self.erase_start = Some(self.cursor),
+			ButtonState::Released => {
+				if let Some(start) = self.erase_start.take() {
+					self.action = InputAction::Erase(cell_rect(start, self.cursor, self.board_size));
+				}
+			}
+		}
+	}
+
+	fn erase_drag(&self) -> Option<Rectangle<usize>> {
+		self.erase_start.map(|start| cell_rect(start, self.cursor, self.board_size))
+	}
+
+	/// Left drag fills a rectangle on release, a right click goes back to the brush
+	fn fill_input(&mut self, state: ButtonState, button: mouse::Button, fill: Fill) {
+		match (button, state) {
+			(mouse::Button::Left, ButtonState::Pressed) => self.fill_start = Some(self.cursor),
+			(mouse::Button::Left, ButtonState::Released) => {
+				if let Some(start) = self.fill_start.take() {
+					self.action = InputAction::Fill(cell_rect(start, self.cursor, self.board_size), fill);
+				}
+			}
+			(mouse::Button::Right, ButtonState::Pressed) => {
+				self.fill_start = None;
+				self.tool = Tool::Brush;
+				self.action = InputAction::SelectFill(None);
+			}
+			_ => {},
+		}
+	}
+
+	/// Left drag selects a rectangle, a right click drops the selection and goes back to the brush
+	fn select_input(&mut self, state: ButtonState, button: mouse::Button) {
+		match (button, state) {
+			(mouse::Button::Left, ButtonState::Pressed) => self.select_start = Some(self.cursor),
+			(mouse::Button::Left, ButtonState::Released) => {
+				if let Some(start) = self.select_start.take() {
+					self.action = InputAction::Select(Some(cell_rect(start, self.cursor, self.board_size)));
+				}
+			}
+			(mouse::Button::Right, ButtonState::Pressed) => {
+				self.select_start = None;
+				self.tool = Tool::Brush;
+				self.action = InputAction::Select(None);
+			}
+			_ => {},
+		}
+	}
+
+	/// In the moving mode a left drag on the selection moves it, anywhere else it drags the board along
+	fn move_input(&mut self, state: ButtonState, button: mouse::Button) {
+		match (button, state) {
+			(mouse::Button::Left, ButtonState::Pressed) => {
+				let (x, y) = cell_at(self.cursor, self.board_size);
+				let grabbed = self.selection.filter(|area| {
+					(area.x..area.x + area.width).contains(&x) && (area.y..area.y + area.height).contains(&y)
+				});
+				match grabbed {
+					Some(_) => self.move_start = Some(self.cursor),
+					None => self.grab = Some(self.window_cursor),
+				}
+			}
+			(mouse::Button::Left, ButtonState::Released) => {
+				self.grab = None;
+				if let Some(start) = self.move_start.take() {
+					self.action = InputAction::MoveSelection(cell_at(start, self.board_size), cell_at(self.cursor, self.board_size));
+				}
+			}
+			_ => {},
+		}
+	}
+
+	/// Cells the selection is being dragged by in the moving mode, for previewing it
+	fn move_drag(&self) -> Option<(isize, isize)> {
+		let ((ax, ay), (bx, by)) = (cell_at(self.move_start?, self.board_size), cell_at(self.cursor, self.board_size));
+		Some((bx as isize - ax as isize, by as isize - ay as isize))
+	}
+
+	/// Selection being dragged out, for previewing it
+	fn select_drag(&self) -> Option<Rectangle<usize>> {
+		self.select_start.map(|start| cell_rect(start, self.cursor, self.board_size))
+	}
+
+	/// A left click pastes the clipboard centered on the cursor, a right click goes back to the brush
+	fn paste_input(&mut self, state: ButtonState, button: mouse::Button) {
+		match (button, state) {
+			(mouse::Button::Left, ButtonState::Pressed) => self.action = InputAction::Paste(cell_at(self.cursor, self.board_size)),
+			(mouse::Button::Right, ButtonState::Pressed) => self.tool = Tool::Brush,
+			_ => {},
+		}
+	}
+
+	/// Cell a click would stamp or paste around, while stamping or pasting
+	fn ghost_target(&self) -> Option<(usize, usize)> {
+		matches!(self.tool, Tool::Paste | Tool::Stamp(_)).then(|| cell_at(self.cursor, self.board_size))
+	}
+
+	/// Rectangle being dragged out for the fill tool, for previewing it
+	fn fill_drag(&self) -> Option<(Rectangle<usize>, Fill)> {
+		match self.tool {
+			Tool::Fill(fill) => self.fill_start.map(|start| (cell_rect(start, self.cursor, self.board_size), fill)),
+			_ => None,
+		}
+	}
+
+	fn key_pressed(&mut self, key_code: KeyCode) {
+		if let Some((prompt, entry)) = &mut self.entry {
+			match key_code {
+				KeyCode::Return | KeyCode::NumpadEnter => {
+					let text = std::mem::take(entry);
+					self.action = match prompt {
+						Prompt::Rule => InputAction::SetRule(text),
+						Prompt::Generations => InputAction::RunFor(text),
+					};
+					self.entry = None;
+				}
+				KeyCode::Escape => self.entry = None,
+				KeyCode::Back => {
+					entry.pop();
+				}
+				_ => entry.extend(prompt.char(key_code)),
+			}
+			return;
+		}
+		if let Some(selected) = &mut self.browser {
+			match key_code {
+				KeyCode::Up => *selected = selected.saturating_sub(1),
+				KeyCode::Down => *selected = (*selected + 1).min(library::PATTERNS.len() - 1),
+				KeyCode::Return | KeyCode::NumpadEnter => {
+					self.tool = Tool::Stamp(*selected);
+					self.action = InputAction::SelectStamp(Some(*selected));
+					self.browser = None;
+				}
+				KeyCode::Escape => self.browser = None,
+				KeyCode::B if self.ctrl_held => self.browser = None,
+				_ => {}
+			}
+			return;
+		}
+		if self.ctrl_held {
+			self.action = match key_code {
+				KeyCode::C => InputAction::Copy,
+				KeyCode::X => InputAction::Cut,
+				KeyCode::V => InputAction::BeginPaste,
+				KeyCode::Z if self.shift_held => InputAction::Redo,
+				KeyCode::Z => InputAction::Undo,
+				KeyCode::Y => InputAction::Redo,
+				KeyCode::M => InputAction::NextSymmetry,
+				KeyCode::R => InputAction::Soup,
+				KeyCode::F => InputAction::ToggleRates,
+				KeyCode::A => InputAction::ToggleAges,
+				KeyCode::T => InputAction::ToggleTrails,
+				KeyCode::H => InputAction::NextTheme,
+				KeyCode::L => InputAction::ToggleGrid,
+				KeyCode::E => InputAction::ToggleHeatmap,
+				KeyCode::P if self.shift_held => InputAction::ToggleRecording,
+				KeyCode::P => InputAction::Screenshot,
+				KeyCode::D => InputAction::ToggleDark,
+				KeyCode::O => InputAction::SetPasteOrigin(cell_at(self.cursor, self.board_size)),
+				KeyCode::B => {
+					self.browser = Some(match self.tool {
+						Tool::Stamp(index) => index,
+						_ => 0,
+					});
+					return;
+				}
+				KeyCode::G => {
+					self.entry = Some((Prompt::Generations, String::new()));
+					return;
+				}
+				_ => return,
+			};
+			return;
+		}
+		self.action = match key_code {
+			KeyCode::P => InputAction::Pause,
+			KeyCode::H => InputAction::ToggleTopology,
+			KeyCode::V => InputAction::ToggleVonNeumann,
+			KeyCode::C => InputAction::Share,
+			KeyCode::B => InputAction::Bookmark,
+			KeyCode::F => InputAction::ToggleRendering,
+			KeyCode::N => InputAction::ToggleSnap,
+			KeyCode::J => InputAction::WriteReport,
+			KeyCode::S => InputAction::Save,
+			KeyCode::E => InputAction::ToggleLosses,
+			KeyCode::I => InputAction::ToggleStatus,
+			KeyCode::K => InputAction::NextPreset,
+			KeyCode::W => InputAction::ToggleWireworld,
+			KeyCode::A => InputAction::ToggleAnts,
+			KeyCode::T => InputAction::ToggleBoundary,
+			KeyCode::Q => InputAction::NextColors,
+			KeyCode::G => InputAction::NextPaintColor,
+			KeyCode::Z => InputAction::Jump,
+			KeyCode::Delete => InputAction::Clear,
+			KeyCode::Home => InputAction::Reset,
+			KeyCode::Space => InputAction::Step,
+			KeyCode::Back => InputAction::Rewind,
+			KeyCode::PageUp => InputAction::ChangeSpeed(true),
+			KeyCode::PageDown => InputAction::ChangeSpeed(false),
+			KeyCode::Tab => {
+				self.grab = None;
+				self.move_start = None;
+				InputAction::ToggleMoving
+			}
+			KeyCode::Left => InputAction::Pan(-1, 0),
+			KeyCode::Right => InputAction::Pan(1, 0),
+			KeyCode::Up => InputAction::Pan(0, -1),
+			KeyCode::Down => InputAction::Pan(0, 1),
+			KeyCode::Equals | KeyCode::Add => InputAction::Zoom { steps: 1, at_cursor: false },
+			KeyCode::Minus | KeyCode::Subtract => InputAction::Zoom { steps: -1, at_cursor: false },
+			KeyCode::Key0 => InputAction::ResetZoom,
+			KeyCode::Period => InputAction::ResizeBrush(1),
+			KeyCode::Comma => InputAction::ResizeBrush(-1),
+			KeyCode::O => InputAction::ToggleBrushShape,
+			KeyCode::U => {
+				self.entry = Some((Prompt::Rule, String::new()));
+				return;
+			}
+			KeyCode::RBracket => InputAction::Transform(Transform::RotateClockwise),
+			KeyCode::LBracket => InputAction::Transform(Transform::RotateCounterclockwise),
+			KeyCode::X => InputAction::Transform(Transform::FlipHorizontal),
+			KeyCode::Y => InputAction::Transform(Transform::FlipVertical),
+			KeyCode::R => {
+				self.drag_start = None;
+				self.tool = match self.tool {
+					Tool::Region => Tool::Brush,
+					_ => Tool::Region,
+				};
+				return;
+			}
+			KeyCode::Escape => {
+				self.drag_start = None;
+				self.erase_start = None;
+				self.fill_start = None;
+				self.select_start = None;
+				match std::mem::replace(&mut self.tool, Tool::Brush) {
+					Tool::Stamp(_) => InputAction::SelectStamp(None),
+					_ => return,
+				}
+			}
+			KeyCode::M => {
+				self.select_start = None;
+				self.tool = match self.tool {
+					Tool::Select => Tool::Brush,
+					_ => Tool::Select,
+				};
+				return;
+			}
+			KeyCode::D => {
+				self.fill_start = None;
+				self.tool = match self.tool {
+					Tool::Fill(fill) => fill.next().map_or(Tool::Brush, Tool::Fill),
+					_ => Tool::Fill(Fill::Alive),
+				};
+				match self.tool {
+					Tool::Fill(fill) => InputAction::SelectFill(Some(fill)),
+					_ => InputAction::SelectFill(None),
+				}
+			}
+			KeyCode::L => {
+				self.tool = match self.tool {
+					Tool::Stamp(index) if index + 1 < library::PATTERNS.len() => Tool::Stamp(index + 1),
+					Tool::Stamp(_) => Tool::Brush,
+					_ => Tool::Stamp(0),
+				};
+				match self.tool {
+					Tool::Stamp(index) => InputAction::SelectStamp(Some(index)),
+					_ => InputAction::SelectStamp(None),
+				}
+			}
+			_ => match bookmark_slot(key_code) {
+				Some(slot) => InputAction::Recall(slot),
+				None => return,
+			},
+		};
+	}
+
+	/// Whether the cursor is over the minimap
+	fn cursor_on_minimap(&self) -> bool {
+		let (x, y) = (self.window_cursor.x, self.window_cursor.y);
+		minimap::area(&self.camera, self.board_size).is_some_and(|area| {
+			(area.x..=area.x + area.width).contains(&x) && (area.y..=area.y + area.height).contains(&y)
+		})
+	}
+
+	/// Centers the view on the board pixel the cursor points at on the minimap
+	fn minimap_jump(&mut self) {
+		if let Some(area) = minimap::area(&self.camera, self.board_size) {
+			let point = minimap::board_point(area, self.board_size, self.window_cursor);
+			self.action = InputAction::CenterOn(cell_at(point, self.board_size));
+		}
+	}
+
+	fn set_view(&mut self, camera: Camera, board_size: (usize, usize)) {
+		self.camera = camera;
+		self.board_size = board_size;
+		self.cursor = self.camera.board_point(self.window_cursor);
+	}
+
+	/// Strokes are recorded in every mode, `Conway::interact` decides whether they are applied. A `continued`
+	/// stroke is joined to the previous one, so fast drags still paint an unbroken line.
+	fn paint(&mut self, continued: bool) {
+		if let Some(cell) = self.brush() {
+			self.strokes.push((self.cursor, cell, continued));
+		}
+	}
+}
+
 impl Input for CustomInput {
 	fn new() -> Self {
 		Self {
-			mouse_points: vec![],
+			strokes: vec![],
+			cursor: Point::new(0.0, 0.0),
+			window_cursor: Point::new(0.0, 0.0),
+			camera: Camera::centered(DEFAULT_BOARD_SIZE),
+			board_size: DEFAULT_BOARD_SIZE,
+			left_held: false,
+			right_held: false,
+			latest: Cell::Alive,
+			live: Cell::Alive,
+			tool: Tool::Brush,
+			drag_start: None,
+			shift_held: false,
+			ctrl_held: false,
+			erase_start: None,
+			fill_start: None,
+			select_start: None,
+			grab: None,
+			dragged: Vector::new(0.0, 0.0),
+			move_start: None,
+			selection: None,
+			on_minimap: false,
+			browser: None,
+			press_at: None,
+			drag_threshold: Config::get().drag_threshold,
+			entry: None,
+			ants: false,
 			action: InputAction::None,
 			mode: Mode::Editor(EditorMode::Drawing),
 		}
@@ -61,37 +906,111 @@ impl Input for CustomInput {
 		match event {
 			input::Event::Mouse(mouse_event) => match mouse_event {
 				mouse::Event::CursorMoved { x, y } => {
-					if self.mode == Mode::Simulation {
+					let previous = std::mem::replace(&mut self.window_cursor, Point::new(x, y));
+					self.cursor = self.camera.board_point(self.window_cursor);
+					if self.on_minimap {
+						self.minimap_jump();
+						return;
+					}
+					if self.grab.is_some() {
+						self.dragged += previous - self.window_cursor;
+						self.grab = Some(self.window_cursor);
 						return;
 					}
-					self.mouse_points.push(Point::new(x, y));
+					if let Some(press) = self.press_at {
+						if (self.cursor - press).norm() <= self.drag_threshold {
+							return;
+						}
+						self.press_at = None;
+					}
+					self.paint(true);
 				}
-				mouse::Event::Input {
-					state: input::ButtonState::Pressed,
-					button,
-				} => {
+				mouse::Event::Input { state, button } => {
+					if button == mouse::Button::Left && (self.on_minimap || self.cursor_on_minimap()) {
+						self.on_minimap = state == ButtonState::Pressed;
+						if self.on_minimap {
+							self.minimap_jump();
+						}
+						return;
+					}
+					if self.mode == Mode::Editor(EditorMode::Moving) {
+						self.move_input(state, button);
+						return;
+					}
+					if self.tool == Tool::Region {
+						self.region_input(state, button);
+						return;
+					}
+					if let Tool::Stamp(index) = self.tool {
+						self.stamp_input(state, button, index);
+						return;
+					}
+					if let Tool::Fill(fill) = self.tool {
+						self.fill_input(state, button, fill);
+						return;
+					}
+					if self.tool == Tool::Select {
+						self.select_input(state, button);
+						return;
+					}
+					if self.tool == Tool::Paste {
+						self.paste_input(state, button);
+						return;
+					}
+					let erasing = self.erase_start.is_some() || (self.shift_held && !self.left_held && !self.right_held);
+					if button == mouse::Button::Left && erasing {
+						self.erase_input(state);
+						return;
+					}
+					let pressed = state == ButtonState::Pressed;
 					match button {
 						mouse::Button::Left => {
-							self.action = match self.action {
-								InputAction::PlaceAlive => InputAction::None,
-								_ => InputAction::PlaceAlive,
+							self.left_held = pressed;
+							if pressed {
+								self.latest = self.live;
 							}
 						}
 						mouse::Button::Right => {
-							self.action = match self.action {
-								InputAction::PlaceDead => InputAction::None,
-								_ => InputAction::PlaceDead,
+							self.right_held = pressed;
+							if pressed {
+								self.latest = Cell::Dead;
 							}
 						}
-						_ => {},
+						mouse::Button::Middle if pressed && self.ants => {
+							self.action = InputAction::PlaceAnt(cell_at(self.cursor, self.board_size));
+							return;
+						}
+						// NOTE(Simon): in Wireworld a live cell is an electron head, elsewhere this just paints a live cell
+						mouse::Button::Middle if pressed => {
+							self.strokes.push((self.cursor, Cell::Alive, false));
+							return;
+						}
+						_ => return,
+					}
+					if pressed {
+						self.press_at = Some(self.cursor);
+						self.paint(false);
+					} else if !self.left_held && !self.right_held {
+						self.press_at = None;
 					}
 				},
+				mouse::Event::WheelScrolled { delta_y, .. } if delta_y != 0.0 => {
+					self.action = InputAction::Zoom { steps: delta_y.signum() as i32, at_cursor: true };
+				}
 				_ => {},
 			},
 			input::Event::Keyboard(keyboard::Event::Input {
-				key_code: KeyCode::P,
+				key_code: KeyCode::LShift | KeyCode::RShift,
+				state,
+			}) => self.shift_held = state == ButtonState::Pressed,
+			input::Event::Keyboard(keyboard::Event::Input {
+				key_code: KeyCode::LControl | KeyCode::RControl,
+				state,
+			}) => self.ctrl_held = state == ButtonState::Pressed,
+			input::Event::Keyboard(keyboard::Event::Input {
+				key_code,
 				state: ButtonState::Pressed,
-			}) => self.action = InputAction::Pause,
+			}) => self.key_pressed(key_code),
 			_ => {}
 		}
 	}
@@ -99,175 +1018,3431 @@ impl Input for CustomInput {
 	fn clear(&mut self) {}
 }
 
+/// What the text typed in place of key actions is for
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Mode {
-	Simulation,
-	Editor(EditorMode),
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum EditorMode {
-	Drawing,
-	Moving,
-}
-
-#[derive(Debug)]
-pub struct Conway {
-	current_board: Board,
-	new_board: Board,
-	mode: Mode,
+enum Prompt {
+	Rule,
+	/// Generations to run for, see `InputAction::RunFor`
+	Generations,
 }
 
-impl Conway {
-	pub fn new() -> Self {
-		Self {
-			current_board: vec![vec![Cell::Dead; CELL_COUNT_X]; CELL_COUNT_Y],
-			new_board: vec![vec![Cell::Dead; CELL_COUNT_X]; CELL_COUNT_Y],
-			mode: Mode::Editor(EditorMode::Drawing),
+impl Prompt {
+	const fn label(self) -> &'static str {
+		match self {
+			Self::Rule => "rule",
+			Self::Generations => "generations to run",
 		}
 	}
 
-	pub fn count_neighbors(&self, x: usize, y: usize) -> usize {
-		let mut n = 0;
-		for (dx, dy) in &KERNEL {
-			let x = (x as isize + dx) as usize;
-			let y = (y as isize + dy) as usize;
-
-			if self.out_of_bounds(x, y) {
-				continue;
-			}
-			if let Cell::Alive = self.current_board[y][x] {
-				n += 1;
-			}
+	/// Character a key types into the entry
+	const fn char(self, key_code: KeyCode) -> Option<char> {
+		match self {
+			Self::Rule => rule_char(key_code),
+			Self::Generations => digit_char(key_code),
 		}
-		n
 	}
+}
 
-	pub fn update_board_state(&mut self) {
-		for (y, row) in self.current_board.iter().enumerate() {
-			for (x, cell) in row.iter().enumerate() {
-				let n = self.count_neighbors(x, y);
-				let new_cell = match cell {
-					Cell::Alive if n < 2 => Cell::Dead,
-					Cell::Alive if n == 2 || n == 3 => Cell::Alive,
-					Cell::Alive if n > 3 => Cell::Dead,
-					Cell::Dead if n == 3 => Cell::Alive,
-					_ => Cell::Dead,
-				};
-				self.new_board[y][x] = new_cell;
-			}
-		}
-		std::mem::swap(&mut self.current_board, &mut self.new_board);
-		self.clear_new_board();
-	}
+const fn digit_char(key_code: KeyCode) -> Option<char> {
+	Some(match key_code {
+		KeyCode::Key0 | KeyCode::Numpad0 => '0',
+		KeyCode::Key1 | KeyCode::Numpad1 => '1',
+		KeyCode::Key2 | KeyCode::Numpad2 => '2',
+		KeyCode::Key3 | KeyCode::Numpad3 => '3',
+		KeyCode::Key4 | KeyCode::Numpad4 => '4',
+		KeyCode::Key5 | KeyCode::Numpad5 => '5',
+		KeyCode::Key6 | KeyCode::Numpad6 => '6',
+		KeyCode::Key7 | KeyCode::Numpad7 => '7',
+		KeyCode::Key8 | KeyCode::Numpad8 => '8',
+		KeyCode::Key9 | KeyCode::Numpad9 => '9',
+		_ => return None,
+	})
+}
 
-	// NOTE(Simon): we rely on overflowing the usize for checking indices which are out of bound in the negative direction
-	const fn out_of_bounds(&self, x: usize, y: usize) -> bool {
-		x >= CELL_COUNT_X || y >= CELL_COUNT_Y
-	}
+/// Character a key types into a rulestring
+const fn rule_char(key_code: KeyCode) -> Option<char> {
+	Some(match key_code {
+		KeyCode::B => 'B',
+		KeyCode::S => 'S',
+		KeyCode::H => 'H',
+		KeyCode::V => 'V',
+		KeyCode::Slash | KeyCode::Divide => '/',
+		KeyCode::Key0 | KeyCode::Numpad0 => '0',
+		KeyCode::Key1 | KeyCode::Numpad1 => '1',
+		KeyCode::Key2 | KeyCode::Numpad2 => '2',
+		KeyCode::Key3 | KeyCode::Numpad3 => '3',
+		KeyCode::Key4 | KeyCode::Numpad4 => '4',
+		KeyCode::Key5 | KeyCode::Numpad5 => '5',
+		KeyCode::Key6 | KeyCode::Numpad6 => '6',
+		KeyCode::Key7 | KeyCode::Numpad7 => '7',
+		KeyCode::Key8 | KeyCode::Numpad8 => '8',
+		_ => return None,
+	})
+}
 
-	fn clear_new_board(&mut self) {
-		for row in &mut self.new_board {
-			unsafe {
-				let row_ptr = row.as_mut_ptr();
-				std::ptr::write_bytes(row_ptr, 0, CELL_COUNT_X);
+/// Number keys 1-9 map to bookmark slots 0-8
+const fn bookmark_slot(key_code: KeyCode) -> Option<usize> {
+	Some(match key_code {
+		KeyCode::Key1 => 0,
+		KeyCode::Key2 => 1,
+		KeyCode::Key3 => 2,
+		KeyCode::Key4 => 3,
+		KeyCode::Key5 => 4,
+		KeyCode::Key6 => 5,
+		KeyCode::Key7 => 6,
+		KeyCode::Key8 => 7,
+		KeyCode::Key9 => 8,
+		_ => return None,
+	})
+}
+
+/// Copies the cells of `cells` that are not dead into `board` with the top left corner at `x`, `y`, clipped
+/// to the board
+fn overlay(board: &mut Board, cells: &Board, x: usize, y: usize) {
+	for (row, cells) in board[y..].iter_mut().zip(cells) {
+		for (target, cell) in row[x..].iter_mut().zip(cells) {
+			if *cell != Cell::Dead {
+				*target = *cell;
 			}
 		}
 	}
+}
 
-	fn draw_cells(&mut self, mesh: &mut Mesh) {
-		for (y, row) in self.current_board.iter_mut().enumerate() {
-			for (x, cell) in row.iter_mut().enumerate() {
-				if let Cell::Alive = cell {
-					mesh.fill(
-						Shape::Rectangle(Rectangle {
-							x: (x * CELL_SIZE) as f32,
-							y: (y * CELL_SIZE) as f32,
-							width: CELL_SIZE as f32,
-							height: CELL_SIZE as f32,
-						}),
-						Color::BLACK,
-					);
-				}
-			}
-		}
+/// Cells spanned by a drag between two cursor positions, clamped to a board of `size`
+fn cell_rect(a: Point, b: Point, size: (usize, usize)) -> Rectangle<usize> {
+	let ((ax, ay), (bx, by)) = (cell_at(a, size), cell_at(b, size));
+	Rectangle {
+		x: ax.min(bx),
+		y: ay.min(by),
+		width: ax.max(bx) - ax.min(bx) + 1,
+		height: ay.max(by) - ay.min(by) + 1,
 	}
+}
 
-	pub fn draw_grid(mesh: &mut Mesh) {
-		let x_bound = WINDOW_SIZE_X / CELL_SIZE as usize;
-		let y_bound = WINDOW_SIZE_Y / CELL_SIZE as usize;
-		for i in 0..x_bound {
-			let i = i as f32;
-			let line = Shape::Polyline {
-				points: vec![
-					Point::new(i * CELL_SIZE as f32, 0.0),
-					Point::new(i * CELL_SIZE as f32, WINDOW_SIZE_Y as f32),
-				],
-			};
-			mesh.stroke(line, GRID_COLOR, 1.0);
+/// Cells on the line from `a` to `b`, both included, each touching the one before at least at a corner
+fn line(a: (usize, usize), b: (usize, usize)) -> Vec<(usize, usize)> {
+	// NOTE(Simon): Bresenham's algorithm, stepping along both axes in proportion to the error
+	let (mut x, mut y) = (a.0 as isize, a.1 as isize);
+	let (dx, dy) = ((b.0 as isize - x).abs(), -(b.1 as isize - y).abs());
+	let (sx, sy) = ((b.0 as isize - x).signum(), (b.1 as isize - y).signum());
+	let mut error = dx + dy;
+	let mut cells = vec![(x as usize, y as usize)];
+	while (x, y) != (b.0 as isize, b.1 as isize) {
+		let doubled = 2 * error;
+		if doubled >= dy {
+			error += dy;
+			x += sx;
 		}
-		for i in 0..y_bound {
-			let i = i as f32;
-			let line = Shape::Polyline {
-				points: vec![
-					Point::new(0.0, i * CELL_SIZE as f32),
-					Point::new(WINDOW_SIZE_X as f32, i * CELL_SIZE as f32),
-				],
-			};
-			mesh.stroke(line, GRID_COLOR, 1.0);
+		if doubled <= dx {
+			error += dx;
+			y += sy;
 		}
+		cells.push((x as usize, y as usize));
 	}
+	cells
+}
 
-	pub fn toggle_mode(&mut self) {
-		self.mode = match self.mode {
-			Mode::Editor(_) => Mode::Simulation,
-			Mode::Simulation => Mode::Editor(EditorMode::Drawing),
-		};
-	}
+/// Color of a heatmap block whose share of live cells is `density`
+fn heat_color(density: f32) -> Color {
+	// NOTE(Simon): the square root spreads out the sparse blocks, which are most of them
+	let position = density.sqrt().clamp(0.0, 1.0) * (HEAT_COLORS.len() - 1) as f32;
+	let (low, high) = (HEAT_COLORS[position.floor() as usize], HEAT_COLORS[position.ceil() as usize]);
+	let t = position.fract();
+	let mix = |low: f32, high: f32| (high - low).mul_add(t, low);
+	Color { r: mix(low.r, high.r), g: mix(low.g, high.g), b: mix(low.b, high.b), a: 1.0 }
 }
 
-impl Game for Conway {
-	const TICKS_PER_SECOND: u16 = 10;
-	type Input = CustomInput;
-	type LoadingScreen = ();
+/// Cell under `p`, clamped to a board of `size`
+fn cell_at(p: Point, (width, height): (usize, usize)) -> (usize, usize) {
+	let cell = |p: f32, count: usize| ((p / CELL_SIZE as f32) as usize).min(count - 1);
+	(cell(p.x, width), cell(p.y, height))
+}
 
-	fn load(_window: &Window) -> Task<Self> {
-		Task::succeed(Self::new)
-	}
+// NOTE(Simon): corners of a hex tile relative to its center, in cells, clockwise from the top. The tiles are
+// exactly the areas closest to each center of the odd-r layout, so picking the nearest center matches the drawing
+const HEX_CORNERS: [(f32, f32); 6] = [(0.0, -0.625), (0.5, -0.375), (0.5, 0.375), (0.0, 0.625), (-0.5, 0.375), (-0.5, -0.375)];
 
-	fn update(&mut self, _: &Window) {
+/// Center of a hex cell in pixels, odd rows are shifted half a cell to the right
+fn hex_center(x: usize, y: usize) -> Point {
+	let shift = if y % 2 == 1 { 0.5 } else { 0.0 };
+	Point::new((x as f32 + 0.5 + shift) * CELL_SIZE as f32, (y as f32 + 0.5) * CELL_SIZE as f32)
+}
+
+/// Hex tile around `center`, `size` pixels wide
+fn hexagon(center: Point, size: f32) -> Shape {
+	let points = (0..HEX_CORNERS.len()).map(|i| hex_corner(center, i, size)).collect();
+	Shape::Polyline { points }
+}
+
+/// Corner `i` of the hex tile around `center`, see `HEX_CORNERS`
+fn hex_corner(center: Point, i: usize, size: f32) -> Point {
+	let (dx, dy) = HEX_CORNERS[i];
+	Point::new(dx.mul_add(size, center.x), dy.mul_add(size, center.y))
+}
+
+/// Hex cell whose center is closest to `p`, may be past the last row or column
+fn hex_at(p: Point) -> (usize, usize) {
+	let row = (p.y / CELL_SIZE as f32).max(0.0) as usize;
+	let mut nearest = (0, 0);
+	let mut nearest_distance = f32::MAX;
+	for y in row.saturating_sub(1)..=row + 1 {
+		let shift = if y % 2 == 1 { 0.5 } else { 0.0 };
+		let x = (p.x / CELL_SIZE as f32 - shift).max(0.0) as usize;
+		for x in [x, x + 1] {
+			let center = hex_center(x, y);
+			let (dx, dy) = (center.x - p.x, center.y - p.y);
+			let distance = dx.mul_add(dx, dy * dy);
+			if distance < nearest_distance {
+				nearest = (x, y);
+				nearest_distance = distance;
+			}
+		}
+	}
+	nearest
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Mode {
+	Simulation,
+	Editor(EditorMode),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EditorMode {
+	Drawing,
+	/// Dragging moves the board or the selection instead of painting
+	Moving,
+}
+
+/// Everything besides the board that decides the next generation, see `Conway::dynamics`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dynamics {
+	generation: u64,
+	rule: Ruleset,
+	ltl: Option<LargerThanLife>,
+	rule_table: bool,
+	rule_file: bool,
+	kernel: bool,
+	topology: Topology,
+	boundary: Boundary,
+	wireworld: bool,
+	colors: u8,
+	margin: usize,
+	region: Option<Rectangle<usize>>,
+}
+
+/// Cells that changed in the last generation, only their neighborhoods can change in the next one
+#[derive(Debug)]
+struct ActiveCells {
+	dynamics: Dynamics,
+	cells: Vec<(usize, usize)>,
+}
+
+/// Cell, age shade and trail shade of every cell in a render chunk, row by row
+type ChunkCells = Vec<(Cell, u8, u8)>;
+
+/// Settings besides the cells themselves that change how cells are drawn
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct CellStyle {
+	wireworld: bool,
+	ants: bool,
+	rule_file: bool,
+	topology: Topology,
+	states: u8,
+	ages: bool,
+	trails: bool,
+	theme: &'static str,
+	dark: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Topology {
+	Square,
+	Hex,
+	/// Square grid with only the four orthogonal neighbors
+	VonNeumann,
+}
+
+impl Topology {
+	/// Size of the neighborhood, i.e. the highest possible neighbor count
+	pub const fn neighbors(self) -> usize {
+		match self {
+			Self::Square => KERNEL.len(),
+			Self::Hex => HEX_KERNEL_EVEN.len(),
+			Self::VonNeumann => VON_NEUMANN_KERNEL.len(),
+		}
+	}
+
+	/// Name used in sessions and reports
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Square => "square",
+			Self::Hex => "hex",
+			Self::VonNeumann => "von-neumann",
+		}
+	}
+
+	/// Golly's suffix for rules on this neighborhood, like the `H` in `B2/S34H`
+	pub const fn suffix(self) -> &'static str {
+		match self {
+			Self::Square => "",
+			Self::Hex => "H",
+			Self::VonNeumann => "V",
+		}
+	}
+
+	pub const fn default_rule(self) -> Ruleset {
+		match self {
+			Self::Square => Ruleset::CONWAY,
+			Self::Hex => Ruleset::HEX,
+			// NOTE(Simon): there is no standard von Neumann rule, Life is at least well defined on four neighbors
+			Self::VonNeumann => Ruleset::CONWAY,
+		}
+	}
+}
+
+/// What cells at the edge of the simulated area see past it
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Boundary {
+	/// Everything past the edge is dead
+	Dead,
+	/// Opposite edges are glued together, anything leaving on one side comes back on the other
+	Wrap,
+	/// Every edge is a mirror, cells past it see the board reflected
+	Mirror,
+}
+
+impl Boundary {
+	/// Name used in sessions and reports
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Dead => "dead",
+			Self::Wrap => "wrap",
+			Self::Mirror => "mirror",
+		}
+	}
+}
+
+/// A cell that would have been born just outside the simulated area
+#[derive(Debug)]
+struct LostCell {
+	/// Edge cell next to the lost cell
+	edge: (usize, usize),
+	/// Direction from the edge cell towards the lost cell
+	direction: (isize, isize),
+	generation: u64,
+}
+
+#[derive(Debug)]
+pub struct Conway {
+	current_board: Board,
+	new_board: Board,
+	mode: Mode,
+	camera: Camera,
+	/// Unbounded plane the board is a window onto, see `--infinite`
+	plane: Option<Plane>,
+	/// Where the top left corner of the board sits on the plane
+	origin: (i64, i64),
+	/// Generations skipped by a jump, see `--jump`
+	jump: u64,
+	topology: Topology,
+	boundary: Boundary,
+	rule: Ruleset,
+	/// Replaces `rule` when set
+	rule_table: Option<RuleTable>,
+	/// Replaces `rule` when set, cells see every cell within its radius
+	ltl: Option<LargerThanLife>,
+	/// Replaces the topology's neighborhood when set
+	kernel: Option<Kernel>,
+	/// Number of colors live cells come in, 2 is Immigration and 4 QuadLife
+	colors: u8,
+	/// Color the left button paints
+	paint_color: u8,
+	/// Chance that a birth the rule calls for happens, see `roll`
+	birth_chance: f32,
+	/// Chance that a cell the rule keeps alive survives
+	survive_chance: f32,
+	seed: u64,
+	/// Replaces `rule` when set, a multi-state rule from a Golly `.rule` file
+	rule_file: Option<GollyRule>,
+	/// Where `rule_table`, `rule_file` and `kernel` came from, for `save_session`
+	rule_sources: RuleSources,
+	/// Width of the quiet zone along every edge, cells in it are always dead
+	margin: usize,
+	print_checksums: bool,
+	/// Whether dead cells are drawn in a checkerboard pattern
+	checkerboard: bool,
+	/// Whether cells are drawn as a texture, see `draw_texture`
+	texture: bool,
+	/// Meshes of the live cells in the visible `RENDER_CHUNK` squares, together with the cells and age shades
+	/// they were built from. Only squares whose cells changed are tessellated again.
+	chunks: HashMap<(usize, usize), (ChunkCells, Mesh)>,
+	/// What the chunks were drawn with, they are all rebuilt when it changes
+	chunk_style: Option<CellStyle>,
+	/// Cells that changed in the last generation, unless the board was edited since
+	active: Option<ActiveCells>,
+	/// Cell the last stroke painted, continued strokes draw a line from it
+	last_stroke: Option<(usize, usize)>,
+	/// Cells the brush reaches from its center, 0 paints single cells
+	brush_radius: usize,
+	/// Whether the brush paints a disc instead of a square
+	round_brush: bool,
+	/// Strokes are also painted at their images under this symmetry
+	symmetry: Symmetry,
+	/// Checkerboard for the visible cells, only tessellated again once the camera shows other cells
+	checkerboard_mesh: Option<(Rectangle<usize>, Mesh)>,
+	/// When set, only cells inside this rectangle are simulated, everything else stays frozen
+	active_region: Option<Rectangle<usize>>,
+	region_preview: Option<Rectangle<usize>>,
+	/// Rectangle being dragged out to clear
+	erase_preview: Option<Rectangle<usize>>,
+	/// Rectangle being dragged out for the fill tool
+	fill_preview: Option<(Rectangle<usize>, Fill)>,
+	/// Random fills so far, so filling the same rectangle twice in one generation rolls new cells
+	random_fills: u64,
+	/// Share of live cells in random fills
+	soup_density: f32,
+	/// Cells that copy and cut take, they stay selected while painting
+	selection: Option<Rectangle<usize>>,
+	selection_preview: Option<Rectangle<usize>>,
+	/// Cells last copied or cut
+	clipboard: Option<Board>,
+	/// Cell the stamp or the clipboard is previewed around while stamping or pasting
+	ghost: Option<(usize, usize)>,
+	/// Cell stamps and pastes put their top left corner on instead of centering on the click
+	paste_origin: Option<(usize, usize)>,
+	/// Cells of the library pattern being stamped, with its index
+	stamp_cells: Option<(usize, Board)>,
+	generation: u64,
+	/// Generation in which each cell was last born, lets `draw` tell newborn cells apart
+	born_at: Vec<Vec<u64>>,
+	/// Generation in which each cell last died, 0 if it has not died since the last edit
+	died_at: Vec<Vec<u64>>,
+	pop: bool,
+	/// Color live cells by how many generations they have been alive
+	ages: bool,
+	/// Draw cells that died in the last `TRAIL_LENGTH` generations fading out
+	trails: bool,
+	/// Colors in use, the inverse of the chosen theme in dark mode
+	theme: Theme,
+	dark: bool,
+	/// Draw blocks colored by their share of live cells instead of the cells, see `draw_heatmap`
+	heatmap: bool,
+	show_grid: bool,
+	/// Color of the grid lines instead of the theme's
+	grid_color: Option<Color>,
+	/// Width of the grid lines in window pixels
+	grid_width: f32,
+	/// Snapshots of interesting generations, oldest first
+	bookmarks: Vec<(u64, Board)>,
+	/// Generation and board the simulation was last started from
+	run_start: Option<(u64, Board)>,
+	/// Recently simulated generations with their boards and ants, the latest last
+	history: VecDeque<(u64, Board, Option<Vec<Ant>>)>,
+	/// Boards from before each edit, the latest last
+	undo: Vec<Board>,
+	/// Boards undone since the last edit, the latest undone last
+	redo: Vec<Board>,
+	/// While simulating, skip drawing the board and spend each tick on as many generations as fit
+	frozen: bool,
+	/// Generations simulated per second while rendering
+	speed: f32,
+	/// Generations owed to `speed` that did not make a whole generation yet
+	pending_steps: f32,
+	/// When `pending_steps` was last brought up to date
+	last_update: Instant,
+	max_gens_per_frame: u64,
+	hud: Option<Hud>,
+	minimap: Minimap,
+	/// Thumbnails of the pattern library, drawn while `browsing`
+	browser: Browser,
+	/// Pattern highlighted in the open pattern browser
+	browsing: Option<usize>,
+	/// Paint on the nearest live cell within `snap_radius` of the cursor instead of the cell under it
+	snap: bool,
+	snap_radius: usize,
+	snap_target: Option<(usize, usize)>,
+	/// Cell under the cursor, shown in the corner
+	hovered: Option<(usize, usize)>,
+	stats: RunStats,
+	/// The board was edited since the run started, starting the simulation starts a new run
+	edited: bool,
+	report_path: PathBuf,
+	/// Where `S` saves the board
+	save_path: PathBuf,
+	screenshot_path: PathBuf,
+	screenshot_scale: usize,
+	/// Copies of the board screenshots repeat across and down, only seamless on a wrapped board
+	tiles: (usize, usize),
+	record_path: PathBuf,
+	record_generations: u64,
+	/// GIF every simulated generation is added to while recording
+	recording: Option<Recording>,
+	report_on_exit: bool,
+	/// Allow painting while the simulation is running
+	sandbox: bool,
+	/// Track and draw cells that would have been born just outside the board
+	show_losses: bool,
+	lost: Vec<LostCell>,
+	/// Where the session is autosaved, see `--session`
+	session_path: Option<PathBuf>,
+	last_autosave: Instant,
+	/// Sink every generation is written to, see `--stream`
+	stream: Option<Stream>,
+	/// Cells that changed state in the last generation
+	changed: usize,
+	/// Draw a status line with generation, population and activity, and the population graph
+	show_status: bool,
+	/// Population of the most recent generations, the latest last
+	population_history: VecDeque<usize>,
+	/// Draw frames, ticks and generations per second in the corner
+	show_rates: bool,
+	frame_rate: RateMeter,
+	tick_rate: RateMeter,
+	generation_rate: RateMeter,
+	/// Text the user is typing and what it is for, drawn in place of the status line
+	entry: Option<(Prompt, String)>,
+	/// Generation a run started with `InputAction::RunFor` pauses at
+	run_until: Option<u64>,
+	/// Simulate Wireworld instead of the rule
+	wireworld: bool,
+	/// Rule the ants follow
+	turmite: Turmite,
+	/// While set, the ants walk the board and the rule is not simulated
+	ants: Option<Vec<Ant>>,
+	/// Short message and when it was shown, see `notify`
+	notice: Option<(String, Instant)>,
+}
+
+/// Everything the next state of a cell depends on, borrowed from the game. Unlike the game it can be shared
+/// between the threads that simulate the rows of a generation.
+#[derive(Debug, Copy, Clone)]
+struct Neighborhood<'a> {
+	board: &'a Board,
+	rule_table: Option<&'a RuleTable>,
+	ltl: Option<&'a LargerThanLife>,
+	kernel: Option<&'a Kernel>,
+	rule_file: Option<&'a GollyRule>,
+	topology: Topology,
+	boundary: Boundary,
+	margin: usize,
+	wireworld: bool,
+	colors: u8,
+	states: u8,
+	birth_chance: f32,
+	survive_chance: f32,
+	seed: u64,
+	generation: u64,
+}
+
+impl<'a> Neighborhood<'a> {
+	fn width(&self) -> usize {
+		self.board.first().map_or(0, Vec::len)
+	}
+
+	const fn height(&self) -> usize {
+		self.board.len()
+	}
+
+	fn count_neighbors(&self, x: usize, y: usize) -> usize {
+		self.neighbor_mask(x, y).count_ones() as usize
+	}
+
+	/// Live cells in the neighborhood of a Larger than Life `rule`, including the cell itself if the rule says so
+	fn count_in_radius(&self, x: usize, y: usize, rule: &LargerThanLife) -> usize {
+		let r = rule.radius as isize;
+		let mut count = 0;
+		for dy in -r..=r {
+			for dx in -r..=r {
+				if ((dx, dy) == (0, 0) && !rule.middle) || (rule.reach == Reach::VonNeumann && dx.abs() + dy.abs() > r) {
+					continue;
+				}
+				if self.neighbor(x, y, dx, dy).is_some_and(|(x, y)| self.board[y][x].is_alive()) {
+					count += 1;
+				}
+			}
+		}
+		count
+	}
+
+	/// Live neighbors of a cell as a bitmask, bit `i` is the `i`th offset of the active kernel
+	fn neighbor_mask(&self, x: usize, y: usize) -> u8 {
+		let mut mask = 0;
+		for (i, (dx, dy)) in self.kernel(y).iter().enumerate() {
+			if self.neighbor(x, y, *dx, *dy).is_some_and(|(x, y)| self.board[y][x].is_alive()) {
+				mask |= 1 << i;
+			}
+		}
+		mask
+	}
+
+	/// Neighbor offsets of the cells in row `y`
+	fn kernel(&self, y: usize) -> &'a [(isize, isize)] {
+		match (self.kernel, self.topology) {
+			(Some(kernel), _) => kernel.offsets(),
+			(None, Topology::Square) => &KERNEL,
+			(None, Topology::Hex) if y.is_multiple_of(2) => &HEX_KERNEL_EVEN,
+			(None, Topology::Hex) => &HEX_KERNEL_ODD,
+			(None, Topology::VonNeumann) => &VON_NEUMANN_KERNEL,
+		}
+	}
+
+	/// Color a cell born at `x`, `y` takes: the most common color among its live neighbors. If there is
+	/// none, a color no neighbor has wins, which gives QuadLife's rule for three parents of different colors.
+	fn birth_color(&self, x: usize, y: usize) -> Cell {
+		let mut counts = [0; MAX_LIFE_COLORS];
+		for (dx, dy) in self.kernel(y) {
+			if let Some(color) = self.neighbor(x, y, *dx, *dy).and_then(|(x, y)| self.board[y][x].color()) {
+				counts[usize::from(color) % MAX_LIFE_COLORS] += 1;
+			}
+		}
+		let counts = &counts[..usize::from(self.colors)];
+		let most = counts.iter().max().copied().unwrap_or_default();
+		let mut common = (0..counts.len()).filter(|color| counts[*color] == most);
+		let color = match (common.next(), common.next()) {
+			(Some(color), None) => color,
+			(first, _) => counts.iter().position(|count| *count == 0).or(first).unwrap_or_default(),
+		};
+		Cell::colored(color as u8)
+	}
+
+	/// Next state of the cell at `x`, `y` if it were `cell`, with births and survivals only happening at their chance
+	fn next_cell(&self, rule: &Ruleset, cell: Cell, x: usize, y: usize) -> Cell {
+		let next = self.rule_next_cell(rule, cell, x, y);
+		match (cell, next) {
+			(Cell::Dead, Cell::Alive) if self.roll(x, y) >= self.birth_chance => Cell::Dead,
+			(Cell::Dead, Cell::Alive) if self.colors > 1 => self.birth_color(x, y),
+			(cell, Cell::Alive) if cell.is_alive() && self.roll(x, y) >= self.survive_chance => {
+				if self.states > 2 {
+					Cell::Dying(1)
+				} else {
+					Cell::Dead
+				}
+			}
+			// NOTE(Simon): rules only know a single live state, survivors keep their color
+			(cell, Cell::Alive) if cell.is_alive() => cell,
+			_ => next,
+		}
+	}
+
+	/// Uniform number in `0..1` for the cell at `x`, `y` in this generation. It only depends on the seed, the
+	/// position and the generation, so a run with the same seed plays out the same way.
+	fn roll(&self, x: usize, y: usize) -> f32 {
+		let hash = mix(self.seed ^ mix(self.generation ^ mix((y * self.width() + x) as u64)));
+		(hash >> 40) as f32 / (1u64 << 24) as f32
+	}
+
+	/// Next state of the cell at `x`, `y` if it were `cell`, under the rule table if there is one and `rule` otherwise
+	fn rule_next_cell(&self, rule: &Ruleset, cell: Cell, x: usize, y: usize) -> Cell {
+		if self.wireworld {
+			return rule::wireworld(cell, self.count_neighbors(x, y));
+		}
+		if let Some(ltl) = self.ltl {
+			return ltl.next(cell, self.count_in_radius(x, y, ltl));
+		}
+		if let Some(rule_file) = self.rule_file {
+			let mut states = [0; 8];
+			for (state, (dx, dy)) in states.iter_mut().zip(rule_file.offsets()) {
+				if let Some((x, y)) = self.neighbor(x, y, *dx, *dy) {
+					*state = self.board[y][x].state();
+				}
+			}
+			return Cell::from_state(rule_file.next(cell.state(), &states[..rule_file.offsets().len()]));
+		}
+		self.rule_table.map_or_else(
+			|| rule.next(cell, self.count_neighbors(x, y)),
+			|table| table.next(cell, self.neighbor_mask(x, y)),
+		)
+	}
+
+	/// Cell `dx`, `dy` away from `x`, `y`, `None` if that is past the edge of the simulated area and the boundary is dead
+	fn neighbor(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<(usize, usize)> {
+		let (nx, ny) = ((x as isize + dx) as usize, (y as isize + dy) as usize);
+		if !self.out_of_bounds(nx, ny) {
+			return Some((nx, ny));
+		}
+		// NOTE(Simon): the quiet zone stays dead, wrapping and mirroring only involve the cells inside it
+		let m = self.margin as isize;
+		match self.boundary {
+			Boundary::Dead => None,
+			Boundary::Wrap => {
+				let wrap = |v: usize, d: isize, count: usize| ((v as isize + d - m).rem_euclid(count as isize - 2 * m) + m) as usize;
+				Some((wrap(x, dx, self.width()), wrap(y, dy, self.height())))
+			}
+			Boundary::Mirror => {
+				// NOTE(Simon): the mirror sits on the edge itself, so the cell just past it is the edge cell again.
+				// Offsets reaching past the opposite edge too bounce back and forth between both mirrors
+				let mirror = |v: usize, d: isize, count: usize| {
+					let inside = count as isize - 2 * m;
+					let t = (v as isize + d - m).rem_euclid(2 * inside);
+					(if t < inside { t } else { 2 * inside - 1 - t } + m) as usize
+				};
+				Some((mirror(x, dx, self.width()), mirror(y, dy, self.height())))
+			}
+		}
+	}
+
+	// NOTE(Simon): we rely on overflowing the usize for checking indices which are out of bound in the negative direction
+	// NOTE(Simon): cells in the quiet zone count as out of bounds, so they never contribute to a neighbor count
+	fn out_of_bounds(&self, x: usize, y: usize) -> bool {
+		let m = self.margin;
+		x < m || y < m || x >= self.width() - m || y >= self.height() - m
+	}
+}
+
+impl Conway {
+	/// Empty board of `width` x `height` cells, the camera starts in its middle
+	pub fn with_size((width, height): (usize, usize)) -> Self {
+		Self {
+			current_board: vec![vec![Cell::Dead; width]; height],
+			new_board: vec![vec![Cell::Dead; width]; height],
+			mode: Mode::Editor(EditorMode::Drawing),
+			camera: Camera::centered((width, height)),
+			plane: None,
+			origin: (0, 0),
+			jump: DEFAULT_JUMP,
+			topology: Topology::Square,
+			boundary: Boundary::Dead,
+			rule: Topology::Square.default_rule(),
+			rule_table: None,
+			ltl: None,
+			kernel: None,
+			colors: 1,
+			paint_color: 0,
+			birth_chance: 1.0,
+			survive_chance: 1.0,
+			seed: 0,
+			rule_file: None,
+			rule_sources: RuleSources::default(),
+			margin: 0,
+			print_checksums: false,
+			checkerboard: false,
+			texture: false,
+			chunks: HashMap::new(),
+			chunk_style: None,
+			active: None,
+			last_stroke: None,
+			brush_radius: 0,
+			round_brush: false,
+			symmetry: Symmetry::None,
+			checkerboard_mesh: None,
+			active_region: None,
+			region_preview: None,
+			erase_preview: None,
+			fill_preview: None,
+			random_fills: 0,
+			soup_density: DEFAULT_SOUP_DENSITY,
+			selection: None,
+			selection_preview: None,
+			clipboard: None,
+			ghost: None,
+			paste_origin: None,
+			stamp_cells: None,
+			generation: 0,
+			born_at: vec![vec![0; width]; height],
+			died_at: vec![vec![0; width]; height],
+			pop: false,
+			ages: false,
+			trails: false,
+			theme: theme::THEMES[0],
+			dark: false,
+			heatmap: false,
+			show_grid: true,
+			grid_color: None,
+			grid_width: DEFAULT_GRID_WIDTH,
+			bookmarks: vec![],
+			run_start: None,
+			history: VecDeque::new(),
+			undo: vec![],
+			redo: vec![],
+			frozen: false,
+			speed: DEFAULT_SPEED,
+			pending_steps: 0.0,
+			last_update: Instant::now(),
+			max_gens_per_frame: DEFAULT_MAX_GENS_PER_FRAME,
+			hud: None,
+			minimap: Minimap::default(),
+			browser: Browser::default(),
+			browsing: None,
+			snap: false,
+			snap_radius: DEFAULT_SNAP_RADIUS,
+			snap_target: None,
+			hovered: None,
+			stats: RunStats::default(),
+			edited: true,
+			report_path: PathBuf::from(DEFAULT_REPORT_PATH),
+			save_path: PathBuf::from(DEFAULT_SAVE_PATH),
+			screenshot_path: PathBuf::from(DEFAULT_SCREENSHOT_PATH),
+			screenshot_scale: 1,
+			tiles: (1, 1),
+			record_path: PathBuf::from(DEFAULT_RECORD_PATH),
+			record_generations: DEFAULT_RECORD_GENERATIONS,
+			recording: None,
+			report_on_exit: false,
+			sandbox: false,
+			show_losses: false,
+			lost: vec![],
+			session_path: None,
+			last_autosave: Instant::now(),
+			stream: None,
+			changed: 0,
+			show_status: false,
+			population_history: VecDeque::new(),
+			show_rates: false,
+			frame_rate: RateMeter::default(),
+			tick_rate: RateMeter::default(),
+			generation_rate: RateMeter::default(),
+			entry: None,
+			run_until: None,
+			wireworld: false,
+			turmite: Turmite::langtons_ant(),
+			ants: None,
+			notice: None,
+		}
+	}
+
+	pub fn from_config(config: &Config) -> Self {
+		// NOTE(Simon): an explicit starting board wins over the saved session
+		let size = config.board_size();
+		let game = match (&config.share_code, &config.restored) {
+			(Some(code), _) => Self::from_share_code(code, size).expect("share code is validated when parsing the config"),
+			(None, Some(session)) if config.pattern.is_none() && config.macrocell.is_none() => Self::from_session(session, size),
+			_ => Self::with_size(size),
+		};
+		let rule = config.rule.unwrap_or_else(|| config.topology.map_or(game.rule, Topology::default_rule));
+		// NOTE(Simon): a rule given on the command line replaces the session's rule of any kind
+		let own_rule = config.rule.is_some()
+			|| config.ltl.is_some()
+			|| config.rule_table.is_some()
+			|| config.rule_file.is_some()
+			|| config.wireworld
+			|| config.turmite.is_some();
+		let own_kernel = config.kernel.is_some() || config.topology.is_some();
+		let mut game = Self {
+			current_board: Self::starting_board(config, game.current_board),
+			margin: config.margin,
+			plane: match (config.hashlife, config.infinite) {
+				(true, _) => Some(Plane::HashLife(HashLife::default())),
+				(false, true) => Some(Plane::Sparse(SparseBoard::default())),
+				(false, false) => None,
+			},
+			jump: config.jump,
+			camera: config.camera(),
+			// NOTE(Simon): stamps line up with the pattern `--at` put on the board
+			paste_origin: config.pattern_at,
+			topology: config.topology.unwrap_or(game.topology),
+			boundary: config.boundary.unwrap_or(game.boundary),
+			rule,
+			rule_table: if own_rule { config.rule_table.clone() } else { game.rule_table },
+			ltl: if own_rule { config.ltl } else { game.ltl },
+			kernel: if own_kernel { config.kernel.clone() } else { game.kernel },
+			colors: if own_rule || config.colors > 1 { config.colors } else { game.colors },
+			birth_chance: config.birth_chance,
+			survive_chance: config.survive_chance,
+			seed: config.seed.unwrap_or_else(|| {
+				SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64)
+			}),
+			rule_file: if own_rule { config.rule_file.clone() } else { game.rule_file },
+			rule_sources: RuleSources {
+				rule_table: if own_rule { config.rule_sources.rule_table.clone() } else { game.rule_sources.rule_table },
+				rule_file: if own_rule { config.rule_sources.rule_file.clone() } else { game.rule_sources.rule_file },
+				kernel: if own_kernel { config.rule_sources.kernel.clone() } else { game.rule_sources.kernel },
+			},
+			print_checksums: config.print_checksums,
+			checkerboard: config.checkerboard,
+			texture: config.texture,
+			pop: config.pop,
+			ages: config.ages,
+			trails: config.trails,
+			theme: if config.dark { config.theme.inverted() } else { *config.theme },
+			dark: config.dark,
+			heatmap: config.heatmap,
+			show_grid: config.grid,
+			grid_color: config.grid_color,
+			grid_width: config.grid_width,
+			snap_radius: config.snap_radius,
+			soup_density: config.soup_density,
+			max_gens_per_frame: config.max_gens_per_frame,
+			report_path: config.report_path.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_REPORT_PATH)),
+			report_on_exit: config.report_path.is_some(),
+			save_path: config.save_path.clone(),
+			screenshot_path: config.screenshot_path.clone(),
+			screenshot_scale: config.screenshot_scale,
+			tiles: config.tile_export.map_or((1, 1), |(rows, columns)| (columns, rows)),
+			record_path: config.record_path.clone(),
+			record_generations: config.record_generations,
+			recording: None,
+			sandbox: config.sandbox,
+			wireworld: config.wireworld || (!own_rule && game.wireworld),
+			turmite: config.turmite.clone().unwrap_or(game.turmite),
+			ants: config.turmite.is_some().then(|| vec![Ant::new(size.0 / 2, size.1 / 2)]),
+			session_path: config.session_path.clone(),
+			stream: config.stream_path.as_ref().and_then(|path| match Stream::open(path) {
+				Ok(stream) => Some(stream),
+				Err(err) => {
+					eprintln!("could not open stream {}: {}", path.display(), err);
+					None
+				}
+			}),
+			..game
+		};
+		if let (Some(_), Some(reason)) = (&game.plane, game.dense_only()) {
+			eprintln!("not simulating on the infinite plane, it does not support {}", reason);
+			game.plane = None;
+		}
+		if let (Some(tree), Some((x, y)), Some(Plane::HashLife(_))) = (&config.macrocell, config.macrocell_origin(), &game.plane) {
+			// NOTE(Simon): the tree's root is centered on the plane, the board moves to where the pattern should be
+			let life = HashLife::from_macrocell(tree);
+			let ((x0, y0), _) = tree.bounds().unwrap_or_default();
+			let half = 1 << (tree.level() - 1);
+			game.origin = (x0 as i64 - half - x, y0 as i64 - half - y);
+			life.load(game.origin, &mut game.current_board);
+			game.plane = Some(Plane::HashLife(life));
+		}
+		if game.birth_chance < 1.0 || game.survive_chance < 1.0 {
+			println!("seed {}, pass it to --seed to repeat this run", game.seed);
+		}
+		game.start_run();
+		game
+	}
+
+	/// What keeps the game `config` describes from simulating on HashLife, see `dense_only`. Unlike that this
+	/// can be asked before there is a game.
+	pub fn plane_blocker(config: &Config) -> Option<&'static str> {
+		let topology = config.topology.unwrap_or(Topology::Square);
+		let probe = Self {
+			plane: Some(Plane::HashLife(HashLife::default())),
+			topology,
+			boundary: config.boundary.unwrap_or(Boundary::Dead),
+			rule: config.rule.unwrap_or_else(|| topology.default_rule()),
+			rule_table: config.rule_table.clone(),
+			ltl: config.ltl,
+			kernel: config.kernel.clone(),
+			colors: config.colors,
+			birth_chance: config.birth_chance,
+			survive_chance: config.survive_chance,
+			rule_file: config.rule_file.clone(),
+			margin: config.margin,
+			wireworld: config.wireworld,
+			ants: config.turmite.as_ref().map(|_| vec![]),
+			..Self::with_size((1, 1))
+		};
+		probe.dense_only()
+	}
+
+	fn starting_board(config: &Config, mut board: Board) -> Board {
+		if let (Some(pattern), Some((x, y))) = (&config.pattern, config.pattern_origin()) {
+			pattern.place(&mut board, x, y).expect("pattern placement is validated when parsing the config");
+		}
+		board
+	}
+
+	/// Encodes the live cells into a short token that can be pasted into `--share`
+	pub fn to_share_code(&self) -> String {
+		share::encode(&self.current_board)
+	}
+
+	/// Game on `board`, which must not be empty
+	pub fn from_board(board: Board) -> Self {
+		let size = (board[0].len(), board.len());
+		Self { current_board: board, ..Self::with_size(size) }
+	}
+
+	/// The same game under the birth/survival `rule`
+	pub fn with_rule(self, rule: Ruleset) -> Self {
+		Self { rule, ..self }
+	}
+
+	pub fn from_share_code(code: &str, size: (usize, usize)) -> Result<Self> {
+		Ok(Self {
+			current_board: share::decode(code, size)?,
+			..Self::with_size(size)
+		})
+	}
+
+	/// What the next states of the cells depend on, see `Neighborhood`
+	fn neighborhood(&self) -> Neighborhood<'_> {
+		Neighborhood {
+			board: &self.current_board,
+			rule_table: self.rule_table.as_ref(),
+			ltl: self.ltl.as_ref(),
+			kernel: self.kernel.as_ref(),
+			rule_file: self.rule_file.as_ref(),
+			topology: self.topology,
+			boundary: self.boundary,
+			margin: self.margin,
+			wireworld: self.wireworld,
+			colors: self.colors,
+			states: self.states(),
+			birth_chance: self.birth_chance,
+			survive_chance: self.survive_chance,
+			seed: self.seed,
+			generation: self.generation,
+		}
+	}
+
+	fn from_session(session: &SavedSession, size: (usize, usize)) -> Self {
+		Self {
+			current_board: share::decode_states(&session.board, size).expect("session board is validated when parsing the config"),
+			generation: session.generation,
+			mode: if session.simulating { Mode::Simulation } else { Mode::Editor(EditorMode::Drawing) },
+			topology: session.topology,
+			boundary: session.boundary,
+			rule: session.rule,
+			ltl: session.ltl,
+			rule_table: session.rule_table.as_ref().map(|(_, table)| table.clone()),
+			rule_file: session.rule_file.as_ref().map(|(_, rule)| rule.clone()),
+			kernel: session.kernel.as_ref().map(|(_, kernel)| kernel.clone()),
+			rule_sources: RuleSources {
+				rule_table: session.rule_table.as_ref().map(|(path, _)| path.clone()),
+				rule_file: session.rule_file.as_ref().map(|(path, _)| path.clone()),
+				kernel: session.kernel.as_ref().map(|(path, _)| path.clone()),
+			},
+			colors: session.colors,
+			wireworld: session.wireworld,
+			..Self::with_size(size)
+		}
+	}
+
+	pub fn update_board_state(&mut self) {
+		if let Some(ants) = &mut self.ants {
+			self.active = None;
+			self.turmite.step(&mut self.current_board, ants);
+			self.changed = ants.len();
+			self.generation += 1;
+			return;
+		}
+		if self.plane.is_some() {
+			match self.dense_only() {
+				Some(reason) => {
+					self.plane = None;
+					self.notify(format!("left the infinite plane, it does not support {}", reason));
+				}
+				None => {
+					self.advance_plane(1);
+					return;
+				}
+			}
+		}
+		let m = self.margin;
+		let (mut x0, mut y0, mut x1, mut y1) = (m, m, self.width() - m, self.height() - m);
+		if let Some(region) = self.active_region {
+			x0 = x0.max(region.x);
+			y0 = y0.max(region.y);
+			x1 = x1.min(region.x + region.width);
+			y1 = y1.min(region.y + region.height);
+		}
+		// NOTE(Simon): for B0 rules the board is stored relative to the background, see `Ruleset::for_generation`
+		let rule = self.rule.for_generation(self.generation, self.neighbors());
+		let dynamics = self.dynamics();
+		let active = self.active.take().filter(|active| Some(&active.dynamics) == dynamics.as_ref());
+		let changes = if let Some(active) = active {
+			let changes = self.step_active(&rule, &active.cells, (x0, y0), (x1, y1));
+			if self.show_losses {
+				self.track_losses(&rule);
+			}
+			for &(x, y, cell) in &changes {
+				if self.current_board[y][x] == Cell::Dead && cell.is_alive() {
+					self.born_at[y][x] = self.generation + 1;
+				}
+				self.current_board[y][x] = cell;
+			}
+			self.changed = changes.len();
+			changes.into_iter().map(|(x, y, _)| (x, y)).collect()
+		} else {
+			if self.active_region.is_some() {
+				// NOTE(Simon): cells outside the region are carried over unchanged, except that the margin stays dead
+				self.new_board.clone_from(&self.current_board);
+				self.clear_new_margin();
+			}
+			let changes = if self.packed() { self.step_packed(&rule) } else { self.step_rows(&rule, (x0, y0), (x1, y1)) };
+			if self.show_losses {
+				self.track_losses(&rule);
+			}
+			std::mem::swap(&mut self.current_board, &mut self.new_board);
+			self.clear_new_board();
+			changes
+		};
+		self.generation += 1;
+		for &(x, y) in &changes {
+			if self.current_board[y][x] == Cell::Dead {
+				self.died_at[y][x] = self.generation;
+			}
+		}
+		self.remember_changes(changes);
+	}
+
+	/// What the next generation depends on besides the board. Nothing when a cell's next state can change
+	/// even though its neighborhood did not, then every cell has to be simulated.
+	fn dynamics(&self) -> Option<Dynamics> {
+		let plain_b0 = self.rule.born(0) && self.rule_table.is_none() && self.ltl.is_none() && self.rule_file.is_none();
+		if plain_b0 || self.birth_chance < 1.0 || self.survive_chance < 1.0 || self.ants.is_some() {
+			return None;
+		}
+		Some(Dynamics {
+			generation: self.generation,
+			rule: self.rule,
+			ltl: self.ltl,
+			rule_table: self.rule_table.is_some(),
+			rule_file: self.rule_file.is_some(),
+			kernel: self.kernel.is_some(),
+			topology: self.topology,
+			boundary: self.boundary,
+			wireworld: self.wireworld,
+			colors: self.colors,
+			margin: self.margin,
+			region: self.active_region,
+		})
+	}
+
+	/// Size of the neighborhood the rule counts live cells in
+	fn neighbors(&self) -> usize {
+		self.kernel.as_ref().map_or_else(|| self.topology.neighbors(), |kernel| kernel.offsets().len())
+	}
+
+	/// Furthest a cell looks for its neighbors, along either axis
+	fn reach(&self) -> usize {
+		let furthest = |offsets: &[(isize, isize)]| {
+			offsets.iter().map(|(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs())).max().unwrap_or_default()
+		};
+		let rule_file = self.rule_file.as_ref().map_or(0, |rule_file| furthest(rule_file.offsets()));
+		let ltl = self.ltl.map_or(0, |ltl| ltl.radius);
+		let cells = self.neighborhood();
+		furthest(cells.kernel(0)).max(furthest(cells.kernel(1))).max(rule_file).max(ltl)
+	}
+
+	/// Keeps the cells that changed this generation, the next one only needs to simulate their neighborhoods.
+	/// Once too many changed that is no faster than simulating every cell.
+	fn remember_changes(&mut self, cells: Vec<(usize, usize)>) {
+		let neighborhood = (2 * self.reach() + 1).pow(2);
+		let few = cells.len() * neighborhood < self.width() * self.height() / ACTIVE_SHARE;
+		self.active = self.dynamics().filter(|_| few).map(|dynamics| ActiveCells { dynamics, cells });
+	}
+
+	/// Next states of the cells around `changed` that differ from their current state. Only cells from `x0`, `y0`
+	/// up to but excluding `x1`, `y1` are simulated.
+	fn step_active(
+		&self,
+		rule: &Ruleset,
+		changed: &[(usize, usize)],
+		(x0, y0): (usize, usize),
+		(x1, y1): (usize, usize),
+	) -> Vec<(usize, usize, Cell)> {
+		let r = self.reach() as isize;
+		let neighborhood = self.neighborhood();
+		let mut cells = Vec::with_capacity(changed.len() * (2 * r as usize + 1).pow(2));
+		for &(x, y) in changed {
+			for dy in -r..=r {
+				for dx in -r..=r {
+					// NOTE(Simon): the square around a cell is symmetric, so it also finds every cell that sees it
+					// across a wrapped or mirrored edge
+					if let Some((x, y)) = neighborhood.neighbor(x, y, dx, dy) {
+						if (x0..x1).contains(&x) && (y0..y1).contains(&y) {
+							cells.push((x, y));
+						}
+					}
+				}
+			}
+		}
+		cells.sort_unstable();
+		cells.dedup();
+		cells
+			.into_iter()
+			.filter_map(|(x, y)| {
+				let cell = self.current_board[y][x];
+				let next = neighborhood.next_cell(rule, cell, x, y);
+				(next != cell).then_some((x, y, next))
+			})
+			.collect()
+	}
+
+	/// Whether the rule can be simulated on a `BitBoard`
+	fn packed(&self) -> bool {
+		self.dense_only().is_none() && self.kernel.is_none() && self.topology == Topology::Square
+	}
+
+	/// Simulates the whole board a word of cells at a time, see `BitBoard`. Returns the cells that changed.
+	fn step_packed(&mut self, rule: &Ruleset) -> Vec<(usize, usize)> {
+		let board = BitBoard::from_board(&self.current_board);
+		let next = board.step(rule);
+		next.write(&mut self.new_board);
+		for (x, y) in next.born(&board) {
+			self.born_at[y][x] = self.generation + 1;
+		}
+		let changes: Vec<_> = next.changes(&board).collect();
+		self.changed = changes.len();
+		changes
+	}
+
+	/// Simulates the cells from `x0`, `y0` up to but excluding `x1`, `y1` one at a time. Returns the cells that
+	/// changed.
+	fn step_rows(&mut self, rule: &Ruleset, (x0, y0): (usize, usize), (x1, y1): (usize, usize)) -> Vec<(usize, usize)> {
+		// NOTE(Simon): rows are simulated in parallel, the threads write the boards taken out of `self` and read
+		// the rest through a borrowed `Neighborhood`
+		let (mut new_board, mut born_at) = (std::mem::take(&mut self.new_board), std::mem::take(&mut self.born_at));
+		let cells = self.neighborhood();
+		let changes: Vec<Vec<_>> = new_board
+			.par_iter_mut()
+			.zip(born_at.par_iter_mut())
+			.enumerate()
+			.take(y1)
+			.skip(y0)
+			.map(|(y, (new_row, born_row))| {
+				let mut changes = Vec::new();
+				for (x, cell) in cells.board[y].iter().enumerate().take(x1).skip(x0) {
+					let new_cell = cells.next_cell(rule, *cell, x, y);
+					new_row[x] = new_cell;
+					if new_cell != *cell {
+						changes.push((x, y));
+					}
+					if *cell == Cell::Dead && new_cell.is_alive() {
+						born_row[x] = cells.generation + 1;
+					}
+				}
+				changes
+			})
+			.collect();
+		self.new_board = new_board;
+		self.born_at = born_at;
+		let changes: Vec<_> = changes.into_iter().flatten().collect();
+		self.changed = changes.len();
+		changes
+	}
+
+	/// Advances the infinite plane, afterwards the board shows the part of the plane under it
+	fn advance_plane(&mut self, generations: u64) {
+		let Some(mut plane) = self.plane.take() else {
+			return;
+		};
+		self.active = None;
+		plane.store(self.origin, &self.current_board);
+		let cells = self.neighborhood();
+		plane.advance(&self.rule, [cells.kernel(0), cells.kernel(1)], generations);
+		plane.load(self.origin, &mut self.new_board);
+		self.changed = 0;
+		for (y, (row, new_row)) in self.current_board.iter().zip(&self.new_board).enumerate() {
+			for (x, (cell, new_cell)) in row.iter().zip(new_row).enumerate() {
+				if cell != new_cell {
+					self.changed += 1;
+				}
+				if *cell == Cell::Dead && new_cell.is_alive() {
+					self.born_at[y][x] = self.generation + generations;
+				}
+				if cell.is_alive() && *new_cell == Cell::Dead {
+					self.died_at[y][x] = self.generation + generations;
+				}
+			}
+		}
+		self.plane = Some(plane);
+		self.generation += generations;
+		std::mem::swap(&mut self.current_board, &mut self.new_board);
+	}
+
+	/// Skips `jump` generations at once, only HashLife is fast enough for that
+	fn jump(&mut self) {
+		if !matches!(self.plane, Some(Plane::HashLife(_))) {
+			self.notify("jumping ahead needs --hashlife".to_string());
+			return;
+		}
+		if let Some(reason) = self.dense_only() {
+			self.notify(format!("cannot jump ahead with {}", reason));
+			return;
+		}
+		self.advance_plane(self.jump);
+		self.stats.record(self.generation, self.population(), self.checksum());
+		self.notify(format!("jumped to generation {}", self.generation));
+	}
+
+	/// What keeps the infinite plane from simulating the current setup, it only knows two-state rules
+	fn dense_only(&self) -> Option<&'static str> {
+		Some(if self.wireworld {
+			"Wireworld"
+		} else if self.ants.is_some() {
+			"turmites"
+		} else if self.ltl.is_some() || self.rule_table.is_some() || self.rule_file.is_some() {
+			"rules other than birth/survival rules"
+		} else if self.rule.states() > 2 {
+			"Generations rules"
+		} else if self.rule.born(0) {
+			"B0 rules"
+		} else if self.colors > 1 {
+			"colored cells"
+		} else if self.birth_chance < 1.0 || self.survive_chance < 1.0 {
+			"birth and survival chances"
+		} else if self.margin > 0 {
+			"a margin"
+		} else if self.active_region.is_some() {
+			"regions"
+		} else if self.boundary != Boundary::Dead {
+			"wrapped or mirrored edges"
+		} else if self.plane.as_ref().is_some_and(|plane| {
+			let cells = self.neighborhood();
+			!plane.supports([cells.kernel(0), cells.kernel(1)])
+		}) {
+			"this neighborhood"
+		} else {
+			return None;
+		})
+	}
+
+	/// Fraction of the board that changed state in the last generation, 0 once the board has settled into a still life
+	pub fn activity(&self) -> f32 {
+		self.changed as f32 / (self.width() * self.height()) as f32
+	}
+
+	fn width(&self) -> usize {
+		self.current_board.first().map_or(0, Vec::len)
+	}
+
+	const fn height(&self) -> usize {
+		self.current_board.len()
+	}
+
+	/// Cells at least partly inside the window
+	fn visible(&self) -> Rectangle<usize> {
+		self.camera.visible((self.width(), self.height()))
+	}
+
+	/// Moves the camera by `offset` window pixels, it never leaves the board. On the infinite plane the board
+	/// slides along instead once the camera reaches its edge.
+	fn pan(&mut self, offset: Vector) {
+		self.camera.position += offset / self.camera.zoom;
+		let size = (self.width(), self.height());
+		let overflow = self.camera.clamp(size);
+		let Some(plane) = &mut self.plane else {
+			return;
+		};
+		// NOTE(Simon): slides are rounded up to whole cells and kept even, hex rows only line up with the plane
+		// while their parity does
+		let slide = |overflow: f32| {
+			let cells = (overflow.abs() / CELL_SIZE as f32).ceil() as i64;
+			overflow.signum() as i64 * (cells + cells % 2)
+		};
+		let (slide_x, slide_y) = (slide(overflow.x), slide(overflow.y));
+		if (slide_x, slide_y) == (0, 0) {
+			return;
+		}
+		// NOTE(Simon): whatever the slide overshot is made up for by moving the camera back
+		let back = Vector::new(overflow.x - (slide_x * CELL_SIZE as i64) as f32, overflow.y - (slide_y * CELL_SIZE as i64) as f32);
+		self.camera.position += back;
+		self.camera.clamp(size);
+		plane.store(self.origin, &self.current_board);
+		self.origin = (self.origin.0 + slide_x, self.origin.1 + slide_y);
+		plane.load(self.origin, &mut self.current_board);
+		self.active = None;
+		for row in self.born_at.iter_mut().chain(&mut self.died_at) {
+			row.fill(0);
+		}
+	}
+
+	/// Live cells on the board, and on the rest of the plane with `--infinite`
+	pub fn population(&self) -> usize {
+		let outside = self.plane.as_ref().map_or(0, |plane| plane.population_outside(self.origin, &self.current_board));
+		self.current_board.iter().flatten().filter(|cell| cell.is_alive()).count() + outside
+	}
+
+	/// Records the cells the rule would have born in the ring just outside the simulated area.
+	/// Under dead boundaries they are dropped, which otherwise looks like structures blinking out.
+	fn track_losses(&mut self, rule: &Ruleset) {
+		let generation = self.generation + 1;
+		self.lost.retain(|lost| generation - lost.generation < LOST_MARKER_GENERATIONS);
+		if self.boundary != Boundary::Dead {
+			// NOTE(Simon): nothing leaves a torus, and past a mirror is just the board again
+			return;
+		}
+		if self.rule_table.is_none() && self.ltl.is_none() && self.rule_file.is_none() && self.rule.born(0) {
+			// NOTE(Simon): the whole background is born under B0 rules, there is no meaningful edge loss
+			return;
+		}
+		let m = self.margin;
+		// NOTE(Simon): ring coordinates wrap below zero, neighbor_mask handles that like any other out of bounds index
+		let (x0, y0, x1, y1) = (m.wrapping_sub(1), m.wrapping_sub(1), self.width() - m, self.height() - m);
+		let mut ring = vec![];
+		for x in (m..x1).chain([x0, x1]) {
+			ring.push((x, y0));
+			ring.push((x, y1));
+		}
+		for y in m..y1 {
+			ring.push((x0, y));
+			ring.push((x1, y));
+		}
+		let cells = self.neighborhood();
+		ring.retain(|&(x, y)| cells.next_cell(rule, Cell::Dead, x, y) != Cell::Dead);
+		for (x, y) in ring {
+			let direction = (
+				if x == x0 { -1 } else { isize::from(x == x1) },
+				if y == y0 { -1 } else { isize::from(y == y1) },
+			);
+			let inside = |v: usize, before: usize, after: usize| if v == before { m } else { v.min(after - 1) };
+			let edge = (inside(x, x0, x1), inside(y, y0, y1));
+			self.lost.push(LostCell {
+				edge,
+				direction,
+				generation,
+			});
+		}
+	}
+
+	/// Stable hash of the live cells. Each live cell is hashed from its position alone and the hashes
+	/// are summed, so the result does not depend on iteration order or on how the board is stored.
+	pub fn checksum(&self) -> u64 {
+		let mut sum = 0u64;
+		for (y, row) in self.current_board.iter().enumerate() {
+			for (x, cell) in row.iter().enumerate() {
+				let stage = match cell {
+					Cell::Dead => continue,
+					Cell::Alive => 0,
+					Cell::Dying(stage) => u64::from(*stage),
+					Cell::Wire => u64::from(u8::MAX) + 1,
+					Cell::Colored(color) => u64::from(u8::MAX) + 1 + u64::from(*color),
+				};
+				sum = sum.wrapping_add(mix(stage << 48 | (y as u64) << 32 | x as u64));
+			}
+		}
+		let outside = self.plane.as_ref().map_or(0, |plane| plane.checksum_outside(self.origin, &self.current_board));
+		sum.wrapping_add(outside)
+	}
+
+	fn clear_new_board(&mut self) {
+		for row in &mut self.new_board {
+			unsafe {
+				let row_ptr = row.as_mut_ptr();
+				std::ptr::write_bytes(row_ptr, 0, row.len());
+			}
+		}
+	}
+
+	fn clear_new_margin(&mut self) {
+		let (m, width, height) = (self.margin, self.width(), self.height());
+		for (y, row) in self.new_board.iter_mut().enumerate() {
+			if y < m || y >= height - m {
+				row.fill(Cell::Dead);
+			} else {
+				row[..m].fill(Cell::Dead);
+				row[width - m..].fill(Cell::Dead);
+			}
+		}
+	}
+
+	/// Draws the visible cells through `camera` a chunk at a time, rebuilding only the meshes of chunks whose
+	/// cells changed since they were last drawn. Newborns get popped separately, see `draw_newborns`.
+	fn draw_cells(&mut self, frame: &mut Frame, camera: Transformation) {
+		let style = CellStyle {
+			wireworld: self.wireworld,
+			ants: self.ants.is_some(),
+			rule_file: self.rule_file.is_some(),
+			topology: self.topology,
+			states: self.states(),
+			ages: self.ages,
+			trails: self.trails,
+			theme: self.theme.name,
+			dark: self.dark,
+		};
+		if self.chunk_style != Some(style) {
+			self.chunks.clear();
+			self.chunk_style = Some(style);
+		}
+		let visible = self.visible();
+		let columns = visible.x / RENDER_CHUNK..(visible.x + visible.width).div_ceil(RENDER_CHUNK);
+		let rows = visible.y / RENDER_CHUNK..(visible.y + visible.height).div_ceil(RENDER_CHUNK);
+		self.chunks.retain(|(x, y), _| columns.contains(x) && rows.contains(y));
+		let mut target = frame.as_target();
+		let mut target = target.transform(camera);
+		for chunk_y in rows {
+			for chunk_x in columns.clone() {
+				let (x0, y0) = (chunk_x * RENDER_CHUNK, chunk_y * RENDER_CHUNK);
+				let (x1, y1) = ((x0 + RENDER_CHUNK).min(self.width()), (y0 + RENDER_CHUNK).min(self.height()));
+				let cells: Vec<_> = (y0..y1)
+					.flat_map(|y| (x0..x1).map(move |x| (x, y)))
+					.map(|(x, y)| (self.current_board[y][x], self.age_shade(x, y), self.trail_shade(x, y)))
+					.collect();
+				if self.chunks.get(&(chunk_x, chunk_y)).is_none_or(|(drawn, _)| *drawn != cells) {
+					let mut mesh = Mesh::new();
+					for (y, row) in self.current_board.iter().enumerate().take(y1).skip(y0) {
+						for (x, cell) in row.iter().enumerate().take(x1).skip(x0) {
+							self.fill_cell(&mut mesh, *cell, x, y, 0.0);
+						}
+					}
+					self.chunks.insert((chunk_x, chunk_y), (cells, mesh));
+				}
+				if let Some((_, mesh)) = self.chunks.get(&(chunk_x, chunk_y)) {
+					mesh.draw(&mut target);
+				}
+			}
+		}
+	}
+
+	/// Draws the cells born this generation `pop` larger over their cached mesh
+	fn draw_newborns(&self, mesh: &mut Mesh, pop: f32) {
+		if pop == 0.0 || self.generation == 0 {
+			return;
+		}
+		let visible = self.visible();
+		for (y, row) in self.current_board.iter().enumerate().skip(visible.y).take(visible.height) {
+			for (x, cell) in row.iter().enumerate().skip(visible.x).take(visible.width) {
+				if self.born_at[y][x] == self.generation {
+					self.fill_cell(mesh, *cell, x, y, pop);
+				}
+			}
+		}
+	}
+
+	fn fill_cell(&self, mesh: &mut Mesh, cell: Cell, x: usize, y: usize, pop: f32) {
+		if let Some((size, color)) = self.cell_look(cell, x, y, pop) {
+			self.fill_tile(mesh, x, y, size, color);
+		} else if let Some(color) = self.trail_color(x, y) {
+			self.fill_tile(mesh, x, y, CELL_SIZE as f32, color);
+		}
+	}
+
+	/// Fills the tile of the cell at `x`, `y`, `size` pixels across
+	fn fill_tile(&self, mesh: &mut Mesh, x: usize, y: usize, size: f32, color: Color) {
+		if self.topology == Topology::Hex {
+			mesh.fill(hexagon(hex_center(x, y), size), color);
+			return;
+		}
+		let inset = (CELL_SIZE as f32 - size) / 2.0;
+		mesh.fill(
+			Shape::Rectangle(Rectangle {
+				x: (x * CELL_SIZE) as f32 + inset,
+				y: (y * CELL_SIZE) as f32 + inset,
+				width: size,
+				height: size,
+			}),
+			color,
+		);
+	}
+
+	/// Shade of the live cell at `x`, `y` when coloring by age: 0 for a newborn cell, one more every time the
+	/// age doubles, up to `AGE_SHADES - 1`. Always 0 when not coloring by age.
+	fn age_shade(&self, x: usize, y: usize) -> u8 {
+		if !self.ages || !self.current_board[y][x].is_alive() {
+			return 0;
+		}
+		let age = self.generation.saturating_sub(self.born_at[y][x]);
+		((age + 1).ilog2() as u8).min(AGE_SHADES - 1)
+	}
+
+	/// How fresh the trail of the dead cell at `x`, `y` is: `TRAIL_LENGTH` in the generation it died, one less
+	/// every generation after that, 0 once it faded out. Always 0 without trails.
+	fn trail_shade(&self, x: usize, y: usize) -> u8 {
+		let died = self.died_at[y][x];
+		if !self.trails || died == 0 || died > self.generation || self.current_board[y][x] != Cell::Dead {
+			return 0;
+		}
+		TRAIL_LENGTH.saturating_sub((self.generation - died).min(u64::from(TRAIL_LENGTH)) as u8)
+	}
+
+	/// Color the trail of the dead cell at `x`, `y` is drawn in, nothing once it faded out
+	fn trail_color(&self, x: usize, y: usize) -> Option<Color> {
+		let shade = self.trail_shade(x, y);
+		(shade > 0).then(|| Color { a: TRAIL_COLOR.a * f32::from(shade) / f32::from(TRAIL_LENGTH), ..TRAIL_COLOR })
+	}
+
+	/// Size and color `cell` at `x`, `y` is drawn with, nothing for dead cells
+	fn cell_look(&self, cell: Cell, x: usize, y: usize, pop: f32) -> Option<(f32, Color)> {
+		Some(match (self.wireworld, cell) {
+			(_, Cell::Dead) => return None,
+			(true, Cell::Alive) => (CELL_SIZE as f32, HEAD_COLOR),
+			(true, Cell::Dying(_)) => (CELL_SIZE as f32, TAIL_COLOR),
+			(_, Cell::Wire) => (CELL_SIZE as f32, WIRE_COLOR),
+			(false, cell) if self.rule_file.is_some() => (CELL_SIZE as f32, self.rule_file_color(cell)),
+			(_, Cell::Alive | Cell::Colored(_)) => {
+				let newborn = self.generation != 0 && self.born_at[y][x] == self.generation;
+				let color = if self.ages {
+					let shade = f32::from(self.age_shade(x, y)) / f32::from(AGE_SHADES - 1);
+					let mix = |young: f32, old: f32| (old - young).mul_add(shade, young);
+					Color { r: mix(AGE_YOUNG.r, AGE_OLD.r), g: mix(AGE_YOUNG.g, AGE_OLD.g), b: mix(AGE_YOUNG.b, AGE_OLD.b), a: 1.0 }
+				} else {
+					match usize::from(cell.color().unwrap_or_default()) % MAX_LIFE_COLORS {
+						0 => self.theme.cell,
+						color => LIFE_COLORS[color - 1],
+					}
+				};
+				(if newborn { CELL_SIZE as f32 * (1.0 + pop) } else { CELL_SIZE as f32 }, color)
+			}
+			(false, Cell::Dying(stage)) if self.ants.is_some() => {
+				(CELL_SIZE as f32, STATE_COLORS[usize::from(stage.saturating_sub(1)) % STATE_COLORS.len()])
+			}
+			(false, Cell::Dying(stage)) => {
+				// NOTE(Simon): fade out towards the background, the last stage is still visible
+				let fade = f32::from(stage) / f32::from(self.states() - 1);
+				(CELL_SIZE as f32, Color { a: 1.0 - fade, ..DYING_COLOR })
+			}
+		})
+	}
+
+	/// Draws the visible cells as a texture with a texel per cell, which the GPU scales up, instead of
+	/// tessellating a shape for every cell
+	fn draw_texture(&self, frame: &mut Frame, camera: Transformation) {
+		let visible = self.visible();
+		if visible.width == 0 || visible.height == 0 {
+			return;
+		}
+		let mut texels = Vec::with_capacity(visible.width * visible.height * 4);
+		for (y, row) in self.current_board.iter().enumerate().skip(visible.y).take(visible.height) {
+			for (x, cell) in row.iter().enumerate().skip(visible.x).take(visible.width) {
+				let color = self.cell_look(*cell, x, y, 0.0).map(|(_, color)| color).or_else(|| self.trail_color(x, y));
+				let texel = color.map_or([0; 4], |color| color.to_rgba());
+				texels.extend_from_slice(&texel);
+			}
+		}
+		let Some(texture) = image::RgbaImage::from_raw(visible.width as u32, visible.height as u32, texels) else {
+			return;
+		};
+		match Image::from_image(frame.gpu(), &image::DynamicImage::ImageRgba8(texture)) {
+			Ok(image) => {
+				let quad = Quad {
+					position: Point::new((visible.x * CELL_SIZE) as f32, (visible.y * CELL_SIZE) as f32),
+					size: ((visible.width * CELL_SIZE) as f32, (visible.height * CELL_SIZE) as f32),
+					..Quad::default()
+				};
+				image.draw(quad, &mut frame.as_target().transform(camera));
+			}
+			Err(error) => eprintln!("could not upload the board texture: {}", error),
+		}
+	}
+
+	/// Live cells in view counted in square blocks at least `HEATMAP_BLOCK` window pixels across, each block
+	/// colored by its share of live cells. Blocks are aligned to the board so they stay put while panning.
+	fn draw_heatmap(&self, mesh: &mut Mesh) {
+		let cell = CELL_SIZE as f32 * self.camera.zoom;
+		let block = ((HEATMAP_BLOCK / cell).ceil() as usize).max(1).next_power_of_two();
+		let visible = self.visible();
+		let (x1, y1) = (visible.x + visible.width, visible.y + visible.height);
+		for y in (visible.y / block * block..y1).step_by(block) {
+			for x in (visible.x / block * block..x1).step_by(block) {
+				let (width, height) = ((x + block).min(self.width()) - x, (y + block).min(self.height()) - y);
+				let rows = &self.current_board[y..y + height];
+				let alive: usize = rows.iter().map(|row| row[x..x + width].iter().filter(|cell| cell.is_alive()).count()).sum();
+				if alive == 0 {
+					continue;
+				}
+				let area = Rectangle {
+					x: (x * CELL_SIZE) as f32,
+					y: (y * CELL_SIZE) as f32,
+					width: (width * CELL_SIZE) as f32,
+					height: (height * CELL_SIZE) as f32,
+				};
+				mesh.fill(Shape::Rectangle(area), heat_color(alive as f32 / (width * height) as f32));
+			}
+		}
+	}
+
+	fn draw_margin(&self, mesh: &mut Mesh) {
+		if self.margin == 0 {
+			return;
+		}
+		let m = (self.margin * CELL_SIZE) as f32;
+		let (w, h) = ((self.width() * CELL_SIZE) as f32, (self.height() * CELL_SIZE) as f32);
+		let strips = [
+			(0.0, 0.0, w, m),
+			(0.0, h - m, w, m),
+			(0.0, m, m, h - m - m),
+			(w - m, m, m, h - m - m),
+		];
+		for (x, y, width, height) in strips {
+			mesh.fill(Shape::Rectangle(Rectangle { x, y, width, height }), self.theme.margin);
+		}
+	}
+
+	/// Cell under a cursor position, nothing off the board
+	fn cell_under(&self, p: Point) -> Option<(usize, usize)> {
+		let (x, y) = match self.topology {
+			Topology::Square | Topology::VonNeumann => ((p.x / CELL_SIZE as f32) as usize, (p.y / CELL_SIZE as f32) as usize),
+			Topology::Hex => hex_at(p),
+		};
+		(p.x >= 0.0 && p.y >= 0.0 && x < self.width() && y < self.height()).then_some((x, y))
+	}
+
+	/// Cell under a cursor position, moved to the nearest live cell within the snap radius when snapping
+	fn target_cell(&self, p: Point) -> Option<(usize, usize)> {
+		let (x, y) = self.cell_under(p)?;
+		if !self.snap {
+			return Some((x, y));
+		}
+		let r = self.snap_radius;
+		let mut nearest = None;
+		let mut nearest_distance = usize::MAX;
+		for ny in y.saturating_sub(r)..=y.saturating_add(r).min(self.height() - 1) {
+			for nx in x.saturating_sub(r)..=x.saturating_add(r).min(self.width() - 1) {
+				let distance = nx.abs_diff(x).pow(2) + ny.abs_diff(y).pow(2);
+				if self.current_board[ny][nx].is_alive() && distance <= r.saturating_mul(r) && distance < nearest_distance {
+					nearest = Some((nx, ny));
+					nearest_distance = distance;
+				}
+			}
+		}
+		nearest.or(Some((x, y)))
+	}
+
+	/// Fading arrows on the edge cells next to recently lost cells, pointing the way they left
+	fn draw_losses(&self, mesh: &mut Mesh) {
+		if !self.show_losses {
+			return;
+		}
+		for lost in &self.lost {
+			let age = (self.generation - lost.generation) as f32 / LOST_MARKER_GENERATIONS as f32;
+			let ((x, y), (dx, dy)) = (lost.edge, lost.direction);
+			let length = ((dx * dx + dy * dy) as f32).sqrt();
+			let (dx, dy) = (dx as f32 / length, dy as f32 / length);
+			let size = CELL_SIZE as f32;
+			let center = Point::new((x as f32 + 0.5) * size, (y as f32 + 0.5) * size);
+			let tip = Point::new(dx.mul_add(0.5 * size, center.x), dy.mul_add(0.5 * size, center.y));
+			let base = Point::new(dx.mul_add(-0.2 * size, center.x), dy.mul_add(-0.2 * size, center.y));
+			let (px, py) = (-dy * 0.35 * size, dx * 0.35 * size);
+			mesh.fill(
+				Shape::Polyline {
+					points: vec![tip, Point::new(base.x + px, base.y + py), Point::new(base.x - px, base.y - py)],
+				},
+				Color {
+					a: 1.0 - age,
+					..LOST_COLOR
+				},
+			);
+		}
+	}
+
+	/// Draws every ant as a triangle pointing where it is headed
+	fn draw_ants(&self, mesh: &mut Mesh) {
+		let half = CELL_SIZE as f32 / 2.0;
+		for ant in self.ants.iter().flatten() {
+			let center = Point::new((ant.x * CELL_SIZE) as f32 + half, (ant.y * CELL_SIZE) as f32 + half);
+			let (dx, dy) = match ant.heading {
+				0 => (0.0, -half),
+				1 => (half, 0.0),
+				2 => (0.0, half),
+				_ => (-half, 0.0),
+			};
+			let points = vec![
+				Point::new(center.x + dx, center.y + dy),
+				Point::new(center.x - dx - dy, center.y - dy + dx),
+				Point::new(center.x - dx + dy, center.y - dy - dx),
+			];
+			mesh.fill(Shape::Polyline { points }, ANT_COLOR);
+		}
+	}
+
+	/// Crosshair on the top left corner of the paste origin, a cell long each way
+	fn draw_paste_origin(&self, mesh: &mut Mesh) {
+		let Some((x, y)) = self.paste_origin else {
+			return;
+		};
+		let cell = CELL_SIZE as f32;
+		let (cx, cy) = (x as f32 * cell, y as f32 * cell);
+		let width = 2.0 / self.camera.zoom;
+		mesh.stroke(Shape::Polyline { points: vec![Point::new(cx - cell, cy), Point::new(cx + cell, cy)] }, REGION_COLOR, width);
+		mesh.stroke(Shape::Polyline { points: vec![Point::new(cx, cy - cell), Point::new(cx, cy + cell)] }, REGION_COLOR, width);
+	}
+
+	fn draw_snap_target(&self, mesh: &mut Mesh) {
+		if let Some((x, y)) = self.snap_target {
+			mesh.stroke(
+				Shape::Rectangle(Rectangle {
+					x: (x * CELL_SIZE) as f32,
+					y: (y * CELL_SIZE) as f32,
+					width: CELL_SIZE as f32,
+					height: CELL_SIZE as f32,
+				}),
+				SNAP_COLOR,
+				2.0 / self.camera.zoom,
+			);
+		}
+	}
+
+	/// Mode, rule and the cell under the cursor, always shown in the bottom left corner
+	fn footer(&self) -> String {
+		let mode = match self.mode {
+			Mode::Editor(EditorMode::Drawing) => "editor",
+			Mode::Editor(EditorMode::Moving) => "editor, moving",
+			Mode::Simulation if self.sandbox => "simulation, sandbox",
+			Mode::Simulation => "simulation",
+		};
+		let mut footer = format!("{}  {}", mode, self.rule_name());
+		if let Some((x, y)) = self.hovered {
+			// NOTE(Simon): on the infinite plane the board is a window that slides along, show where on the plane
+			footer += &format!("  cell {},{}", self.origin.0 + x as i64, self.origin.1 + y as i64);
+		}
+		footer
+	}
+
+	/// Frames drawn, ticks updated and generations simulated in the last second, below the generation
+	fn draw_rates(&mut self, frame: &mut Frame) {
+		let (Some(hud), true) = (&mut self.hud, self.show_rates) else {
+			return;
+		};
+		let rates = [
+			format!("{} fps", self.frame_rate.per_second()),
+			format!("{} ticks/s", self.tick_rate.per_second()),
+			format!("{} gen/s", self.generation_rate.per_second()),
+		];
+		for (line, rate) in rates.iter().enumerate() {
+			hud.draw_corner(frame, rate, line + 1);
+		}
+	}
+
+	/// Population of the last `GRAPH_LENGTH` generations in window pixels, scaled to the highest of them
+	fn draw_population_graph(&self, frame: &mut Frame) {
+		let (x, y) = GRAPH_ORIGIN;
+		let mut mesh = Mesh::new();
+		let panel = Rectangle { x, y, width: GRAPH_LENGTH as f32, height: GRAPH_HEIGHT };
+		mesh.fill(Shape::Rectangle(panel), self.theme.panel);
+		mesh.stroke(Shape::Rectangle(panel), self.theme.grid, 1.0);
+		let peak = self.population_history.iter().copied().max().unwrap_or_default().max(1) as f32;
+		let points: Vec<_> = self
+			.population_history
+			.iter()
+			.enumerate()
+			.map(|(i, population)| Point::new(x + i as f32, GRAPH_HEIGHT.mul_add(1.0 - *population as f32 / peak, y)))
+			.collect();
+		if points.len() > 1 {
+			mesh.stroke(Shape::Polyline { points }, GRAPH_COLOR, 1.5);
+		}
+		mesh.draw(&mut frame.as_target());
+	}
+
+	/// Mirror axes, or the middle of the board that strokes are turned around
+	fn draw_symmetry(&self, mesh: &mut Mesh) {
+		let (width, height) = ((self.width() * CELL_SIZE) as f32, (self.height() * CELL_SIZE) as f32);
+		let (x, y) = (width / 2.0, height / 2.0);
+		let vertical = Shape::Polyline { points: vec![Point::new(x, 0.0), Point::new(x, height)] };
+		let horizontal = Shape::Polyline { points: vec![Point::new(0.0, y), Point::new(width, y)] };
+		let axes = match self.symmetry {
+			Symmetry::None => vec![],
+			Symmetry::Horizontal => vec![vertical],
+			Symmetry::Vertical => vec![horizontal],
+			Symmetry::FourFold => vec![vertical, horizontal],
+			Symmetry::Rotate2 | Symmetry::Rotate4 => vec![Shape::Circle { center: Point::new(x, y), radius: CELL_SIZE as f32 }],
+		};
+		for axis in axes {
+			mesh.stroke(axis, SYMMETRY_COLOR, 2.0 / self.camera.zoom);
+		}
+	}
+
+	fn draw_regions(&self, mesh: &mut Mesh) {
+		let regions = self.active_region.iter().chain(self.region_preview.iter()).map(|region| (region, REGION_COLOR));
+		let erased = self.erase_preview.iter().map(|region| (region, ERASE_COLOR));
+		let filled = self.fill_preview.iter().map(|(region, fill)| {
+			(region, if *fill == Fill::Dead { ERASE_COLOR } else { FILL_COLOR })
+		});
+		let selected = self.selection.iter().chain(self.selection_preview.iter()).map(|region| (region, SELECTION_COLOR));
+		for (region, color) in regions.chain(erased).chain(filled).chain(selected) {
+			mesh.stroke(
+				Shape::Rectangle(Rectangle {
+					x: (region.x * CELL_SIZE) as f32,
+					y: (region.y * CELL_SIZE) as f32,
+					width: (region.width * CELL_SIZE) as f32,
+					height: (region.height * CELL_SIZE) as f32,
+				}),
+				color,
+				2.0 / self.camera.zoom,
+			);
+		}
+	}
+
+	/// Checkerboard covering `cells` in `color`, in board pixels
+	fn checkerboard_mesh(cells: Rectangle<usize>, color: Color) -> Mesh {
+		let mut mesh = Mesh::new();
+		for y in cells.y..cells.y + cells.height {
+			let first = cells.x + (cells.x + y) % 2;
+			for x in (first..cells.x + cells.width).step_by(2) {
+				mesh.fill(
+					Shape::Rectangle(Rectangle {
+						x: (x * CELL_SIZE) as f32,
+						y: (y * CELL_SIZE) as f32,
+						width: CELL_SIZE as f32,
+						height: CELL_SIZE as f32,
+					}),
+					color,
+				);
+			}
+		}
+		mesh
+	}
+
+	/// Grid lines around the visible cells, in board pixels. They stay `grid_width` window pixels wide at any
+	/// zoom, and fade out as the cells get too small for them.
+	fn draw_grid(&self, mesh: &mut Mesh) {
+		let cell = CELL_SIZE as f32 * self.camera.zoom;
+		if !self.show_grid || cell < MIN_GRID_CELL {
+			return;
+		}
+		let visible = self.visible();
+		let width = self.grid_width / self.camera.zoom;
+		let color = self.grid_color.unwrap_or(self.theme.grid);
+		let fade = ((cell - MIN_GRID_CELL) / (GRID_FADE_CELL - MIN_GRID_CELL)).min(1.0);
+		let color = Color { a: color.a * fade, ..color };
+		if self.topology == Topology::Hex {
+			Self::draw_hex_grid(mesh, visible, width, color);
+			return;
+		}
+		let (top, bottom) = ((visible.y * CELL_SIZE) as f32, ((visible.y + visible.height) * CELL_SIZE) as f32);
+		let (left, right) = ((visible.x * CELL_SIZE) as f32, ((visible.x + visible.width) * CELL_SIZE) as f32);
+		for i in visible.x..visible.x + visible.width {
+			let i = i as f32;
+			let line = Shape::Polyline {
+				points: vec![Point::new(i * CELL_SIZE as f32, top), Point::new(i * CELL_SIZE as f32, bottom)],
+			};
+			mesh.stroke(line, color, width);
+		}
+		for i in visible.y..visible.y + visible.height {
+			let i = i as f32;
+			let line = Shape::Polyline {
+				points: vec![Point::new(left, i * CELL_SIZE as f32), Point::new(right, i * CELL_SIZE as f32)],
+			};
+			mesh.stroke(line, color, width);
+		}
+	}
+
+	/// Outlines every tile in `cells`: a zigzag along the top of each row, the bottom of the last row and the
+	/// vertical edges between neighbors
+	fn draw_hex_grid(mesh: &mut Mesh, cells: Rectangle<usize>, width: f32, color: Color) {
+		let corner = |x: usize, y: usize, i: usize| hex_corner(hex_center(x, y), i, CELL_SIZE as f32);
+		let columns = cells.x..cells.x + cells.width;
+		let last = columns.end - 1;
+		for y in cells.y..cells.y + cells.height {
+			let mut points: Vec<_> = columns.clone().flat_map(|x| [corner(x, y, 5), corner(x, y, 0)]).collect();
+			points.push(corner(last, y, 1));
+			mesh.stroke(Shape::Polyline { points }, color, width);
+			for x in columns.clone() {
+				let points = vec![corner(x, y, 5), corner(x, y, 4)];
+				mesh.stroke(Shape::Polyline { points }, color, width);
+			}
+			let points = vec![corner(last, y, 1), corner(last, y, 2)];
+			mesh.stroke(Shape::Polyline { points }, color, width);
+		}
+		let y = cells.y + cells.height - 1;
+		let mut points: Vec<_> = columns.flat_map(|x| [corner(x, y, 4), corner(x, y, 3)]).collect();
+		points.push(corner(last, y, 2));
+		mesh.stroke(Shape::Polyline { points }, color, width);
+	}
+
+	/// Remembers the current generation, dropping the oldest bookmarks once all slots are taken or they
+	/// would hold more than `BOOKMARK_CELLS`
+	pub fn bookmark(&mut self) {
+		let limit = snapshot_limit(BOOKMARK_CELLS, self.width() * self.height(), MAX_BOOKMARKS);
+		if self.bookmarks.len() >= limit {
+			self.bookmarks.drain(..=self.bookmarks.len() - limit);
+		}
+		self.bookmarks.push((self.generation, self.current_board.clone()));
+		self.notify(format!("bookmarked generation {} as {}", self.generation, self.bookmarks.len()));
+	}
+
+	pub fn recall(&mut self, index: usize) {
+		if let Some((generation, board)) = self.bookmarks.get(index) {
+			self.generation = *generation;
+			self.current_board.clone_from(board);
+			self.active = None;
+			if self.mode == Mode::Simulation {
+				self.start_run();
+			} else {
+				self.edited = true;
+			}
+		}
+	}
+
+	/// Kills every cell in `area`, like painting dead cells this needs the editor or `--sandbox`
+	fn erase(&mut self, area: Rectangle<usize>) {
+		if self.mode == Mode::Simulation && !self.sandbox {
+			return;
+		}
+		self.checkpoint();
+		self.clear(area);
+		self.active = None;
+		self.edited = true;
+	}
+
+	fn clear(&mut self, area: Rectangle<usize>) {
+		for row in &mut self.current_board[area.y..area.y + area.height] {
+			row[area.x..area.x + area.width].fill(Cell::Dead);
+		}
+	}
+
+	/// Remembers the board before an edit so it can be undone, anything undone so far is gone for good
+	fn checkpoint(&mut self) {
+		self.push_undo(self.current_board.clone());
+	}
+
+	/// Remembers `board` from before an edit. The edited board starts counting generations from 0 again, or
+	/// for B0 rules from the first generation with the same background.
+	fn push_undo(&mut self, board: Board) {
+		let limit = snapshot_limit(UNDO_CELLS, self.width() * self.height(), UNDO_LIMIT);
+		if self.undo.len() >= limit {
+			self.undo.drain(..=self.undo.len() - limit);
+		}
+		self.undo.push(board);
+		self.redo.clear();
+		let plain = self.rule_table.is_none() && self.ltl.is_none() && self.rule_file.is_none();
+		self.generation = if plain { self.rule.restart_generation(self.generation, self.neighbors()) } else { 0 };
+		for row in self.born_at.iter_mut().chain(&mut self.died_at) {
+			row.fill(0);
+		}
+	}
+
+	/// Goes back to the board before the latest edit, or forward again after undoing one. Like the edits
+	/// themselves this needs the editor or `--sandbox`.
+	fn undo(&mut self, redo: bool) {
+		if self.mode == Mode::Simulation && !self.sandbox {
+			return;
+		}
+		let (from, to) = if redo { (&mut self.redo, &mut self.undo) } else { (&mut self.undo, &mut self.redo) };
+		let Some(board) = from.pop() else {
+			self.notify(if redo { "nothing to redo" } else { "nothing to undo" }.to_string());
+			return;
+		};
+		to.push(std::mem::replace(&mut self.current_board, board));
+		self.active = None;
+		self.edited = true;
+	}
+
+	/// Sets every cell in `area` according to `fill`, like painting this needs the editor or `--sandbox`
+	fn fill(&mut self, area: Rectangle<usize>, fill: Fill) {
+		if self.mode == Mode::Simulation && !self.sandbox {
+			return;
+		}
+		self.checkpoint();
+		let live = if self.wireworld { Cell::Wire } else { Cell::colored(self.paint_color) };
+		self.random_fills += 1;
+		for y in area.y..area.y + area.height {
+			for x in area.x..area.x + area.width {
+				let alive = match fill {
+					Fill::Alive => true,
+					Fill::Dead => false,
+					Fill::Random => {
+						let hash = mix(self.seed ^ mix(self.random_fills ^ mix((y * self.width() + x) as u64)));
+						((hash >> 40) as f32 / (1u64 << 24) as f32) < self.soup_density
+					}
+				};
+				self.current_board[y][x] = if alive { live } else { Cell::Dead };
+			}
+		}
+		self.active = None;
+		self.edited = true;
+	}
+
+	/// Places library pattern `index` centered on `cell`, moved inwards where it would stick out of the board
+	fn stamp(&mut self, index: usize, cell: (usize, usize)) {
+		if self.mode == Mode::Simulation && !self.sandbox {
+			return;
+		}
+		let pattern = library::pattern(index);
+		let (x, y) = self.drop_corner(cell, (pattern.width, pattern.height));
+		self.active = None;
+		let before = self.current_board.clone();
+		match pattern.place(&mut self.current_board, x, y) {
+			Ok(()) => {
+				self.push_undo(before);
+				self.edited = true;
+			}
+			Err(err) => self.notify(format!("could not stamp {}: {}", library::name(index), err)),
+		}
+	}
+
+	/// Top left corner of something of `size` cells centered on `center`, moved inwards where it would stick
+	/// out of the board
+	fn placement(&self, (x, y): (usize, usize), (width, height): (usize, usize)) -> (usize, usize) {
+		let corner = |center: usize, size: usize, count: usize| center.saturating_sub(size / 2).min(count.saturating_sub(size));
+		(corner(x, width, self.width()), corner(y, height, self.height()))
+	}
+
+	/// Top left corner of a stamp or paste of `size` cells clicked at `cell`: the paste origin if there is one,
+	/// otherwise centered on the click, see `placement`
+	fn drop_corner(&self, cell: (usize, usize), size: (usize, usize)) -> (usize, usize) {
+		match self.paste_origin {
+			Some((x, y)) => (x.min(self.width() - 1), y.min(self.height() - 1)),
+			None => self.placement(cell, size),
+		}
+	}
+
+	fn set_paste_origin(&mut self, cell: (usize, usize)) {
+		if self.paste_origin == Some(cell) {
+			self.paste_origin = None;
+			self.notify("paste origin removed, stamps and pastes center on the click again".to_string());
+		} else {
+			self.paste_origin = Some(cell);
+			self.notify(format!("paste origin at {}, {}, stamps and pastes go there", cell.0, cell.1));
+		}
+	}
+
+	fn cells_in(&self, area: Rectangle<usize>) -> Board {
+		self.current_board[area.y..area.y + area.height].iter().map(|row| row[area.x..area.x + area.width].to_vec()).collect()
+	}
+
+	/// Puts the selected cells on the clipboard, cutting also clears them, which needs the editor or `--sandbox`
+	fn copy_selection(&mut self, cut: bool) {
+		let Some(area) = self.selection else {
+			self.notify("nothing selected, press M and drag to select".to_string());
+			return;
+		};
+		if cut && self.mode == Mode::Simulation && !self.sandbox {
+			return;
+		}
+		let cells = self.cells_in(area);
+		let live = cells.iter().flatten().filter(|cell| **cell != Cell::Dead).count();
+		self.clipboard = Some(cells);
+		if cut {
+			self.erase(area);
+		}
+		let verb = if cut { "cut" } else { "copied" };
+		self.notify(format!("{} {}x{} cells, {} of them live, Ctrl+V pastes", verb, area.width, area.height, live));
+	}
+
+	/// Copies the clipboard into the board centered on `cell`, leaving the cells under its dead cells as they are
+	fn paste(&mut self, cell: (usize, usize)) {
+		if self.mode == Mode::Simulation && !self.sandbox {
+			return;
+		}
+		let Some(clipboard) = &self.clipboard else {
+			return;
+		};
+		let (x, y) = self.drop_corner(cell, (clipboard[0].len(), clipboard.len()));
+		let before = self.current_board.clone();
+		overlay(&mut self.current_board, clipboard, x, y);
+		self.push_undo(before);
+		self.active = None;
+		self.edited = true;
+	}
+
+	/// Rotates or mirrors the selected cells in place, around the middle of the selection. Only while editing,
+	/// like transforming the whole board.
+	fn transform_selection(&mut self, transform: Transform) {
+		let Some(area) = self.selection else {
+			return;
+		};
+		if self.mode == Mode::Simulation {
+			return;
+		}
+		let (width, height) = if transform.swaps_dimensions() { (area.height, area.width) } else { (area.width, area.height) };
+		if width > self.width() || height > self.height() {
+			self.notify(format!("a {}x{} selection does not fit on the board once rotated", area.width, area.height));
+			return;
+		}
+		let cells = transform.apply(&self.cells_in(area));
+		let (x, y) = self.placement((area.x + area.width / 2, area.y + area.height / 2), (width, height));
+		self.checkpoint();
+		self.clear(area);
+		overlay(&mut self.current_board, &cells, x, y);
+		self.selection = Some(Rectangle { x, y, width, height });
+		self.active = None;
+		self.edited = true;
+	}
+
+	/// `area` moved by `dx`, `dy` cells, but no further than the edges of the board
+	fn moved(&self, area: Rectangle<usize>, dx: isize, dy: isize) -> Rectangle<usize> {
+		let shift = |start: usize, by: isize, size: usize, count: usize| (start as isize + by).clamp(0, (count - size) as isize) as usize;
+		Rectangle { x: shift(area.x, dx, area.width, self.width()), y: shift(area.y, dy, area.height, self.height()), ..area }
+	}
+
+	/// Moves the selected cells by the difference between `from` and `to`, only while editing. The cells they
+	/// leave become dead, the ones they land on are overwritten by their live cells.
+	fn move_selection(&mut self, (from_x, from_y): (usize, usize), (to_x, to_y): (usize, usize)) {
+		let Some(area) = self.selection else {
+			return;
+		};
+		if self.mode == Mode::Simulation || (from_x, from_y) == (to_x, to_y) {
+			return;
+		}
+		let target = self.moved(area, to_x as isize - from_x as isize, to_y as isize - from_y as isize);
+		let cells = self.cells_in(area);
+		self.checkpoint();
+		self.clear(area);
+		overlay(&mut self.current_board, &cells, target.x, target.y);
+		self.selection = Some(target);
+		self.active = None;
+		self.edited = true;
+	}
+
+	/// Translucent stamp or clipboard where a click would place it
+	fn draw_ghost(&self, mesh: &mut Mesh) {
+		let Some(cell) = self.ghost else {
+			return;
+		};
+		// NOTE(Simon): there is only a ghost while stamping or pasting, without a stamp we are pasting
+		let Some(cells) = self.stamp_cells.as_ref().map(|(_, cells)| cells).or(self.clipboard.as_ref()) else {
+			return;
+		};
+		let (x, y) = self.drop_corner(cell, (cells[0].len(), cells.len()));
+		for (dy, row) in cells.iter().enumerate().take(self.height() - y) {
+			for (dx, cell) in row.iter().enumerate().take(self.width() - x) {
+				if let Some((size, color)) = self.cell_look(*cell, x + dx, y + dy, 0.0) {
+					self.fill_tile(mesh, x + dx, y + dy, size, Color { a: GHOST_ALPHA, ..color });
+				}
+			}
+		}
+	}
+
+	/// Rotates or mirrors the whole board, only while editing
+	fn transform(&mut self, transform: Transform) {
+		if self.mode == Mode::Simulation {
+			return;
+		}
+		if transform.swaps_dimensions() && self.width() != self.height() {
+			self.notify(format!("cannot rotate a {}x{} board, rotations need a square board", self.width(), self.height()));
+			return;
+		}
+		self.checkpoint();
+		self.current_board = transform.apply(&self.current_board);
+		self.active = None;
+		self.born_at = transform.apply(&self.born_at);
+		self.died_at = transform.apply(&self.died_at);
+		self.lost.clear();
+		self.edited = true;
+	}
+
+	fn step(&mut self) {
+		self.update_board_state();
+		self.generation_rate.record(1);
+		let checksum = self.checksum();
+		let population = self.population();
+		self.stats.record(self.generation, population, checksum);
+		if self.population_history.len() == GRAPH_LENGTH {
+			self.population_history.pop_front();
+		}
+		self.population_history.push_back(population);
+		if self.print_checksums {
+			println!("{:016x}", checksum);
+		}
+		if let Some(stream) = &mut self.stream {
+			if !stream.send(self.generation, &self.current_board) {
+				self.stream = None;
+			}
+		}
+		self.record_frame();
+	}
+
+	fn start_run(&mut self) {
+		self.stats = RunStats::start(self.to_share_code(), self.generation, self.population(), self.checksum());
+		self.edited = false;
+	}
+
+	/// Rulestring of the active rule, `table` for a lookup-table rule, `wireworld` or `ant <turns>` for turmites
+	fn rule_name(&self) -> String {
+		if self.ants.is_some() {
+			return format!("ant {}", self.turmite);
+		}
+		match (self.wireworld, &self.ltl, &self.rule_file, &self.rule_table) {
+			(true, _, _, _) => "wireworld".to_string(),
+			(false, Some(ltl), _, _) => ltl.to_string(),
+			(false, None, Some(rule_file), _) => rule_file.name.clone(),
+			(false, None, None, Some(_)) => "table".to_string(),
+			(false, None, None, None) => self.rule.to_string(),
+		}
+	}
+
+	/// Color of `cell` under the rule file, from its `@COLORS` if it has one
+	fn rule_file_color(&self, cell: Cell) -> Color {
+		let state = cell.state();
+		match self.rule_file.as_ref().and_then(|rule_file| rule_file.color(state)) {
+			Some([r, g, b]) => Color::from_rgb(r, g, b),
+			None if state == 1 => self.theme.cell,
+			None => STATE_COLORS[usize::from(state - 2) % STATE_COLORS.len()],
+		}
+	}
+
+	/// Number of states of the active rule, see `Ruleset::states`
+	fn states(&self) -> u8 {
+		self.ltl.map_or_else(|| self.rule.states(), |ltl| ltl.states())
+	}
+
+	fn session(&self) -> Session<'static> {
+		Session {
+			rule: self.rule_name(),
+			topology: self.topology.name(),
+			boundary: self.boundary.name(),
+		}
+	}
+
+	pub fn write_report(&mut self) {
+		let message = match std::fs::write(&self.report_path, self.stats.to_json(&self.session())) {
+			Ok(()) => format!("wrote report to {}", self.report_path.display()),
+			Err(err) => format!("could not write report to {}: {}", self.report_path.display(), err),
+		};
+		self.notify(message);
+	}
+
+	/// Saves the board in the format picked by the extension of `--save`, it can be loaded again with `--load`
+	pub fn save(&mut self) {
+		if self.wireworld {
+			self.notify("saving Wireworld boards is not supported, patterns only keep live cells".into());
+			return;
+		}
+		let pattern = Pattern::from_board(&self.current_board);
+		let message = match pattern::write(&self.save_path, &pattern, self.pattern_rule().as_deref()) {
+			Ok(()) => format!("saved board to {}", self.save_path.display()),
+			Err(err) => format!("could not save board: {:#}", err),
+		};
+		self.notify(message);
+	}
+
+	/// The whole board with `screenshot_scale` pixels per cell, in the colors it is drawn in. Hex rows are not
+	/// offset, every cell is a square.
+	fn render(&self) -> image::RgbaImage {
+		export::render((self.width(), self.height()), self.screenshot_scale, self.theme.background, |x, y| {
+			let cell = self.current_board[y][x];
+			self.cell_look(cell, x, y, 0.0).map(|(_, color)| color).or_else(|| self.trail_color(x, y))
+		})
+	}
+
+	/// Size of `render` in pixels, nothing once that is more than `MAX_SCREENSHOT_PIXELS`
+	fn render_size(&mut self) -> Option<(usize, usize)> {
+		let (width, height) = (self.width() * self.screenshot_scale, self.height() * self.screenshot_scale);
+		if width * height > MAX_SCREENSHOT_PIXELS {
+			self.notify(format!("a {}x{} image is too large, lower --screenshot-scale", width, height));
+			return None;
+		}
+		Some((width, height))
+	}
+
+	/// Writes a PNG of the whole board to `screenshot_path`, repeated `tiles` times
+	fn screenshot(&mut self) {
+		let Some((width, height)) = self.render_size() else {
+			return;
+		};
+		let image = if self.tiles == (1, 1) {
+			self.render()
+		} else {
+			// NOTE(Simon): copies of a board with other edges don't line up, patterns would stop at the seams
+			if self.boundary != Boundary::Wrap {
+				self.notify("tiled screenshots need a wrapped board, see T".into());
+				return;
+			}
+			let (width, height) = (width * self.tiles.0, height * self.tiles.1);
+			if width * height > MAX_SCREENSHOT_PIXELS {
+				self.notify(format!("a {}x{} image is too large, lower --tile-export or --screenshot-scale", width, height));
+				return;
+			}
+			export::tile(&self.render(), self.tiles)
+		};
+		let (width, height) = image.dimensions();
+		let message = match export::write_png(&self.screenshot_path, &image) {
+			Ok(()) => format!("saved a {}x{} screenshot to {}", width, height, self.screenshot_path.display()),
+			Err(err) => format!("could not save the screenshot: {:#}", err),
+		};
+		self.notify(message);
+	}
+
+	/// Starts recording the next `record_generations` generations to `record_path`, or stops the recording early.
+	/// Frames are shown as long as a generation takes at the current speed.
+	fn toggle_recording(&mut self) {
+		if self.recording.is_some() {
+			self.finish_recording();
+			return;
+		}
+		let Some((width, height)) = self.render_size() else {
+			return;
+		};
+		let delay = (100.0 / self.speed).round().clamp(f32::from(MIN_FRAME_DELAY), f32::from(u16::MAX)) as u16;
+		match Recording::create(&self.record_path, (width as u32, height as u32), delay) {
+			Ok(recording) => {
+				self.recording = Some(recording);
+				self.record_frame();
+				let message = format!("recording {} generations to {}", self.record_generations, self.record_path.display());
+				self.notify(message);
+			}
+			Err(err) => self.notify(format!("could not start recording: {:#}", err)),
+		}
+	}
+
+	/// Adds the board to the recording, which stops once it holds `record_generations` generations after the
+	/// board it started from
+	fn record_frame(&mut self) {
+		if self.recording.is_none() {
+			return;
+		}
+		let image = self.render();
+		let Some(recording) = &mut self.recording else {
+			return;
+		};
+		if let Err(err) = recording.add(&image) {
+			self.recording = None;
+			self.notify(format!("stopped recording: {:#}", err));
+			return;
+		}
+		if recording.frames > self.record_generations {
+			self.finish_recording();
+		}
+	}
+
+	fn finish_recording(&mut self) {
+		if let Some(recording) = self.recording.take() {
+			let message = format!("saved {} frames to {}", recording.frames, recording.path().display());
+			// NOTE(Simon): the GIF is only complete once its encoder is dropped
+			drop(recording);
+			self.notify(message);
+		}
+	}
+
+	/// Cells of the board the brush covers when centered on `center`
+	fn brush_cells(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+		let r = self.brush_radius as isize;
+		let mut cells = vec![];
+		for dy in -r..=r {
+			for dx in -r..=r {
+				// NOTE(Simon): the extra `r` rounds the disc out, otherwise its edges end in single cells
+				if self.round_brush && dx * dx + dy * dy > r * r + r {
+					continue;
+				}
+				let (x, y) = (x as isize + dx, y as isize + dy);
+				if (0..self.width() as isize).contains(&x) && (0..self.height() as isize).contains(&y) {
+					cells.push((x as usize, y as usize));
+				}
+			}
+		}
+		cells
+	}
+
+	fn notify_brush(&mut self) {
+		let side = 2 * self.brush_radius + 1;
+		let shape = if self.round_brush { "round" } else { "square" };
+		self.notify(format!("brush: {} {}x{}", shape, side, side));
+	}
+
+	/// Rule written to saved patterns, rule tables have no rulestring
+	fn pattern_rule(&self) -> Option<String> {
+		match (&self.ltl, &self.rule_file, &self.rule_table) {
+			(Some(ltl), _, _) => Some(ltl.to_string()),
+			(None, Some(rule_file), _) => Some(rule_file.name.clone()),
+			(None, None, Some(_)) => None,
+			(None, None, None) => Some(format!("{}{}", self.rule, self.topology.suffix())),
+		}
+	}
+
+	/// Simulates `generations` generations without a window, then prints the board as RLE followed by the
+	/// run report, see `--headless`
+	pub fn run_headless(&mut self, generations: u64) {
+		self.start_run();
+		for _ in 0..generations {
+			self.step();
+		}
+		let pattern = Pattern::from_board(&self.current_board);
+		print!("{}", pattern.to_rle(self.pattern_rule().as_deref()));
+		print!("{}", self.stats.to_json(&self.session()));
+	}
+
+	/// Switches to the birth/survival or Larger than Life rule in `rule`, replacing a rule table. A rule with a
+	/// neighborhood suffix like `B2/S34H` also switches the neighborhood.
+	fn set_rule(&mut self, rule: &str) {
+		let (rule, topology) = rule::strip_topology(rule);
+		// NOTE(Simon): the neighborhood only switches along with a rule that parses
+		let topology = topology.unwrap_or(self.topology);
+		if LargerThanLife::matches(rule) {
+			match rule.parse::<LargerThanLife>() {
+				Ok(ltl) => {
+					self.topology = topology;
+					self.ltl = Some(ltl);
+					self.rule_table = None;
+					self.rule_file = None;
+					self.notify(format!("rule: {}", ltl));
+				}
+				Err(err) => self.notify(format!("invalid rule `{}`: {:#}", rule, err)),
+			}
+			return;
+		}
+		match rule.parse::<Ruleset>() {
+			// NOTE(Simon): switching into or out of a B0 rule keeps the stored board as is, if the background
+			// was alive at that point the board comes out inverted
+			Ok(rule) => {
+				self.topology = topology;
+				self.rule = rule;
+				self.rule_table = None;
+				self.ltl = None;
+				self.rule_file = None;
+				self.notify(format!("rule: {}", rule));
+			}
+			Err(err) => self.notify(format!("invalid rule `{}`: {:#}", rule, err)),
+		}
+	}
+
+	/// Switches to the preset after the current rule, or the first one if the rule isn't a preset
+	fn next_preset(&mut self) {
+		let current = Ruleset::PRESETS
+			.iter()
+			.position(|(_, rule)| *rule == self.rule && self.rule_table.is_none() && self.ltl.is_none() && self.rule_file.is_none());
+		let (name, rule) = Ruleset::PRESETS[current.map_or(0, |index| (index + 1) % Ruleset::PRESETS.len())];
+		self.rule = rule;
+		self.rule_table = None;
+		self.ltl = None;
+		self.rule_file = None;
+		self.notify(format!("{} ({})", name, rule));
+	}
+
+	/// Cycles between single-colored Life, Immigration and QuadLife, the rule stays the same
+	fn next_colors(&mut self) {
+		self.colors = match self.colors {
+			1 => 2,
+			2 => 4,
+			_ => 1,
+		};
+		self.paint_color = 0;
+		self.notify(
+			match self.colors {
+				2 => "Immigration, G picks the color to paint",
+				4 => "QuadLife, G picks the color to paint",
+				_ => "single color",
+			}
+			.to_string(),
+		);
+	}
+
+	/// Lets the ants walk, starting with one in the middle of the board, or takes them off it again
+	fn toggle_ants(&mut self) {
+		self.ants = match self.ants {
+			Some(_) => None,
+			None => Some(vec![Ant::new(self.width() / 2, self.height() / 2)]),
+		};
+		self.notify(match self.ants {
+			Some(_) => format!("turmite {}, middle click places another ant", self.turmite),
+			None => format!("rule: {}", self.rule_name()),
+		});
+	}
+
+	/// Paints the strokes recorded by `input` since the last call
+	fn apply_strokes(&mut self, input: &mut CustomInput) {
+		// NOTE(Simon): in sandbox mode this writes into the live board between two updates, which is fine
+		// as long as the simulation runs on this thread
+		for (p, cell, continued) in input.strokes.drain(..) {
+			if !continued {
+				self.last_stroke = None;
+			}
+			let Some(target) = self.target_cell(p) else {
+				continue;
+			};
+			// NOTE(Simon): a whole stroke is undone at once, from the first cell it painted on
+			if self.last_stroke.is_none() {
+				self.checkpoint();
+			}
+			let from = self.last_stroke.unwrap_or(target);
+			let (symmetry, size) = (self.symmetry, (self.width(), self.height()));
+			for center in line(from, target) {
+				for (x, y) in self.brush_cells(center).into_iter().flat_map(|position| symmetry.images(position, size)) {
+					self.current_board[y][x] = cell;
+				}
+			}
+			self.last_stroke = Some(target);
+			self.edited = true;
+			self.active = None;
+		}
+	}
+
+	/// Simulates the generations that are due since the last update, at most `max_gens_per_frame` of them
+	fn simulate(&mut self) {
+		// NOTE(Simon): the simulation runs on wall time instead of ticks, `speed` is independent of the tick rate
+		// and a tick simulates as many generations as are due, possibly none
+		let start = Instant::now();
+		let elapsed = start.duration_since(std::mem::replace(&mut self.last_update, start)).min(MAX_CATCH_UP);
 		if let Mode::Editor(_) = self.mode {
 			return;
 		}
-		self.update_board_state();
+		let budget = Duration::from_secs_f32(TICK_BUDGET / <Self as Game>::TICKS_PER_SECOND as f32);
+		if let Some(target) = self.run_until {
+			for _ in 0..self.max_gens_per_frame {
+				if self.generation >= target || start.elapsed() >= budget {
+					break;
+				}
+				self.step();
+			}
+			if self.generation >= target {
+				self.toggle_mode();
+				self.notify(format!("paused at generation {}", self.generation));
+			}
+			return;
+		}
+		if !self.frozen {
+			self.pending_steps += self.speed * elapsed.as_secs_f32();
+			let steps = self.pending_steps.floor();
+			self.pending_steps -= steps;
+			for _ in 0..(steps as u64).min(self.max_gens_per_frame) {
+				self.remember_generation();
+				self.step();
+				// NOTE(Simon): generations that don't fit into the tick are dropped, catching up later would only
+				// fall further behind
+				if start.elapsed() >= budget {
+					self.pending_steps = 0.0;
+					break;
+				}
+			}
+			return;
+		}
+		for _ in 0..self.max_gens_per_frame {
+			if start.elapsed() >= budget {
+				break;
+			}
+			self.step();
+		}
 	}
 
-	fn draw(&mut self, frame: &mut Frame, _timer: &Timer) {
-		frame.clear(Color::WHITE);
-		let mut mesh = Mesh::new();
-		self.draw_cells(&mut mesh);
-		Self::draw_grid(&mut mesh);
-		mesh.draw(&mut frame.as_target());
+	/// Shows `message` in place of the status line for a moment
+	fn notify(&mut self, message: String) {
+		self.notice = Some((message, Instant::now()));
+	}
+
+	fn save_session(&self) {
+		let Some(path) = &self.session_path else {
+			return;
+		};
+		let sources = (
+			with_source(&self.rule_table, &self.rule_sources.rule_table),
+			with_source(&self.rule_file, &self.rule_sources.rule_file),
+			with_source(&self.kernel, &self.rule_sources.kernel),
+		);
+		let (rule_table, rule_file, kernel) = match (self.ants.is_some(), sources) {
+			(false, (Some(rule_table), Some(rule_file), Some(kernel))) => (rule_table, rule_file, kernel),
+			// NOTE(Simon): a session that comes back under a different rule would be worse than none
+			(true, _) => return eprintln!("not saving the session to {}, turmites can't be saved", path.display()),
+			(false, _) => return eprintln!("not saving the session to {}, its rule was not read from a file", path.display()),
+		};
+		let session = SavedSession {
+			board: share::encode_states(&self.current_board),
+			generation: self.generation,
+			simulating: self.mode == Mode::Simulation,
+			size: (self.width(), self.height()),
+			topology: self.topology,
+			boundary: self.boundary,
+			rule: self.rule,
+			ltl: self.ltl,
+			rule_table,
+			rule_file,
+			kernel,
+			colors: self.colors,
+			wireworld: self.wireworld,
+		};
+		if let Err(err) = std::fs::write(path, session.to_string()) {
+			eprintln!("could not save session to {}: {}", path.display(), err);
+		}
 	}
 
-	fn interact(&mut self, input: &mut Self::Input, _: &mut Window) {
-		if input.action == InputAction::Pause {
-			input.action = InputAction::None;
-			self.toggle_mode();
-			input.mode = self.mode;
+	pub fn toggle_mode(&mut self) {
+		self.mode = match self.mode {
+			Mode::Editor(_) => Mode::Simulation,
+			Mode::Simulation => Mode::Editor(EditorMode::Drawing),
+		};
+		self.run_until = None;
+		if self.mode == Mode::Simulation {
+			self.run_start = Some((self.generation, self.current_board.clone()));
+			if self.edited {
+				self.start_run();
+			}
+		}
+	}
+
+	/// Keeps the current generation so it can be rewound to, dropping the oldest once the history is full
+	fn remember_generation(&mut self) {
+		let limit = (HISTORY_CELLS / (self.width() * self.height()).max(1)).clamp(1, HISTORY_LIMIT);
+		while self.history.len() >= limit {
+			self.history.pop_front();
 		}
+		self.history.push_back((self.generation, self.current_board.clone(), self.ants.clone()));
+	}
+
+	/// Goes back to the latest remembered generation
+	fn rewind(&mut self) {
+		let Some((generation, board, ants)) = self.history.pop_back() else {
+			self.notify("nothing to rewind, only generations simulated while drawing are kept".to_string());
+			return;
+		};
+		self.generation = generation;
+		self.current_board = board;
+		self.ants = ants;
+		self.active = None;
+		self.lost.clear();
+		self.edited = true;
+		self.notify(format!("rewound to generation {}, {} more to go back", generation, self.history.len()));
+	}
+
+	/// Puts back the board the simulation was last started from, which can be undone like an edit
+	fn reset(&mut self) {
+		let Some((generation, board)) = &self.run_start else {
+			self.notify("nothing to reset to, the simulation has not been started yet".to_string());
+			return;
+		};
+		let (generation, board) = (*generation, board.clone());
+		self.checkpoint();
+		self.generation = generation;
+		self.current_board = board;
+		self.active = None;
+		self.lost.clear();
 		if self.mode == Mode::Simulation {
+			self.start_run();
+		} else {
+			self.edited = true;
+		}
+		self.notify(format!("reset to generation {}", generation));
+	}
+
+	/// Draws with the colors of `theme`, inverted when `dark`
+	fn set_theme(&mut self, theme: &'static Theme, dark: bool) {
+		self.theme = if dark { theme.inverted() } else { *theme };
+		self.dark = dark;
+		// NOTE(Simon): the cell chunks notice the new colors in their style, the checkerboard does not
+		self.checkerboard_mesh = None;
+	}
+
+	/// Switches between the square and hex grid, resetting the rule to the topology's default
+	pub const fn toggle_topology(&mut self) {
+		self.topology = match self.topology {
+			Topology::Square | Topology::VonNeumann => Topology::Hex,
+			Topology::Hex => Topology::Square,
+		};
+		self.rule = self.topology.default_rule();
+	}
+
+	/// Switches between the Moore and von Neumann neighborhood, keeping the rule
+	pub fn toggle_von_neumann(&mut self) {
+		self.topology = match self.topology {
+			Topology::VonNeumann => Topology::Square,
+			Topology::Square | Topology::Hex => Topology::VonNeumann,
+		};
+		self.notify(format!("neighborhood: {}", self.topology.name()));
+	}
+}
+
+/// How many copies of a board of `cells` fit in a budget of `budget` cells, at least one and at most `most`
+fn snapshot_limit(budget: usize, cells: usize, most: usize) -> usize {
+	(budget / cells.max(1)).clamp(1, most)
+}
+
+/// `rule` paired with the file it was read from for a session, nothing when the file is unknown
+fn with_source<T: Clone>(rule: &Option<T>, source: &Option<PathBuf>) -> Option<Option<(PathBuf, T)>> {
+	match (rule, source) {
+		(Some(rule), Some(source)) => Some(Some((source.clone(), rule.clone()))),
+		(Some(_), None) => None,
+		(None, _) => Some(None),
+	}
+}
+
+// NOTE(Simon): splitmix64 finalizer, spreads neighboring positions over the whole u64 range
+pub const fn mix(mut z: u64) -> u64 {
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	z ^ (z >> 31)
+}
+
+impl Game for Conway {
+	const TICKS_PER_SECOND: u16 = 60;
+	type Input = CustomInput;
+	type LoadingScreen = ();
+
+	fn load(_window: &Window) -> Task<Self> {
+		(Task::succeed(|| Self::from_config(Config::get())), Hud::load())
+			.join()
+			.map(|(game, hud)| Self { hud: Some(hud), ..game })
+	}
+
+	fn update(&mut self, _: &Window) {
+		self.tick_rate.record(1);
+		if self.last_autosave.elapsed() >= AUTOSAVE_INTERVAL {
+			self.save_session();
+			self.last_autosave = Instant::now();
+		}
+		self.simulate();
+	}
+
+	fn draw(&mut self, frame: &mut Frame, _: &Timer) {
+		frame.clear(self.theme.background);
+		self.frame_rate.record(1);
+		if let Some(hud) = &mut self.hud {
+			hud.set_color(self.theme.text);
+		}
+		// NOTE(Simon): freezing only affects the simulation, we never want to edit blind
+		if self.frozen && self.mode == Mode::Simulation {
+			let message = format!("simulating... generation {}", self.generation);
+			if let Some(hud) = &mut self.hud {
+				hud.draw_centered(frame, &message);
+			}
+			self.draw_rates(frame);
+			return;
+		}
+		let camera = self.camera.transformation();
+		if self.checkerboard {
+			let visible = self.visible();
+			if self.checkerboard_mesh.as_ref().is_none_or(|(cells, _)| *cells != visible) {
+				self.checkerboard_mesh = Some((visible, Self::checkerboard_mesh(visible, self.theme.checker)));
+			}
+			if let Some((_, checkerboard)) = &self.checkerboard_mesh {
+				checkerboard.draw(&mut frame.as_target().transform(camera));
+			}
+		}
+		let mut mesh = Mesh::new();
+		self.draw_margin(&mut mesh);
+		let pop = if self.pop && self.mode == Mode::Simulation {
+			// NOTE(Simon): ease out until the next generation is due, so the cell has settled by the time it arrives
+			let progress = self.speed.mul_add(self.last_update.elapsed().as_secs_f32(), self.pending_steps).min(1.0);
+			POP_SCALE * (1.0 - progress).powi(2)
+		} else {
+			0.0
+		};
+		// NOTE(Simon): hex rows are offset by half a cell, a texture cannot line up with them
+		let texture = self.texture && self.topology != Topology::Hex;
+		if self.heatmap {
+			self.draw_heatmap(&mut mesh);
+		} else if texture {
+			self.draw_texture(frame, camera);
+		} else {
+			self.draw_cells(frame, camera);
+			self.draw_newborns(&mut mesh, pop);
+		}
+		self.draw_grid(&mut mesh);
+		self.draw_ants(&mut mesh);
+		self.draw_regions(&mut mesh);
+		if self.mode != Mode::Simulation {
+			self.draw_symmetry(&mut mesh);
+		}
+		self.draw_ghost(&mut mesh);
+		self.draw_paste_origin(&mut mesh);
+		self.draw_snap_target(&mut mesh);
+		self.draw_losses(&mut mesh);
+		mesh.draw(&mut frame.as_target().transform(camera));
+		let notice = self.notice.as_ref().filter(|(_, shown)| shown.elapsed() < NOTICE_DURATION);
+		let status = match (&self.entry, self.run_until, notice) {
+			(Some((prompt, entry)), _, _) => Some(format!("{}: {}_  (Enter applies, Escape cancels)", prompt.label(), entry)),
+			(None, Some(target), _) => Some(format!("running to generation {}, at {}  (P stops)", target, self.generation)),
+			(None, None, Some((message, _))) => Some(message.clone()),
+			(None, None, None) if self.show_status => Some(format!(
+				"generation {}  population {}  activity {:.2}%  {}  {} gen/s",
+				self.generation,
+				self.population(),
+				100.0 * self.activity(),
+				self.rule_name(),
+				self.speed
+			)),
+			(None, None, None) => None,
+		};
+		if self.show_status {
+			self.draw_population_graph(frame);
+		}
+		let footer = self.footer();
+		if let Some(hud) = &mut self.hud {
+			if let Some(status) = status {
+				hud.draw_status(frame, &status);
+			}
+			hud.draw_corner(frame, &format!("generation {}", self.generation), 0);
+			hud.draw_footer(frame, &footer);
+		}
+		self.minimap.draw(frame, &self.camera, &self.current_board, &self.theme);
+		self.draw_rates(frame);
+		if let (Some(selected), Some(hud)) = (self.browsing, &mut self.hud) {
+			self.browser.draw(frame, hud, &self.theme, selected);
+		}
+	}
+
+	fn on_close_request(&mut self) -> bool {
+		if self.report_on_exit {
+			self.write_report();
+		}
+		self.save_session();
+		self.finish_recording();
+		true
+	}
+
+	fn interact(&mut self, input: &mut Self::Input, window: &mut Window) {
+		// NOTE(Simon): a restored session can start out simulating
+		input.mode = self.mode;
+		input.live = if self.wireworld { Cell::Wire } else { Cell::colored(self.paint_color) };
+		input.ants = self.ants.is_some();
+		let window_size = (window.width(), window.height());
+		if window_size != self.camera.window {
+			self.camera.resize(window_size);
+			self.camera.clamp((self.width(), self.height()));
+		}
+		input.set_view(self.camera, (self.width(), self.height()));
+		match std::mem::replace(&mut input.action, InputAction::None) {
+			InputAction::Pause => {
+				self.toggle_mode();
+				input.mode = self.mode;
+			}
+			InputAction::ToggleTopology => self.toggle_topology(),
+			InputAction::ToggleVonNeumann => self.toggle_von_neumann(),
+			InputAction::Share => self.notify(self.to_share_code()),
+			InputAction::SetRegion(region) => self.active_region = region,
+			InputAction::Bookmark => self.bookmark(),
+			InputAction::Recall(index) => self.recall(index),
+			InputAction::ToggleRendering => self.frozen = !self.frozen,
+			InputAction::ToggleSnap => self.snap = !self.snap,
+			InputAction::WriteReport => self.write_report(),
+			InputAction::Save => self.save(),
+			InputAction::Screenshot => self.screenshot(),
+			InputAction::ToggleRecording => self.toggle_recording(),
+			InputAction::ToggleLosses => {
+				self.show_losses = !self.show_losses;
+				self.lost.clear();
+			}
+			InputAction::Transform(transform) => match (input.tool, &mut self.clipboard) {
+				(Tool::Paste, Some(clipboard)) => *clipboard = transform.apply(clipboard),
+				(Tool::Select, _) if self.selection.is_some() => self.transform_selection(transform),
+				_ => self.transform(transform),
+			},
+			InputAction::Erase(area) => self.erase(area),
+			InputAction::Select(selection) => self.selection = selection,
+			InputAction::Copy => self.copy_selection(false),
+			InputAction::Cut => self.copy_selection(true),
+			InputAction::Undo => self.undo(false),
+			InputAction::Redo => self.undo(true),
+			InputAction::Clear => self.erase(Rectangle { x: 0, y: 0, width: self.width(), height: self.height() }),
+			InputAction::Reset => self.reset(),
+			InputAction::Soup => {
+				let m = self.margin;
+				let inside = Rectangle { x: m, y: m, width: self.width() - 2 * m, height: self.height() - 2 * m };
+				self.fill(self.selection.unwrap_or(inside), Fill::Random);
+			}
+			InputAction::Step if self.mode != Mode::Simulation => {
+				if self.edited {
+					self.start_run();
+				}
+				self.remember_generation();
+				self.step();
+				self.notify(format!("generation {}", self.generation));
+			}
+			InputAction::Step => {},
+			InputAction::Rewind => {
+				if self.mode == Mode::Simulation {
+					self.toggle_mode();
+					input.mode = self.mode;
+				}
+				self.rewind();
+			}
+			InputAction::ChangeSpeed(faster) => {
+				self.speed = if faster { self.speed * 2.0 } else { self.speed / 2.0 }.clamp(MIN_SPEED, MAX_SPEED);
+				self.notify(format!("speed: {} generations per second", self.speed));
+			}
+			InputAction::ToggleMoving => {
+				self.mode = match self.mode {
+					Mode::Editor(EditorMode::Drawing) => Mode::Editor(EditorMode::Moving),
+					Mode::Editor(EditorMode::Moving) => Mode::Editor(EditorMode::Drawing),
+					Mode::Simulation => Mode::Simulation,
+				};
+				input.mode = self.mode;
+				self.notify(match self.mode {
+					Mode::Editor(EditorMode::Moving) => "moving: drag the board, or the selection to move its cells".to_string(),
+					Mode::Editor(EditorMode::Drawing) => "drawing".to_string(),
+					Mode::Simulation => "moving the board needs the editor, the arrow keys pan while simulating".to_string(),
+				});
+			}
+			InputAction::MoveSelection(from, to) => self.move_selection(from, to),
+			InputAction::NextSymmetry => {
+				self.symmetry = self.symmetry.next();
+				self.notify(format!("symmetry: {}", self.symmetry.name()));
+			}
+			InputAction::BeginPaste => match self.clipboard {
+				Some(_) => {
+					input.tool = Tool::Paste;
+					self.notify("click to paste, right click goes back to painting".to_string());
+				}
+				None => self.notify("the clipboard is empty, select with M and copy with Ctrl+C".to_string()),
+			},
+			InputAction::Paste(cell) => self.paste(cell),
+			InputAction::SetPasteOrigin(cell) => self.set_paste_origin(cell),
+			InputAction::Fill(area, fill) => self.fill(area, fill),
+			InputAction::SelectFill(fill) => match fill {
+				Some(fill) => self.notify(format!("fill: {}, drag a rectangle, right click goes back to painting", fill.name())),
+				None => self.notify("brush".to_string()),
+			},
+			InputAction::SelectStamp(index) => match index {
+				Some(index) => self.notify(format!("stamp: {}, click to place it, right click to put it away", library::name(index))),
+				None => self.notify("stamp put away".to_string()),
+			},
+			InputAction::Stamp(index, cell) => self.stamp(index, cell),
+			InputAction::SetRule(rule) => self.set_rule(&rule),
+			InputAction::RunFor(generations) => match generations.parse::<u64>() {
+				Ok(generations) if generations > 0 => {
+					if self.mode != Mode::Simulation {
+						self.toggle_mode();
+						input.mode = self.mode;
+					}
+					self.run_until = Some(self.generation.saturating_add(generations));
+				}
+				_ => self.notify(format!("cannot run for `{}` generations", generations)),
+			},
+			InputAction::NextPreset => self.next_preset(),
+			InputAction::ToggleWireworld => {
+				self.wireworld = !self.wireworld;
+				self.notify(if self.wireworld { "Wireworld".to_string() } else { format!("rule: {}", self.rule_name()) });
+			}
+			InputAction::ToggleAnts => self.toggle_ants(),
+			InputAction::Pan(dx, dy) => self.pan(Vector::new(dx as f32 * PAN_STEP, dy as f32 * PAN_STEP)),
+			InputAction::Zoom { steps, at_cursor } => {
+				let anchor = if at_cursor { input.window_cursor } else { self.camera.window_center() };
+				self.camera.zoom_by(anchor, steps);
+				self.camera.clamp((self.width(), self.height()));
+			}
+			InputAction::CenterOn((x, y)) => {
+				let (center, zoom, half) = (self.camera.window_center(), self.camera.zoom, CELL_SIZE as f32 / 2.0);
+				let (x, y) = ((x * CELL_SIZE) as f32 + half, (y * CELL_SIZE) as f32 + half);
+				self.camera.position = Point::new(x - center.x / zoom, y - center.y / zoom);
+				self.camera.clamp((self.width(), self.height()));
+			}
+			InputAction::ResetZoom => {
+				self.camera.zoom_to(self.camera.window_center(), 1.0);
+				self.camera.clamp((self.width(), self.height()));
+			}
+			InputAction::Jump => self.jump(),
+			InputAction::ToggleBoundary => {
+				self.boundary = match self.boundary {
+					Boundary::Dead => Boundary::Wrap,
+					Boundary::Wrap => Boundary::Mirror,
+					Boundary::Mirror => Boundary::Dead,
+				};
+				self.lost.clear();
+				self.notify(format!("boundary: {}", self.boundary.name()));
+			}
+			InputAction::NextColors => self.next_colors(),
+			InputAction::NextPaintColor => {
+				self.paint_color = (self.paint_color + 1) % self.colors;
+				self.notify(format!("painting color {}", self.paint_color + 1));
+			}
+			InputAction::PlaceAnt((x, y)) => self.ants.get_or_insert_with(Vec::new).push(Ant::new(x, y)),
+			InputAction::ToggleStatus => self.show_status = !self.show_status,
+			InputAction::ToggleGrid => self.show_grid = !self.show_grid,
+			InputAction::ToggleHeatmap => self.heatmap = !self.heatmap,
+			InputAction::ToggleRates => self.show_rates = !self.show_rates,
+			InputAction::ToggleAges => self.ages = !self.ages,
+			InputAction::ToggleTrails => self.trails = !self.trails,
+			InputAction::NextTheme => {
+				self.set_theme(self.theme.next(), self.dark);
+				self.notify(format!("theme: {}", self.theme.name));
+			}
+			InputAction::ToggleDark => {
+				let theme = Theme::named(self.theme.name).unwrap_or(&theme::THEMES[0]);
+				self.set_theme(theme, !self.dark);
+				self.notify(format!("dark mode {}", if self.dark { "on" } else { "off" }));
+			}
+			InputAction::ResizeBrush(d) => {
+				self.brush_radius = (self.brush_radius as isize + d).clamp(0, MAX_BRUSH_RADIUS as isize) as usize;
+				self.notify_brush();
+			}
+			InputAction::ToggleBrushShape => {
+				self.round_brush = !self.round_brush;
+				self.notify_brush();
+			}
+			InputAction::None => {},
+		}
+		self.region_preview = input.region_drag();
+		self.entry.clone_from(&input.entry);
+		self.erase_preview = input.erase_drag();
+		self.fill_preview = input.fill_drag();
+		self.selection_preview = input.select_drag().or_else(|| {
+			let (dx, dy) = input.move_drag()?;
+			self.selection.map(|area| self.moved(area, dx, dy))
+		});
+		input.selection = self.selection;
+		let dragged = std::mem::replace(&mut input.dragged, Vector::new(0.0, 0.0));
+		if dragged != Vector::new(0.0, 0.0) {
+			self.pan(dragged);
+			input.set_view(self.camera, (self.width(), self.height()));
+		}
+		self.ghost = input.ghost_target();
+		self.browsing = input.browser;
+		self.stamp_cells = match (input.tool, self.stamp_cells.take()) {
+			(Tool::Stamp(index), Some((cached, cells))) if cached == index => Some((index, cells)),
+			(Tool::Stamp(index), _) => {
+				let pattern = library::pattern(index);
+				let mut cells = vec![vec![Cell::Dead; pattern.width]; pattern.height];
+				pattern.place(&mut cells, 0, 0).expect("a pattern fits a board of its own size");
+				Some((index, cells))
+			}
+			_ => None,
+		};
+		self.hovered = self.cell_under(input.cursor);
+		if self.mode == Mode::Simulation && !self.sandbox {
+			self.snap_target = None;
+			self.last_stroke = None;
+			input.strokes.clear();
 			return;
 		}
-		while let Some(p) = input.mouse_points.pop() {
-			let x = (p.x / CELL_SIZE as f32) as usize;
-			let y = (p.y / CELL_SIZE as f32) as usize;
-			match input.action {
-				InputAction::PlaceAlive => self.current_board[y][x] = Cell::Alive,
-				InputAction::PlaceDead => self.current_board[y][x] = Cell::Dead,
-				InputAction::Pause => self.toggle_mode(),
-				InputAction::None => {},
+		self.apply_strokes(input);
+		self.snap_target = if self.snap { self.target_cell(input.cursor) } else { None };
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const R_PENTOMINO: [(usize, usize); 5] = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+	/// Board of `size` with `cells` alive, offset by `at`
+	fn board_with(size: (usize, usize), at: (usize, usize), cells: &[(usize, usize)]) -> Board {
+		let mut board = vec![vec![Cell::Dead; size.0]; size.1];
+		for (x, y) in cells {
+			board[at.1 + y][at.0 + x] = Cell::Alive;
+		}
+		board
+	}
+
+	#[derive(Debug, Copy, Clone, PartialEq)]
+	enum Engine {
+		Naive,
+		Packed,
+		Active,
+		Sparse,
+		HashLife,
+	}
+
+	/// Advances `game` by one generation on `engine` alone
+	fn step_on(game: &mut Conway, engine: Engine) {
+		match engine {
+			Engine::Naive | Engine::Packed => {
+				let rule = game.rule.for_generation(game.generation, game.topology.neighbors());
+				let (m, size) = (game.margin, (game.width(), game.height()));
+				if engine == Engine::Naive {
+					game.step_rows(&rule, (m, m), (size.0 - m, size.1 - m));
+				} else {
+					game.step_packed(&rule);
+				}
+				std::mem::swap(&mut game.current_board, &mut game.new_board);
+				game.clear_new_board();
+				game.generation += 1;
+			}
+			Engine::Active => {
+				assert!(game.active.is_some(), "generation {} would simulate every cell", game.generation);
+				game.update_board_state();
+			}
+			Engine::Sparse | Engine::HashLife => {
+				game.update_board_state();
+				assert!(game.plane.is_some());
+			}
+		}
+	}
+
+	#[test]
+	fn engines_agree() {
+		let board = board_with((96, 96), (46, 46), &R_PENTOMINO);
+		let engines = [Engine::Naive, Engine::Packed, Engine::Active, Engine::Sparse, Engine::HashLife];
+		let mut games: Vec<_> = engines
+			.iter()
+			.map(|engine| {
+				let mut game = Conway::from_board(board.clone());
+				match engine {
+					Engine::Active => {
+						let alive = R_PENTOMINO.iter().map(|(x, y)| (46 + x, 46 + y)).collect();
+						game.remember_changes(alive);
+					}
+					Engine::Sparse => game.plane = Some(Plane::Sparse(SparseBoard::default())),
+					Engine::HashLife => game.plane = Some(Plane::HashLife(HashLife::default())),
+					Engine::Naive | Engine::Packed => {}
+				}
+				game
+			})
+			.collect();
+		for generation in 1..=60 {
+			for (game, engine) in games.iter_mut().zip(engines) {
+				step_on(game, engine);
+			}
+			let expected = games[0].checksum();
+			for (game, engine) in games.iter().zip(engines) {
+				assert_eq!(game.checksum(), expected, "{:?} differs in generation {}", engine, generation);
+			}
+		}
+	}
+
+	#[test]
+	fn blinker_phase() {
+		for plane in [None, Some(Plane::Sparse(SparseBoard::default())), Some(Plane::HashLife(HashLife::default()))] {
+			let mut game = Conway::from_board(board_with((16, 16), (6, 7), &[(0, 0), (1, 0), (2, 0)]));
+			game.plane = plane;
+			let mut checksums = vec![game.checksum()];
+			for _ in 0..4 {
+				game.update_board_state();
+				checksums.push(game.checksum());
+			}
+			for generation in 0..3 {
+				assert_ne!(checksums[generation], checksums[generation + 1], "the blinker did not turn in generation {}", generation);
+				assert_eq!(checksums[generation], checksums[generation + 2], "the blinker did not come back in generation {}", generation);
+			}
+		}
+	}
+
+	#[test]
+	fn hex_neighbors() {
+		let mut game = Conway::from_board(vec![vec![Cell::Alive; 6]; 6]);
+		game.topology = Topology::Hex;
+		let cells = game.neighborhood();
+		// NOTE(Simon): odd rows are shifted half a cell to the right, see `HEX_KERNEL_ODD`
+		for ((x, y), count) in [((2, 2), 6), ((2, 3), 6), ((0, 0), 2), ((0, 1), 5), ((5, 1), 3), ((5, 2), 5), ((5, 5), 2)] {
+			assert_eq!(cells.count_neighbors(x, y), count, "neighbors of {}, {}", x, y);
+		}
+	}
+
+	#[test]
+	fn b0_across_edit() {
+		for rule in ["B03/S23", "B01/S2378"] {
+			let board = board_with((24, 24), (10, 10), &R_PENTOMINO);
+			let [mut edited, mut expected] = [(); 2].map(|_| Conway { rule: rule.parse().unwrap(), ..Conway::from_board(board.clone()) });
+			for generation in 0..8 {
+				// NOTE(Simon): only `edited` remembers the edit for undoing it, which restarts its generations
+				edited.checkpoint();
+				for game in [&mut edited, &mut expected] {
+					game.current_board[2 + generation][2] = Cell::Alive;
+					game.active = None;
+					game.update_board_state();
+				}
+				assert_eq!(edited.checksum(), expected.checksum(), "{} after an edit in generation {}", rule, generation);
+			}
+		}
+	}
+
+	#[test]
+	fn bookmarks() {
+		let mut game = Conway::from_board(board_with((16, 16), (4, 4), &R_PENTOMINO));
+		for _ in 0..MAX_BOOKMARKS + 3 {
+			game.bookmark();
+			game.update_board_state();
+		}
+		assert_eq!(game.bookmarks.len(), MAX_BOOKMARKS);
+		assert_eq!(game.bookmarks[0].0, 3, "the oldest bookmarks are dropped first");
+		game.recall(0);
+		assert_eq!(game.generation, 3);
+		assert_eq!(snapshot_limit(BOOKMARK_CELLS, BOOKMARK_CELLS / 4, MAX_BOOKMARKS), 4);
+		assert_eq!(snapshot_limit(BOOKMARK_CELLS, 2 * BOOKMARK_CELLS, MAX_BOOKMARKS), 1);
+	}
+
+	#[test]
+	fn overlapping_buttons() {
+		let mouse = |event| input::Event::Mouse(event);
+		let button = |button, state| mouse(mouse::Event::Input { state, button });
+		for right_first in [false, true] {
+			let mut input = CustomInput::new();
+			// NOTE(Simon): the brush the next move paints with, nothing while no button is held. Every move goes
+			// further than the drag threshold.
+			let mut x = 100.0;
+			let mut brush_after = |input: &mut CustomInput, event| {
+				input.update(event);
+				input.strokes.clear();
+				x += 50.0;
+				input.update(mouse(mouse::Event::CursorMoved { x, y: 200.0 }));
+				input.strokes.pop().map(|(_, cell, _)| cell)
+			};
+			assert_eq!(brush_after(&mut input, button(mouse::Button::Left, ButtonState::Pressed)), Some(Cell::Alive));
+			assert_eq!(brush_after(&mut input, button(mouse::Button::Right, ButtonState::Pressed)), Some(Cell::Dead));
+			let (first, second, left) = if right_first {
+				(mouse::Button::Right, mouse::Button::Left, Some(Cell::Alive))
+			} else {
+				(mouse::Button::Left, mouse::Button::Right, Some(Cell::Dead))
+			};
+			assert_eq!(brush_after(&mut input, button(first, ButtonState::Released)), left, "right released first: {}", right_first);
+			assert_eq!(brush_after(&mut input, button(second, ButtonState::Released)), None);
+		}
+	}
+
+	#[test]
+	fn pattern_browser() {
+		let mut input = CustomInput::new();
+		let open = |input: &mut CustomInput| {
+			input.ctrl_held = true;
+			input.key_pressed(KeyCode::B);
+			input.ctrl_held = false;
+		};
+		open(&mut input);
+		assert_eq!(input.browser, Some(0));
+		for key in [KeyCode::Up, KeyCode::Down, KeyCode::Down, KeyCode::P, KeyCode::Return] {
+			input.key_pressed(key);
+		}
+		assert_eq!((input.browser, input.tool), (None, Tool::Stamp(2)));
+		assert!(matches!(input.action, InputAction::SelectStamp(Some(2))));
+
+		// NOTE(Simon): the browser opens on the stamp in hand, Escape keeps it
+		input.action = InputAction::None;
+		open(&mut input);
+		assert_eq!(input.browser, Some(2));
+		for _ in 0..library::PATTERNS.len() {
+			input.key_pressed(KeyCode::Down);
+		}
+		assert_eq!(input.browser, Some(library::PATTERNS.len() - 1));
+		input.key_pressed(KeyCode::Escape);
+		assert_eq!((input.browser, input.tool), (None, Tool::Stamp(2)));
+		assert!(matches!(input.action, InputAction::None));
+	}
+
+	#[test]
+	fn click_within_threshold() {
+		let mouse = |event| input::Event::Mouse(event);
+		let button = |state| mouse(mouse::Event::Input { state, button: mouse::Button::Left });
+		let painted = |moves: &[(f32, f32)]| {
+			let mut game = Conway::with_size(DEFAULT_BOARD_SIZE);
+			let mut input = CustomInput::new();
+			input.update(mouse(mouse::Event::CursorMoved { x: 200.0, y: 200.0 }));
+			input.update(button(ButtonState::Pressed));
+			for (x, y) in moves {
+				input.update(mouse(mouse::Event::CursorMoved { x: *x, y: *y }));
+			}
+			input.update(button(ButtonState::Released));
+			game.apply_strokes(&mut input);
+			game.population()
+		};
+		// NOTE(Simon): jiggling within `DEFAULT_DRAG_THRESHOLD` pixels of the press
+		assert_eq!(painted(&[]), 1);
+		assert_eq!(painted(&[(202.0, 201.0), (198.0, 202.0), (200.5, 197.5)]), 1);
+		assert!(painted(&[(260.0, 200.0)]) > 1, "a drag paints a line");
+	}
+
+	#[test]
+	fn huge_snap_radius() {
+		let mut game = Conway::from_board(board_with((20, 20), (7, 5), &[(0, 0)]));
+		game.snap = true;
+		game.snap_radius = usize::MAX;
+		let p = Point::new(2.5 * CELL_SIZE as f32, 2.5 * CELL_SIZE as f32);
+		assert_eq!(game.target_cell(p), Some((7, 5)));
+	}
+
+	#[test]
+	fn erase_rectangle() {
+		let center = |x: usize, y: usize| Point::new(((x * CELL_SIZE) as f32) + 0.5, ((y * CELL_SIZE) as f32) + 0.5);
+		for (start, end, area) in [
+			(center(12, 9), center(5, 6), Rectangle { x: 5, y: 6, width: 8, height: 4 }),
+			// NOTE(Simon): dragging past the edge clears up to the edge
+			(center(15, 2), Point::new(1e6, -1e6), Rectangle { x: 15, y: 0, width: 5, height: 3 }),
+		] {
+			let mut game = Conway::from_board(vec![vec![Cell::Alive; 20]; 20]);
+			let mut input = CustomInput::new();
+			input.board_size = (20, 20);
+			input.cursor = start;
+			input.erase_input(ButtonState::Pressed);
+			input.cursor = end;
+			input.erase_input(ButtonState::Released);
+			let InputAction::Erase(erased) = input.action else {
+				panic!("releasing did not erase");
+			};
+			game.erase(erased);
+			for (y, row) in game.current_board.iter().enumerate() {
+				for (x, cell) in row.iter().enumerate() {
+					let covered = (area.x..area.x + area.width).contains(&x) && (area.y..area.y + area.height).contains(&y);
+					assert_eq!(cell.is_alive(), !covered, "cell {}, {}", x, y);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn margin_stays_dead() {
+		for region in [None, Some(Rectangle { x: 0, y: 0, width: 14, height: 20 })] {
+			let mut game = Conway::from_board(board_with((20, 20), (4, 4), &R_PENTOMINO));
+			game.margin = 3;
+			game.active_region = region;
+			for generation in 1..=40 {
+				game.update_board_state();
+				for (y, row) in game.current_board.iter().enumerate() {
+					for (x, cell) in row.iter().enumerate() {
+						let margin = x < 3 || y < 3 || x >= 17 || y >= 17;
+						assert!(!margin || *cell == Cell::Dead, "{}, {} alive in generation {} with region {:?}", x, y, generation, region);
+					}
+				}
+			}
+			assert!(game.population() > 0);
+		}
+	}
+
+	#[test]
+	fn margin_cells_die_inside_region() {
+		let mut game = Conway::from_board(board_with((20, 20), (0, 0), &[(0, 0), (1, 10), (19, 19), (8, 8), (9, 8), (10, 8)]));
+		game.margin = 2;
+		game.active_region = Some(Rectangle { x: 4, y: 4, width: 12, height: 12 });
+		game.update_board_state();
+		for (x, y) in [(0, 0), (1, 10), (19, 19)] {
+			assert_eq!(game.current_board[y][x], Cell::Dead, "margin cell {}, {} survived", x, y);
+		}
+		assert_eq!(game.population(), 3);
+	}
+
+	#[test]
+	fn region_freezes_outside() {
+		// NOTE(Simon): a blinker outside the region at 2, 2 and one inside at 14, 15
+		let board = board_with((30, 30), (0, 0), &[(2, 2), (3, 2), (4, 2), (14, 15), (15, 15), (16, 15)]);
+		let mut game = Conway::from_board(board.clone());
+		let region = Rectangle { x: 10, y: 10, width: 10, height: 10 };
+		game.active_region = Some(region);
+		let inside = |x: usize, y: usize| (region.x..region.x + region.width).contains(&x) && (region.y..region.y + region.height).contains(&y);
+		for generation in 1..=3 {
+			game.update_board_state();
+			for (y, row) in game.current_board.iter().enumerate() {
+				for (x, cell) in row.iter().enumerate().filter(|(x, _)| !inside(*x, y)) {
+					assert_eq!(*cell, board[y][x], "cell {}, {} outside the region changed", x, y);
+				}
 			}
+			let vertical = generation % 2 == 1;
+			assert_eq!(game.current_board[14][15].is_alive(), vertical, "generation {}", generation);
+			assert_eq!(game.current_board[15][14].is_alive(), !vertical, "generation {}", generation);
+		}
+	}
+
+	#[test]
+	fn session_keeps_rule() {
+		let path = std::env::temp_dir().join(format!("vitae-session-{}.txt", std::process::id()));
+		let mut game = Conway::from_board(board_with((20, 20), (5, 5), &R_PENTOMINO));
+		game.current_board[1][1] = Cell::Dying(2);
+		game.ltl = Some("R2,C3,M0,S2..5,B4..6,NM".parse().unwrap());
+		game.session_path = Some(path.clone());
+		game.save_session();
+		let restore = |args: &[&str]| {
+			let session = ["--session".to_string(), path.display().to_string()];
+			Conway::from_config(&Config::from_args(args.iter().map(|arg| arg.to_string()).chain(session)).unwrap())
+		};
+		let restored = restore(&[]);
+		assert_eq!((restored.ltl, &restored.current_board), (game.ltl, &game.current_board));
+		// NOTE(Simon): a rule on the command line replaces the saved one
+		let overridden = restore(&["--rule", "B36/S23"]);
+		assert_eq!((overridden.ltl, overridden.rule.to_string()), (None, "B36/S23".to_string()));
+
+		std::fs::remove_file(&path).unwrap();
+		game.ants = Some(vec![]);
+		game.save_session();
+		assert!(!path.exists(), "a turmite session was saved");
+	}
+
+	#[test]
+	fn invalid_rule_keeps_topology() {
+		let mut game = Conway::with_size((10, 10));
+		game.set_rule("Bx/S23H");
+		assert_eq!((game.topology, game.rule), (Topology::Square, Ruleset::CONWAY));
+		game.set_rule("B2/S34H");
+		assert_eq!((game.topology, game.rule), (Topology::Hex, Ruleset::HEX));
+	}
+
+	#[test]
+	fn generations_per_frame() {
+		for frozen in [false, true] {
+			let mut game = Conway::from_board(board_with((64, 64), (30, 30), &R_PENTOMINO));
+			game.mode = Mode::Simulation;
+			game.speed = 1e9;
+			game.frozen = frozen;
+			game.max_gens_per_frame = 7;
+			game.last_update = Instant::now() - MAX_CATCH_UP;
+			game.simulate();
+			assert!((1..=7).contains(&game.generation), "{} generations in one update", game.generation);
+		}
+	}
+
+	#[test]
+	fn activity() {
+		let mut block = Conway::from_board(board_with((10, 10), (4, 4), &[(0, 0), (1, 0), (0, 1), (1, 1)]));
+		let mut blinker = Conway::from_board(board_with((10, 10), (3, 4), &[(0, 0), (1, 0), (2, 0)]));
+		for _ in 0..4 {
+			block.update_board_state();
+			blinker.update_board_state();
+			assert_eq!(block.activity(), 0.0);
+			// NOTE(Simon): two of the three cells die and two are born every generation, 4 of 100 cells
+			assert!((blinker.activity() - 0.04).abs() < 1e-6, "blinker activity {}", blinker.activity());
 		}
 	}
+
+	#[test]
+	fn paste_origin() {
+		const GLIDER: [(usize, usize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+		let path = std::env::temp_dir().join(format!("vitae-glider-{}.rle", std::process::id()));
+		std::fs::write(&path, library::PATTERNS[0].1).unwrap();
+		let args = ["--board", "40x40", "--load", path.to_str().unwrap(), "--at", "5,7"];
+		let mut game = Conway::from_config(&Config::from_args(args.iter().map(|arg| arg.to_string())).unwrap());
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(game.current_board, board_with((40, 40), (5, 7), &GLIDER));
+		assert_eq!(game.paste_origin, Some((5, 7)));
+
+		// NOTE(Simon): with an origin the click does not matter, without one the stamp centers on it
+		game.current_board = board_with((40, 40), (0, 0), &[]);
+		game.set_paste_origin((20, 22));
+		game.stamp(0, (3, 3));
+		assert_eq!(game.current_board, board_with((40, 40), (20, 22), &GLIDER));
+		game.set_paste_origin((20, 22));
+		assert_eq!(game.paste_origin, None);
+		game.current_board = board_with((40, 40), (0, 0), &[]);
+		game.stamp(0, (30, 30));
+		assert_eq!(game.current_board, board_with((40, 40), (29, 29), &GLIDER));
+	}
 }
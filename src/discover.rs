@@ -0,0 +1,202 @@
+//! `vitae discover [--population <n>] [--generations <n>] [--metric <m>] ...`: searches for birth/survival
+//! rules that score well on random soups. Every generation of the search each rule in the population has a
+//! mutant, the mutants are tried on the same seeded soups in parallel, and the best of parents and mutants
+//! make up the next population. Prints the best rule found and its score.
+
+use anyhow::{anyhow, bail, Context, Result};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+use crate::conway::{self, Board, Cell, Conway, DEFAULT_SOUP_DENSITY, MAX_BOARD_SIDE};
+use crate::rule::Ruleset;
+use crate::session;
+
+const DEFAULT_SIZE: (usize, usize) = (64, 64);
+const DEFAULT_POPULATION: usize = 16;
+const DEFAULT_GENERATIONS: u64 = 20;
+/// Soups every rule is tried on, its score is the average over them
+const DEFAULT_TRIALS: u64 = 4;
+/// Generations a soup is simulated for at most
+const DEFAULT_TRIAL_LENGTH: u64 = 500;
+
+/// What a trial is scored by, higher is better
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+	/// Generations until the board repeats, the trial length if it never does
+	Stabilization,
+	/// Live cells after the trial, in thousandths of the board
+	Population,
+}
+
+impl std::str::FromStr for Metric {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		match s {
+			"stabilization" => Ok(Self::Stabilization),
+			"population" => Ok(Self::Population),
+			_ => bail!("unknown --metric `{}`, the metrics are stabilization and population", s),
+		}
+	}
+}
+
+#[derive(Debug)]
+struct Search {
+	size: (usize, usize),
+	metric: Metric,
+	trials: u64,
+	trial_length: u64,
+	density: f32,
+	seed: u64,
+}
+
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<()> {
+	let mut search = Search {
+		size: DEFAULT_SIZE,
+		metric: Metric::Stabilization,
+		trials: DEFAULT_TRIALS,
+		trial_length: DEFAULT_TRIAL_LENGTH,
+		density: DEFAULT_SOUP_DENSITY,
+		seed: 0,
+	};
+	let (mut population, mut generations, mut start) = (DEFAULT_POPULATION, DEFAULT_GENERATIONS, Ruleset::CONWAY);
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or_else(|| anyhow!("{} expects a value", arg));
+		match arg.as_str() {
+			"--population" => population = value()?.parse().context("invalid --population")?,
+			"--generations" => generations = value()?.parse().context("invalid --generations")?,
+			"--metric" => search.metric = value()?.parse()?,
+			"--trials" => search.trials = value()?.parse().context("invalid --trials")?,
+			"--trial-length" => search.trial_length = value()?.parse().context("invalid --trial-length")?,
+			"--board" => search.size = session::parse_size(&value()?).context("invalid --board")?,
+			"--soup-density" => search.density = value()?.parse().context("invalid --soup-density")?,
+			"--seed" => search.seed = value()?.parse().context("invalid --seed")?,
+			"--start" => start = value()?.parse().context("invalid --start")?,
+			_ => bail!("unknown discover option `{}`", arg),
+		}
+	}
+	if population == 0 || search.trials == 0 || search.trial_length == 0 {
+		bail!("--population, --trials and --trial-length must be at least 1");
+	}
+	let (width, height) = search.size;
+	if width.min(height) == 0 || width.max(height) > MAX_BOARD_SIDE {
+		bail!("--board sides must be between 1 and {} cells", MAX_BOARD_SIDE);
+	}
+	if !(0.0..=1.0).contains(&search.density) {
+		bail!("--soup-density must be between 0 and 1");
+	}
+	// NOTE(Simon): mutations never add B0 either, a B0 rule would need the inverted background handling
+	if start.states() != 2 || start.born(0) {
+		bail!("--start must be a two-state rule without B0, got {}", start);
+	}
+	let (rule, score) = search.evolve(start, population, generations);
+	println!("{} scored {:.1}", rule, score);
+	Ok(())
+}
+
+impl Search {
+	/// Best rule and its score after `generations` generations of `population` rules descended from `start`
+	fn evolve(&self, start: Ruleset, population: usize, generations: u64) -> (Ruleset, f64) {
+		let mut rules = vec![(start, self.score(start))];
+		for generation in 0..generations {
+			let mutants: Vec<_> = (0..population as u64)
+				.map(|i| mutate(rules[i as usize % rules.len()].0, conway::mix(self.seed ^ generation << 32 ^ i)))
+				.collect();
+			rules.par_extend(mutants.into_par_iter().map(|rule| (rule, self.score(rule))));
+			// NOTE(Simon): stable, so among equal scores the parents stay ahead of their mutants
+			rules.sort_by(|a, b| b.1.total_cmp(&a.1));
+			let mut kept: Vec<(Ruleset, f64)> = Vec::with_capacity(population);
+			for (rule, score) in rules {
+				if kept.len() < population && kept.iter().all(|(other, _)| *other != rule) {
+					kept.push((rule, score));
+				}
+			}
+			rules = kept;
+			eprintln!("generation {}: {} scored {:.1}", generation + 1, rules[0].0, rules[0].1);
+		}
+		rules[0]
+	}
+
+	/// Average score of `rule` over the trials
+	fn score(&self, rule: Ruleset) -> f64 {
+		(0..self.trials).map(|trial| self.trial(rule, trial)).sum::<f64>() / self.trials as f64
+	}
+
+	fn trial(&self, rule: Ruleset, trial: u64) -> f64 {
+		let mut game = Conway::from_board(self.soup(trial)).with_rule(rule);
+		let mut seen = HashSet::new();
+		seen.insert(game.checksum());
+		for generation in 1..=self.trial_length {
+			game.update_board_state();
+			if self.metric == Metric::Stabilization && !seen.insert(game.checksum()) {
+				return generation as f64;
+			}
+		}
+		match self.metric {
+			Metric::Stabilization => self.trial_length as f64,
+			Metric::Population => game.population() as f64 * 1000.0 / (self.size.0 * self.size.1) as f64,
+		}
+	}
+
+	/// Random board of `density` live cells, the same for every rule
+	fn soup(&self, trial: u64) -> Board {
+		let (width, height) = self.size;
+		let threshold = (f64::from(self.density) * u64::MAX as f64) as u64;
+		(0..height as u64)
+			.map(|y| {
+				(0..width as u64)
+					.map(|x| if conway::mix(self.seed ^ trial << 48 ^ y << 24 ^ x) < threshold { Cell::Alive } else { Cell::Dead })
+					.collect()
+			})
+			.collect()
+	}
+}
+
+/// `rule` with one neighbor count picked by `random` switched on or off, B0 is left alone
+const fn mutate(rule: Ruleset, random: u64) -> Ruleset {
+	// NOTE(Simon): B1 to B8 and S0 to S8
+	let count = 1 + (random % 17) as usize;
+	if count <= 8 {
+		rule.toggled(true, count)
+	} else {
+		rule.toggled(false, count - 9)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn search(metric: Metric) -> Search {
+		Search { size: (32, 32), metric, trials: 2, trial_length: 200, density: 0.4, seed: 7 }
+	}
+
+	#[test]
+	fn mutations() {
+		for random in 0..17 {
+			let mutant = mutate(Ruleset::CONWAY, random);
+			assert_ne!(mutant, Ruleset::CONWAY);
+			assert!(!mutant.born(0));
+			assert_eq!(mutate(mutant, random), Ruleset::CONWAY);
+		}
+	}
+
+	#[test]
+	fn scores() {
+		// NOTE(Simon): without birth or survival every soup dies at once and the empty board repeats after it
+		let barren = Ruleset::new(&[], &[]);
+		assert_eq!(search(Metric::Stabilization).score(barren), 2.0);
+		assert_eq!(search(Metric::Population).score(barren), 0.0);
+		assert!(search(Metric::Stabilization).score(Ruleset::CONWAY) > 2.0);
+	}
+
+	#[test]
+	fn never_gets_worse() {
+		let search = search(Metric::Stabilization);
+		let start = Ruleset::new(&[3], &[]);
+		let (rule, score) = search.evolve(start, 4, 3);
+		assert!(score >= search.score(start));
+		assert_eq!(score, search.score(rule));
+		assert_eq!(search.evolve(start, 4, 3).0, rule);
+	}
+}
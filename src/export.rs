@@ -0,0 +1,139 @@
+//! Images and animations of the board drawn cell by cell, independent of the window and its zoom.
+
+use anyhow::{bail, Context, Result};
+use coffee::graphics::Color;
+use gif::SetParameter;
+use image::{png::PNGEncoder, ColorType, Rgba, RgbaImage};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// How hard frames with more than 256 colors are quantized, from 1 (best, slowest) to 30
+const QUANTIZE_SPEED: i32 = 10;
+
+/// Image of a board of `size` cells with `scale` pixels along each side of a cell. `color` gives the color of
+/// the cell at `x`, `y`, translucent colors are blended over `background` and cells without one show it.
+pub fn render(size: (usize, usize), scale: usize, background: Color, color: impl Fn(usize, usize) -> Option<Color>) -> RgbaImage {
+	let mut image = RgbaImage::new((size.0 * scale) as u32, (size.1 * scale) as u32);
+	for y in 0..size.1 {
+		for x in 0..size.0 {
+			let pixel = Rgba(color(x, y).map_or(background, |color| blend(color, background)).to_rgba());
+			for dy in 0..scale {
+				for dx in 0..scale {
+					image.put_pixel((x * scale + dx) as u32, (y * scale + dy) as u32, pixel);
+				}
+			}
+		}
+	}
+	image
+}
+
+/// `image` repeated `columns` times across and `rows` times down
+pub fn tile(image: &RgbaImage, (columns, rows): (usize, usize)) -> RgbaImage {
+	let (width, height) = image.dimensions();
+	RgbaImage::from_fn(width * columns as u32, height * rows as u32, |x, y| *image.get_pixel(x % width, y % height))
+}
+
+/// `color` drawn over the opaque `background`
+fn blend(color: Color, background: Color) -> Color {
+	let mix = |front: f32, back: f32| (front - back).mul_add(color.a, back);
+	Color { r: mix(color.r, background.r), g: mix(color.g, background.g), b: mix(color.b, background.b), a: 1.0 }
+}
+
+pub fn write_png(path: &Path, image: &RgbaImage) -> Result<()> {
+	let file = File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+	PNGEncoder::new(BufWriter::new(file))
+		.encode(image, image.width(), image.height(), ColorType::RGBA(8))
+		.with_context(|| format!("could not write {}", path.display()))
+}
+
+/// Animated GIF written a frame at a time, it is finished once dropped
+pub struct Recording {
+	encoder: gif::Encoder<BufWriter<File>>,
+	path: PathBuf,
+	/// Hundredths of a second every frame is shown for
+	delay: u16,
+	size: (u16, u16),
+	/// Frames written so far
+	pub frames: u64,
+}
+
+impl fmt::Debug for Recording {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Recording").field("path", &self.path).field("frames", &self.frames).finish()
+	}
+}
+
+impl Recording {
+	/// Starts a GIF of `width` by `height` pixel frames at `path` that loops forever
+	pub fn create(path: &Path, (width, height): (u32, u32), delay: u16) -> Result<Self> {
+		if width > u32::from(u16::MAX) || height > u32::from(u16::MAX) {
+			bail!("GIFs can be at most {} pixels wide and high, got {}x{}", u16::MAX, width, height);
+		}
+		let size = (width as u16, height as u16);
+		let file = File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+		let mut encoder = gif::Encoder::new(BufWriter::new(file), size.0, size.1, &[])?;
+		encoder.set(gif::Repeat::Infinite)?;
+		Ok(Self { encoder, path: path.to_path_buf(), delay, size, frames: 0 })
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Appends `image`, which has to be as large as the frames the recording was created for
+	pub fn add(&mut self, image: &RgbaImage) -> Result<()> {
+		let (width, height) = self.size;
+		let mut frame = indexed_frame(image, self.size)
+			.unwrap_or_else(|| gif::Frame::from_rgba_speed(width, height, &mut image.clone().into_raw(), QUANTIZE_SPEED));
+		frame.delay = self.delay;
+		self.encoder.write_frame(&frame).with_context(|| format!("could not write {}", self.path.display()))?;
+		self.frames += 1;
+		Ok(())
+	}
+}
+
+/// `image` as a frame with a palette of exactly its colors, nothing if it has more than a palette holds.
+/// Boards rarely have more than a handful of colors, this skips quantizing them.
+fn indexed_frame(image: &RgbaImage, (width, height): (u16, u16)) -> Option<gif::Frame<'static>> {
+	let mut indices = HashMap::new();
+	let mut palette = vec![];
+	let mut pixels = Vec::with_capacity(image.len() / 4);
+	for pixel in image.pixels() {
+		let rgb = [pixel[0], pixel[1], pixel[2]];
+		let index = match indices.get(&rgb) {
+			Some(index) => *index,
+			None => {
+				let index = u8::try_from(indices.len()).ok()?;
+				indices.insert(rgb, index);
+				palette.extend_from_slice(&rgb);
+				index
+			}
+		};
+		pixels.push(index);
+	}
+	Some(gif::Frame::from_palette_pixels(width, height, &pixels, &palette, None))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tiles_repeat() {
+		let background = Color::BLACK;
+		let board = render((5, 3), 2, background, |x, y| (x == y || (x, y) == (4, 0)).then_some(Color::WHITE));
+		let tiled = tile(&board, (3, 2));
+		assert_eq!(tiled.dimensions(), (30, 12));
+		for row in 0..2 {
+			for column in 0..3 {
+				for (x, y, pixel) in board.enumerate_pixels() {
+					assert_eq!(tiled.get_pixel(column * 10 + x, row * 6 + y), pixel);
+				}
+			}
+		}
+	}
+}
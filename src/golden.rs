@@ -0,0 +1,67 @@
+//! Golden patterns: the RLE fixtures in `tests/golden` with their population and board checksum after a number
+//! of generations. Every pattern runs on a board with dead edges, large enough that nothing reaches them.
+
+use crate::conway::{Cell, Conway};
+use crate::pattern::Pattern;
+
+const SIZE: (usize, usize) = (128, 128);
+/// Top left corner every pattern is placed at
+const AT: (usize, usize) = (48, 48);
+
+const GLIDER: &str = include_str!("../tests/golden/glider.rle");
+const BLINKER: &str = include_str!("../tests/golden/blinker.rle");
+const LWSS: &str = include_str!("../tests/golden/lwss.rle");
+const PULSAR: &str = include_str!("../tests/golden/pulsar.rle");
+const DIEHARD: &str = include_str!("../tests/golden/diehard.rle");
+const GOSPER_GUN: &str = include_str!("../tests/golden/gosper-gun.rle");
+
+/// Generation, population and checksum
+type Checkpoint = (u64, usize, u64);
+
+/// Fixture name and RLE with its checkpoints
+const GOLDENS: &[(&str, &str, &[Checkpoint])] = &[
+	("glider", GLIDER, &[(0, 5, 2_489_346_762_042_715_342), (100, 5, 199_044_144_805_546_150)]),
+	("blinker", BLINKER, &[(1, 3, 11_911_102_211_926_927_186), (100, 3, 15_756_444_563_333_224_756)]),
+	("lwss", LWSS, &[(40, 9, 1_186_620_830_138_287_831)]),
+	(
+		"pulsar",
+		PULSAR,
+		&[(1, 56, 9_289_967_167_995_737_328), (2, 72, 2_521_197_373_050_146_679), (99, 48, 16_880_214_849_852_679_518)],
+	),
+	// NOTE(Simon): diehard is known for vanishing in generation 130
+	("diehard", DIEHARD, &[(129, 2, 17_394_208_251_692_484_681), (130, 0, 0)]),
+	// NOTE(Simon): a glider of 5 cells every 30 generations, the gun itself is back to its 36 cells
+	("gosper gun", GOSPER_GUN, &[(0, 36, 5_564_445_275_871_351_296), (120, 56, 12_176_957_172_363_467_993)]),
+];
+
+/// `rle` placed at `at` and simulated for `generations` generations
+fn run(rle: &str, at: (usize, usize), generations: u64) -> Conway {
+	let mut board = vec![vec![Cell::Dead; SIZE.0]; SIZE.1];
+	Pattern::from_rle(rle).unwrap().place(&mut board, at.0, at.1).unwrap();
+	let mut game = Conway::from_board(board);
+	for _ in 0..generations {
+		game.update_board_state();
+	}
+	game
+}
+
+#[test]
+fn golden_patterns() {
+	for (name, rle, checkpoints) in GOLDENS {
+		let mut game = run(rle, AT, 0);
+		let mut generation = 0;
+		for &(until, population, checksum) in checkpoints.iter() {
+			while generation < until {
+				game.update_board_state();
+				generation += 1;
+			}
+			assert_eq!((game.population(), game.checksum()), (population, checksum), "{} in generation {}", name, until);
+		}
+	}
+}
+
+#[test]
+fn spaceships_move() {
+	assert_eq!(run(GLIDER, AT, 4).checksum(), run(GLIDER, (AT.0 + 1, AT.1 + 1), 0).checksum());
+	assert_eq!(run(LWSS, AT, 4).checksum(), run(LWSS, (AT.0 - 2, AT.1), 0).checksum());
+}
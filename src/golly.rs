@@ -0,0 +1,373 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Moore neighborhood in Golly's order, clockwise from north
+const MOORE: [(isize, isize); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+/// Von Neumann neighborhood in Golly's order, clockwise from north
+const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Multi-state rule read from a Golly `.rule` file.
+///
+/// Only the `@TABLE` and `@COLORS` sections are used, `@TREE` rules are rejected. Tables can use the
+/// Moore and von Neumann neighborhoods with any of Golly's symmetries. Like in Golly, a variable that
+/// appears more than once in a transition takes the same value everywhere, and a cell no transition
+/// matches keeps its state.
+#[derive(Debug, Clone)]
+pub struct GollyRule {
+	pub name: String,
+	states: usize,
+	neighborhood: &'static [(isize, isize)],
+	symmetry: Symmetry,
+	transitions: Vec<Transition>,
+	/// Number of distinct variables, the size of the bindings while matching
+	variables: usize,
+	/// Colors from `@COLORS`, indexed by state
+	colors: Vec<Option<[u8; 3]>>,
+	/// Next state of every neighborhood seen so far, matching is slow and boards repeat a lot
+	cache: Cache,
+}
+
+#[derive(Debug, Default)]
+struct Cache(Mutex<HashMap<(u8, [u8; 8]), u8>>);
+
+// NOTE(Simon): a clone starts out with an empty cache, it fills up again quickly
+impl Clone for Cache {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Symmetry {
+	None,
+	Rotate4,
+	Rotate8,
+	ReflectHorizontal,
+	Rotate4Reflect,
+	Rotate8Reflect,
+	Permute,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+	states: Vec<u8>,
+	/// Binding slot, for entries that name a variable
+	variable: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Output {
+	State(u8),
+	Variable(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Transition {
+	center: Entry,
+	neighbors: Vec<Entry>,
+	next: Output,
+}
+
+impl GollyRule {
+	/// Neighbor offsets in the order `next` expects their states
+	pub const fn offsets(&self) -> &'static [(isize, isize)] {
+		self.neighborhood
+	}
+
+	/// Color of `state` given by `@COLORS`
+	pub fn color(&self, state: u8) -> Option<[u8; 3]> {
+		self.colors.get(usize::from(state)).copied().flatten()
+	}
+
+	/// Next state of a `center` cell whose neighbors have `neighbors` states, in the order of `offsets`
+	pub fn next(&self, center: u8, neighbors: &[u8]) -> u8 {
+		let mut key = [0; 8];
+		key[..neighbors.len()].copy_from_slice(neighbors);
+		let mut cache = self.cache.0.lock().expect("the cache is never poisoned, nothing panics while holding it");
+		*cache
+			.entry((center, key))
+			.or_insert_with(|| self.transitions.iter().find_map(|transition| self.apply(transition, center, neighbors)).unwrap_or(center))
+	}
+
+	/// Next state if `transition` matches under some symmetry
+	fn apply(&self, transition: &Transition, center: u8, neighbors: &[u8]) -> Option<u8> {
+		let mut bindings = vec![None; self.variables];
+		if !bind(&transition.center, center, &mut bindings) {
+			return None;
+		}
+		let bindings = if self.symmetry == Symmetry::Permute {
+			permuted(&transition.neighbors, neighbors, 0, &mut vec![false; neighbors.len()], &bindings)?
+		} else {
+			self.orders(neighbors.len()).into_iter().find_map(|order| {
+				let mut bindings = bindings.clone();
+				let matched = transition
+					.neighbors
+					.iter()
+					.zip(order)
+					.all(|(entry, i)| bind(entry, neighbors[i], &mut bindings));
+				matched.then_some(bindings)
+			})?
+		};
+		Some(match transition.next {
+			Output::State(state) => state,
+			Output::Variable(variable) => bindings[variable].expect("output variables are bound by the inputs"),
+		})
+	}
+
+	/// Neighbor orders to try a transition in, every rotation or reflection the symmetry allows
+	fn orders(&self, n: usize) -> Vec<Vec<usize>> {
+		let (step, reflect) = match self.symmetry {
+			Symmetry::None | Symmetry::Permute => (n, false),
+			Symmetry::Rotate4 => (n / 4, false),
+			Symmetry::Rotate8 => (1, false),
+			Symmetry::ReflectHorizontal => (n, true),
+			Symmetry::Rotate4Reflect => (n / 4, true),
+			Symmetry::Rotate8Reflect => (1, true),
+		};
+		let mut orders = vec![];
+		for rotation in (0..n).step_by(step) {
+			let order: Vec<_> = (0..n).map(|i| (i + rotation) % n).collect();
+			if reflect {
+				// NOTE(Simon): mirroring left-right keeps north in place, neighbors are listed clockwise from it
+				orders.push(order.iter().map(|i| (n - i) % n).collect());
+			}
+			orders.push(order);
+		}
+		orders
+	}
+}
+
+/// Checks `state` against `entry`, binding its variable on first use
+fn bind(entry: &Entry, state: u8, bindings: &mut [Option<u8>]) -> bool {
+	if !entry.states.contains(&state) {
+		return false;
+	}
+	let Some(variable) = entry.variable else {
+		return true;
+	};
+	*bindings[variable].get_or_insert(state) == state
+}
+
+/// Matches `entries` against the neighbors in any order, entry by entry, returns the bindings of the first match
+fn permuted(entries: &[Entry], neighbors: &[u8], slot: usize, used: &mut Vec<bool>, bindings: &[Option<u8>]) -> Option<Vec<Option<u8>>> {
+	let Some(entry) = entries.get(slot) else {
+		return Some(bindings.to_vec());
+	};
+	for i in 0..neighbors.len() {
+		if used[i] {
+			continue;
+		}
+		let mut bindings = bindings.to_vec();
+		if bind(entry, neighbors[i], &mut bindings) {
+			used[i] = true;
+			let matched = permuted(entries, neighbors, slot + 1, used, &bindings);
+			used[i] = false;
+			if matched.is_some() {
+				return matched;
+			}
+		}
+	}
+	None
+}
+
+impl std::str::FromStr for GollyRule {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let mut name = None;
+		let mut section = None;
+		let (mut table, mut colors) = (vec![], vec![]);
+		for (i, line) in s.lines().enumerate() {
+			let line = line.split('#').next().unwrap_or_default().trim();
+			if let Some(header) = line.strip_prefix('@') {
+				let mut words = header.split_whitespace();
+				section = words.next().map(str::to_string);
+				if section.as_deref() == Some("RULE") {
+					name = words.next().map(str::to_string);
+				}
+				continue;
+			}
+			if line.is_empty() {
+				continue;
+			}
+			match section.as_deref() {
+				Some("TABLE") => table.push((i + 1, line)),
+				Some("COLORS") => colors.push((i + 1, line)),
+				Some("TREE") => bail!("@TREE rules are not supported, only @TABLE"),
+				_ => {}
+			}
+		}
+		if table.is_empty() {
+			bail!("missing @TABLE section");
+		}
+		let mut rule = parse_table(&table)?;
+		rule.name = name.unwrap_or_else(|| "rule file".to_string());
+		for (line_number, line) in colors {
+			parse_color(&mut rule.colors, line, rule.states).with_context(|| format!("line {}: `{}`", line_number, line))?;
+		}
+		Ok(rule)
+	}
+}
+
+fn parse_table(lines: &[(usize, &str)]) -> Result<GollyRule> {
+	let mut states = None;
+	let mut neighborhood: &[(isize, isize)] = &MOORE;
+	let mut symmetry = Symmetry::None;
+	let mut variables = HashMap::new();
+	let mut transitions = vec![];
+	for (line_number, line) in lines {
+		let context = || format!("line {}: `{}`", line_number, line);
+		if let Some((key, value)) = line.split_once(':') {
+			match (key.trim(), value.trim()) {
+				("n_states", value) => match value.parse() {
+					Ok(n) if (2..=256).contains(&n) => states = Some(n),
+					_ => bail!("{}: expected 2 to 256 states", context()),
+				},
+				("neighborhood", "Moore") => neighborhood = &MOORE,
+				("neighborhood", "vonNeumann") => neighborhood = &VON_NEUMANN,
+				("neighborhood", value) => bail!("{}: the {} neighborhood is not supported", context(), value),
+				("symmetries", value) => symmetry = parse_symmetry(value).with_context(context)?,
+				(key, _) => bail!("{}: unknown setting `{}`", context(), key),
+			}
+			continue;
+		}
+		let states = states.ok_or_else(|| anyhow!("{}: `n_states` has to come first", context()))?;
+		if let Some(variable) = line.strip_prefix("var ") {
+			let (name, values) = variable.split_once('=').ok_or_else(|| anyhow!("{}: expected `var <name>={{...}}`", context()))?;
+			let values = parse_set(values.trim(), states, &variables).with_context(context)?;
+			variables.insert(name.trim().to_string(), values);
+			continue;
+		}
+		transitions.push(parse_transition(line, states, neighborhood.len(), &variables).with_context(context)?);
+	}
+	if matches!(symmetry, Symmetry::Rotate8 | Symmetry::Rotate8Reflect) && neighborhood.len() != 8 {
+		bail!("rotate8 symmetries need the Moore neighborhood");
+	}
+	let bound = transitions.iter().flat_map(|transition| transition.neighbors.iter().chain([&transition.center]));
+	Ok(GollyRule {
+		name: String::new(),
+		states: states.ok_or_else(|| anyhow!("missing `n_states`"))?,
+		neighborhood,
+		symmetry,
+		variables: bound.filter_map(|entry| entry.variable).max().map_or(0, |max| max + 1),
+		transitions,
+		colors: vec![],
+		cache: Cache::default(),
+	})
+}
+
+fn parse_symmetry(value: &str) -> Result<Symmetry> {
+	Ok(match value {
+		"none" => Symmetry::None,
+		"rotate4" => Symmetry::Rotate4,
+		"rotate8" => Symmetry::Rotate8,
+		"reflect_horizontal" => Symmetry::ReflectHorizontal,
+		"rotate4reflect" => Symmetry::Rotate4Reflect,
+		"rotate8reflect" => Symmetry::Rotate8Reflect,
+		"permute" => Symmetry::Permute,
+		_ => bail!("unknown symmetry `{}`", value),
+	})
+}
+
+/// Parses `{0,1,a}`, where earlier variables stand for all their states
+fn parse_set(set: &str, states: usize, variables: &HashMap<String, Vec<u8>>) -> Result<Vec<u8>> {
+	let inner = set
+		.strip_prefix('{')
+		.and_then(|set| set.strip_suffix('}'))
+		.ok_or_else(|| anyhow!("expected `{{...}}`, got `{}`", set))?;
+	let mut values = vec![];
+	for value in inner.split(',').map(str::trim) {
+		match variables.get(value) {
+			Some(states) => values.extend_from_slice(states),
+			None => values.push(parse_state(value, states)?),
+		}
+	}
+	Ok(values)
+}
+
+fn parse_state(value: &str, states: usize) -> Result<u8> {
+	match value.parse::<usize>() {
+		Ok(state) if state < states => Ok(state as u8),
+		_ => bail!("invalid state `{}`, expected a variable or a number below {}", value, states),
+	}
+}
+
+/// Splits a transition into its entries, either comma separated or one digit per state
+fn entries(line: &str) -> Vec<String> {
+	if !line.contains([',', '{']) {
+		return line.chars().filter(|c| !c.is_whitespace()).map(String::from).collect();
+	}
+	let mut entries = vec![String::new()];
+	let mut depth = 0;
+	for c in line.chars() {
+		match c {
+			',' if depth == 0 => entries.push(String::new()),
+			'{' | '}' => {
+				depth += if c == '{' { 1 } else { -1 };
+				entries.last_mut().expect("there is always an entry").push(c);
+			}
+			c if !c.is_whitespace() => entries.last_mut().expect("there is always an entry").push(c),
+			_ => {}
+		}
+	}
+	entries
+}
+
+fn parse_transition(line: &str, states: usize, neighbors: usize, variables: &HashMap<String, Vec<u8>>) -> Result<Transition> {
+	let entries = entries(line);
+	if entries.len() != neighbors + 2 {
+		bail!("expected {} entries, center, {} neighbors and the next state", neighbors + 2, neighbors);
+	}
+	// NOTE(Simon): binding slots are only shared within one transition, so they are numbered per line
+	let mut slots: Vec<String> = vec![];
+	let mut entry = |value: &str| -> Result<Entry> {
+		if let Some(states) = variables.get(value) {
+			let slot = slots.iter().position(|name| name == value).unwrap_or_else(|| {
+				slots.push(value.to_string());
+				slots.len() - 1
+			});
+			return Ok(Entry {
+				states: states.clone(),
+				variable: Some(slot),
+			});
+		}
+		let states = if value.starts_with('{') { parse_set(value, states, variables)? } else { vec![parse_state(value, states)?] };
+		Ok(Entry { states, variable: None })
+	};
+	let center = entry(&entries[0])?;
+	let neighbors = entries[1..=neighbors].iter().map(|value| entry(value)).collect::<Result<Vec<_>>>()?;
+	let output = &entries[neighbors.len() + 1];
+	let next = match slots.iter().position(|name| name == output) {
+		Some(slot) => Output::Variable(slot),
+		None if variables.contains_key(output.as_str()) => bail!("output variable `{}` is not used by the inputs", output),
+		None => Output::State(parse_state(output, states)?),
+	};
+	Ok(Transition { center, neighbors, next })
+}
+
+/// Parses `<state> <r> <g> <b>`, or a gradient `<r1> <g1> <b1> <r2> <g2> <b2>` over all live states
+fn parse_color(colors: &mut Vec<Option<[u8; 3]>>, line: &str, states: usize) -> Result<()> {
+	let numbers = line
+		.split_whitespace()
+		.map(|number| number.parse::<u8>().with_context(|| format!("invalid number `{}`", number)))
+		.collect::<Result<Vec<_>>>()?;
+	let mut set = |state: usize, color: [u8; 3]| {
+		if colors.len() <= state {
+			colors.resize(state + 1, None);
+		}
+		colors[state] = Some(color);
+	};
+	match numbers[..] {
+		[state, r, g, b] if usize::from(state) < states => set(usize::from(state), [r, g, b]),
+		[r1, g1, b1, r2, g2, b2] => {
+			for state in 1..states {
+				let t = (state - 1) as f32 / (states - 2).max(1) as f32;
+				let mix = |a: u8, b: u8| (f32::from(b) - f32::from(a)).mul_add(t, f32::from(a)).round() as u8;
+				set(state, [mix(r1, r2), mix(g1, g2), mix(b1, b2)]);
+			}
+		}
+		_ => bail!("expected `<state> <r> <g> <b>` or `<r1> <g1> <b1> <r2> <g2> <b2>`"),
+	}
+	Ok(())
+}
@@ -0,0 +1,466 @@
+//! HashLife, the quadtree engine behind `--hashlife`.
+//!
+//! A node of level `k` covers 2^k x 2^k cells, level 0 being a single cell. Identical nodes are only stored
+//! once, and each node remembers its result: the center 2^(k-1) square after 2^(k-2) generations. Patterns
+//! that repeat in space or time share their results, which lets them be advanced by millions of generations
+//! in one jump. Like the sparse plane the tree is unbounded, it grows whenever the pattern needs more room.
+
+use std::collections::HashMap;
+
+use crate::conway::{Board, Cell};
+use crate::pattern::{MacroNode, Macrocell};
+use crate::rule::Ruleset;
+
+const DEAD: u32 = 0;
+const ALIVE: u32 = 1;
+
+/// Nodes kept before unreachable ones are dropped and the results are forgotten
+const MAX_NODES: usize = 1 << 23;
+/// Highest level of a macrocell pattern the plane takes, so it has room to grow before cell positions overflow
+pub const MAX_LEVEL: u32 = 56;
+
+#[derive(Debug, Copy, Clone)]
+struct Node {
+	level: u8,
+	/// nw, ne, sw and se quadrants, unused for cells
+	children: [u32; 4],
+	population: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct HashLife {
+	rule: Ruleset,
+	/// Neighbor offsets, all within one cell
+	kernel: Vec<(isize, isize)>,
+	nodes: Vec<Node>,
+	ids: HashMap<[u32; 4], u32>,
+	/// Result of a node after 2^j generations, keyed by node and `j`
+	results: HashMap<(u32, u8), u32>,
+	/// Empty node of each level
+	empty: Vec<u32>,
+	root: u32,
+	/// Plane position of the root's top left corner
+	origin: (i64, i64),
+}
+
+impl Default for HashLife {
+	fn default() -> Self {
+		let cell = |population| Node {
+			level: 0,
+			children: [DEAD; 4],
+			population,
+		};
+		let mut life = Self {
+			rule: Ruleset::CONWAY,
+			kernel: vec![],
+			nodes: vec![cell(0), cell(1)],
+			ids: HashMap::new(),
+			results: HashMap::new(),
+			empty: vec![DEAD],
+			root: DEAD,
+			origin: (0, 0),
+		};
+		life.root = life.empty(3);
+		life.origin = (-4, -4);
+		life
+	}
+}
+
+impl HashLife {
+	/// Whether HashLife can simulate a neighborhood, it needs the same one in every row and no cell further
+	/// than one away
+	pub fn supports(kernels: [&[(isize, isize)]; 2]) -> bool {
+		kernels[0] == kernels[1] && kernels[0].iter().all(|(dx, dy)| dx.abs() <= 1 && dy.abs() <= 1)
+	}
+
+	/// Plane holding the macrocell pattern `tree`, whose level must be at most `MAX_LEVEL`. Like every root
+	/// the pattern is centered on 0,0, its top left corner is at minus half its side.
+	pub fn from_macrocell(tree: &Macrocell) -> Self {
+		let mut life = Self::default();
+		let mut ids: Vec<u32> = Vec::with_capacity(tree.nodes.len());
+		for node in &tree.nodes {
+			let id = match node {
+				MacroNode::Leaf(cells) => {
+					let empty = life.empty(3);
+					cells.iter().fold(empty, |id, &(x, y)| life.set(id, x as i64, y as i64, true))
+				}
+				MacroNode::Inner(level, children) => {
+					let empty = life.empty(*level as u8 - 1);
+					life.node(children.map(|child| if child == 0 { empty } else { ids[child] }))
+				}
+			};
+			ids.push(id);
+		}
+		if let Some(root) = ids.pop() {
+			let half = 1 << (tree.level() - 1);
+			life.root = root;
+			life.origin = (-half, -half);
+		}
+		life.shrink();
+		life
+	}
+
+	pub fn population(&self) -> u64 {
+		self.nodes[self.root as usize].population
+	}
+
+	/// Live cells outside the board when its top left corner sits at `origin`
+	pub fn population_outside(&self, origin: (i64, i64), board: &Board) -> u64 {
+		let area = (origin, (board.first().map_or(0, Vec::len) as i64, board.len() as i64));
+		self.population() - self.population_in(self.root, self.origin, area)
+	}
+
+	/// Live cells outside the board when its top left corner sits at `origin`, in plane coordinates
+	pub fn outside(&self, origin: (i64, i64), board: &Board) -> Vec<(i64, i64)> {
+		let area = (origin, (board.first().map_or(0, Vec::len) as i64, board.len() as i64));
+		let mut cells = vec![];
+		self.outside_node(self.root, self.origin, area, &mut cells);
+		cells
+	}
+
+	/// Replaces the part of the plane under the board with the board's cells
+	pub fn store(&mut self, origin: (i64, i64), board: &Board) {
+		let (width, height) = (board.first().map_or(0, Vec::len) as i64, board.len() as i64);
+		while !self.covers(origin, (width, height)) {
+			self.expand();
+		}
+		for (y, row) in board.iter().enumerate() {
+			for (x, cell) in row.iter().enumerate() {
+				let (x, y) = (origin.0 + x as i64 - self.origin.0, origin.1 + y as i64 - self.origin.1);
+				let alive = cell.is_alive();
+				if self.get(self.root, x, y) != alive {
+					self.root = self.set(self.root, x, y, alive);
+				}
+			}
+		}
+		self.shrink();
+	}
+
+	/// Copies the part of the plane under the board into it
+	pub fn load(&self, origin: (i64, i64), board: &mut Board) {
+		for row in board.iter_mut() {
+			row.fill(Cell::Dead);
+		}
+		self.load_node(self.root, self.origin, origin, board);
+	}
+
+	/// Advances the plane by `generations` under `rule` with the neighbor offsets `kernel`
+	pub fn advance(&mut self, rule: &Ruleset, kernel: &[(isize, isize)], generations: u64) {
+		if self.rule != *rule || self.kernel != kernel {
+			self.rule = *rule;
+			self.kernel = kernel.to_vec();
+			self.results.clear();
+		}
+		for j in 0..u64::BITS as u8 {
+			if generations >> j & 1 == 1 {
+				self.step(j);
+			}
+		}
+		if self.nodes.len() > MAX_NODES {
+			self.collect();
+		}
+		self.shrink();
+	}
+
+	/// Takes away the padding the root gathered while growing, so equal planes end up with the same root
+	fn shrink(&mut self) {
+		// NOTE(Simon): the root stays centered on 0,0 through every expansion, result and shrink
+		loop {
+			let root = self.nodes[self.root as usize];
+			if root.level <= 3 || self.inner_population() != root.population {
+				break;
+			}
+			self.root = self.center(self.root);
+			let quarter = 1 << (root.level - 2);
+			self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+		}
+	}
+
+	/// Advances the plane by 2^j generations
+	fn step(&mut self, j: u8) {
+		// NOTE(Simon): the result is the center half of the root, so everything has to start in its center
+		// quarter for nothing to be cut off. Cells move at most one cell per generation.
+		loop {
+			let root = self.nodes[self.root as usize];
+			if root.level >= j + 3 && self.inner_population() == root.population {
+				break;
+			}
+			self.expand();
+		}
+		let level = self.nodes[self.root as usize].level;
+		self.root = self.result(self.root, j);
+		let quarter = 1 << (level - 2);
+		self.origin = (self.origin.0 + quarter, self.origin.1 + quarter);
+	}
+
+	/// Center square of `id` after 2^j generations, or after 2^(k-2) if `j` is larger than that
+	fn result(&mut self, id: u32, j: u8) -> u32 {
+		let node = self.nodes[id as usize];
+		let j = j.min(node.level - 2);
+		if node.population == 0 {
+			return self.empty(node.level - 1);
+		}
+		if let Some(result) = self.results.get(&(id, j)) {
+			return *result;
+		}
+		let result = if node.level == 2 {
+			self.base_result(id)
+		} else {
+			let [nw, ne, sw, se] = node.children;
+			let parts = [
+				nw,
+				self.horizontal(nw, ne),
+				ne,
+				self.vertical(nw, sw),
+				self.center(id),
+				self.vertical(ne, se),
+				sw,
+				self.horizontal(sw, se),
+				se,
+			];
+			// NOTE(Simon): at full speed both halves advance the pattern, otherwise only the second one does
+			let mut r = [DEAD; 9];
+			for (r, part) in r.iter_mut().zip(parts) {
+				*r = if j == node.level - 2 { self.result(part, j) } else { self.center(part) };
+			}
+			let quadrants = [[r[0], r[1], r[3], r[4]], [r[1], r[2], r[4], r[5]], [r[3], r[4], r[6], r[7]], [r[4], r[5], r[7], r[8]]];
+			let mut children = [DEAD; 4];
+			for (child, quadrant) in children.iter_mut().zip(quadrants) {
+				let quadrant = self.node(quadrant);
+				*child = self.result(quadrant, j);
+			}
+			self.node(children)
+		};
+		self.results.insert((id, j), result);
+		result
+	}
+
+	/// One generation of the center 2x2 cells of a 4x4 node
+	fn base_result(&mut self, id: u32) -> u32 {
+		let mut cells = [[false; 4]; 4];
+		for (y, row) in cells.iter_mut().enumerate() {
+			for (x, cell) in row.iter_mut().enumerate() {
+				*cell = self.get(id, x as i64, y as i64);
+			}
+		}
+		let mut children = [DEAD; 4];
+		for (i, child) in children.iter_mut().enumerate() {
+			let (x, y) = (1 + i % 2, 1 + i / 2);
+			let n = self.kernel.iter().filter(|(dx, dy)| cells[(y as isize + dy) as usize][(x as isize + dx) as usize]).count();
+			let next = if cells[y][x] { self.rule.survives(n) } else { self.rule.born(n) };
+			*child = if next { ALIVE } else { DEAD };
+		}
+		self.node(children)
+	}
+
+	/// Node with the given quadrants, stored only once
+	fn node(&mut self, children: [u32; 4]) -> u32 {
+		if let Some(id) = self.ids.get(&children) {
+			return *id;
+		}
+		let id = self.nodes.len() as u32;
+		self.nodes.push(Node {
+			level: self.nodes[children[0] as usize].level + 1,
+			children,
+			population: children.iter().map(|child| self.nodes[*child as usize].population).sum(),
+		});
+		self.ids.insert(children, id);
+		id
+	}
+
+	fn empty(&mut self, level: u8) -> u32 {
+		while self.empty.len() <= usize::from(level) {
+			let below = self.empty[self.empty.len() - 1];
+			let empty = self.node([below; 4]);
+			self.empty.push(empty);
+		}
+		self.empty[usize::from(level)]
+	}
+
+	/// Live cells in the center quarter of the root
+	fn inner_population(&mut self) -> u64 {
+		let center = self.center(self.root);
+		let inner = self.center(center);
+		self.nodes[inner as usize].population
+	}
+
+	/// Center half of a node
+	fn center(&mut self, id: u32) -> u32 {
+		let [nw, ne, sw, se] = self.nodes[id as usize].children.map(|child| self.nodes[child as usize].children);
+		self.node([nw[3], ne[2], sw[1], se[0]])
+	}
+
+	/// Node centered between two side by side nodes
+	fn horizontal(&mut self, w: u32, e: u32) -> u32 {
+		let (w, e) = (self.nodes[w as usize].children, self.nodes[e as usize].children);
+		self.node([w[1], e[0], w[3], e[2]])
+	}
+
+	/// Node centered between two stacked nodes
+	fn vertical(&mut self, n: u32, s: u32) -> u32 {
+		let (n, s) = (self.nodes[n as usize].children, self.nodes[s as usize].children);
+		self.node([n[2], n[3], s[0], s[1]])
+	}
+
+	/// Doubles the root, keeping its cells in the middle
+	fn expand(&mut self) {
+		let level = self.nodes[self.root as usize].level;
+		let [nw, ne, sw, se] = self.nodes[self.root as usize].children;
+		let e = self.empty(level - 1);
+		let children = [self.node([e, e, e, nw]), self.node([e, e, ne, e]), self.node([e, sw, e, e]), self.node([se, e, e, e])];
+		self.root = self.node(children);
+		let half = 1 << (level - 1);
+		self.origin = (self.origin.0 - half, self.origin.1 - half);
+	}
+
+	/// Whether the root covers the area of `size` at `origin`
+	fn covers(&self, origin: (i64, i64), size: (i64, i64)) -> bool {
+		let side = 1 << self.nodes[self.root as usize].level;
+		origin.0 >= self.origin.0
+			&& origin.1 >= self.origin.1
+			&& origin.0 + size.0 <= self.origin.0 + side
+			&& origin.1 + size.1 <= self.origin.1 + side
+	}
+
+	/// Cell at `x`, `y` relative to the node's top left corner
+	fn get(&self, id: u32, x: i64, y: i64) -> bool {
+		let node = self.nodes[id as usize];
+		if node.level == 0 || node.population == 0 {
+			return node.population > 0;
+		}
+		let half = 1 << (node.level - 1);
+		let quadrant = usize::from(x >= half) + 2 * usize::from(y >= half);
+		self.get(node.children[quadrant], x % half, y % half)
+	}
+
+	/// The node with the cell at `x`, `y` set
+	fn set(&mut self, id: u32, x: i64, y: i64, alive: bool) -> u32 {
+		let node = self.nodes[id as usize];
+		if node.level == 0 {
+			return if alive { ALIVE } else { DEAD };
+		}
+		let half = 1 << (node.level - 1);
+		let quadrant = usize::from(x >= half) + 2 * usize::from(y >= half);
+		let mut children = node.children;
+		children[quadrant] = self.set(children[quadrant], x % half, y % half, alive);
+		self.node(children)
+	}
+
+	fn population_in(&self, id: u32, corner: (i64, i64), (origin, size): ((i64, i64), (i64, i64))) -> u64 {
+		let node = self.nodes[id as usize];
+		let side = 1 << node.level;
+		let overlaps = corner.0 < origin.0 + size.0 && origin.0 < corner.0 + side && corner.1 < origin.1 + size.1 && origin.1 < corner.1 + side;
+		if node.population == 0 || !overlaps {
+			return 0;
+		}
+		let inside = origin.0 <= corner.0 && corner.0 + side <= origin.0 + size.0 && origin.1 <= corner.1 && corner.1 + side <= origin.1 + size.1;
+		if inside {
+			return node.population;
+		}
+		let half = side / 2;
+		(0..4)
+			.map(|i| {
+				let corner = (corner.0 + half * (i % 2), corner.1 + half * (i / 2));
+				self.population_in(node.children[i as usize], corner, (origin, size))
+			})
+			.sum()
+	}
+
+	fn outside_node(&self, id: u32, corner: (i64, i64), (origin, size): ((i64, i64), (i64, i64)), cells: &mut Vec<(i64, i64)>) {
+		let node = self.nodes[id as usize];
+		let side = 1 << node.level;
+		let inside = origin.0 <= corner.0 && corner.0 + side <= origin.0 + size.0 && origin.1 <= corner.1 && corner.1 + side <= origin.1 + size.1;
+		if node.population == 0 || inside {
+			return;
+		}
+		if node.level == 0 {
+			cells.push(corner);
+			return;
+		}
+		let half = side / 2;
+		for (i, child) in node.children.iter().enumerate() {
+			let i = i as i64;
+			self.outside_node(*child, (corner.0 + half * (i % 2), corner.1 + half * (i / 2)), (origin, size), cells);
+		}
+	}
+
+	fn load_node(&self, id: u32, corner: (i64, i64), origin: (i64, i64), board: &mut Board) {
+		let node = self.nodes[id as usize];
+		let side = 1 << node.level;
+		let (width, height) = (board.first().map_or(0, Vec::len) as i64, board.len() as i64);
+		let (x, y) = (corner.0 - origin.0, corner.1 - origin.1);
+		if node.population == 0 || x >= width || y >= height || x + side <= 0 || y + side <= 0 {
+			return;
+		}
+		if node.level == 0 {
+			board[y as usize][x as usize] = Cell::Alive;
+			return;
+		}
+		let half = side / 2;
+		for (i, child) in node.children.iter().enumerate() {
+			let i = i as i64;
+			self.load_node(*child, (corner.0 + half * (i % 2), corner.1 + half * (i / 2)), origin, board);
+		}
+	}
+
+	/// Drops every node the root can't reach, along with all results
+	fn collect(&mut self) {
+		let mut fresh = Self {
+			rule: self.rule,
+			kernel: self.kernel.clone(),
+			..Self::default()
+		};
+		let mut copied = HashMap::new();
+		fresh.root = fresh.copy(self, self.root, &mut copied);
+		fresh.origin = self.origin;
+		*self = fresh;
+	}
+
+	fn copy(&mut self, from: &Self, id: u32, copied: &mut HashMap<u32, u32>) -> u32 {
+		if id <= ALIVE {
+			return id;
+		}
+		if let Some(copy) = copied.get(&id) {
+			return *copy;
+		}
+		let mut children = from.nodes[id as usize].children;
+		for child in &mut children {
+			*child = self.copy(from, *child, copied);
+		}
+		let copy = self.node(children);
+		copied.insert(id, copy);
+		copy
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::pattern::Pattern;
+
+	const GLIDER: [(usize, usize); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+	#[test]
+	fn from_macrocell() {
+		// NOTE(Simon): two gliders a million cells apart, far more than any board holds
+		let far = 1 << 20;
+		let alive: Vec<_> = GLIDER.iter().flat_map(|&(x, y)| [(x, y), (far + x, far + y)]).collect();
+		let pattern = Pattern { width: far + 3, height: far + 3, alive };
+		let tree = Macrocell::parse(&pattern.to_macrocell(None)).unwrap();
+		assert_eq!(tree.size(), (far + 3, far + 3));
+		assert!(tree.to_pattern().is_err());
+		let mut life = HashLife::from_macrocell(&tree);
+		assert_eq!(life.population(), 10);
+		let moore: Vec<_> = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))).filter(|&d| d != (0, 0)).collect();
+		life.advance(&Ruleset::CONWAY, &moore, 4 * 1000);
+		assert_eq!(life.population(), 10);
+		// NOTE(Simon): after 4000 generations both gliders moved 1000 cells down and to the right
+		let half = 1 << (tree.level() - 1);
+		let mut board = vec![vec![Cell::Dead; 3]; 3];
+		for corner in [(0, 0), (far as i64, far as i64)] {
+			life.load((corner.0 + 1000 - half, corner.1 + 1000 - half), &mut board);
+			let cells: Vec<_> = GLIDER.iter().map(|&(x, y)| board[y][x]).collect();
+			assert!(cells.iter().all(|cell| cell.is_alive()), "glider at {:?}", corner);
+		}
+	}
+}
@@ -0,0 +1,96 @@
+use coffee::graphics::{Color, Font, Frame, HorizontalAlignment, Point, Text, VerticalAlignment};
+use coffee::load::Task;
+use std::fmt;
+
+const FONT: &[u8] = include_bytes!("../resources/Inconsolata-Regular.ttf");
+
+/// Distance between lines drawn in the corner
+const LINE_HEIGHT: f32 = 24.0;
+
+/// Text overlay drawn on top of the board.
+pub struct Hud {
+	font: Font,
+	color: Color,
+}
+
+impl fmt::Debug for Hud {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Hud").finish()
+	}
+}
+
+impl Hud {
+	pub fn load() -> Task<Self> {
+		Font::load_from_bytes(FONT).map(|font| Self { font, color: Color::BLACK })
+	}
+
+	/// Sets the color of the text drawn from now on.
+	pub const fn set_color(&mut self, color: Color) {
+		self.color = color;
+	}
+
+	/// Draws `content` centered on the frame.
+	pub fn draw_centered(&mut self, frame: &mut Frame, content: &str) {
+		self.font.add(Text {
+			content,
+			position: Point::new(frame.width() / 2.0, frame.height() / 2.0),
+			size: 32.0,
+			color: self.color,
+			horizontal_alignment: HorizontalAlignment::Center,
+			vertical_alignment: VerticalAlignment::Center,
+			..Text::default()
+		});
+		self.font.draw(&mut frame.as_target());
+	}
+
+	/// Draws `content` as line `line` (counting from 0) in the top right corner of the frame.
+	pub fn draw_corner(&mut self, frame: &mut Frame, content: &str, line: usize) {
+		self.font.add(Text {
+			content,
+			position: Point::new(frame.width() - 8.0, LINE_HEIGHT.mul_add(line as f32, 8.0)),
+			size: 20.0,
+			color: self.color,
+			horizontal_alignment: HorizontalAlignment::Right,
+			..Text::default()
+		});
+		self.font.draw(&mut frame.as_target());
+	}
+
+	/// Draws a line of `content` in the bottom left corner of the frame.
+	pub fn draw_footer(&mut self, frame: &mut Frame, content: &str) {
+		self.font.add(Text {
+			content,
+			position: Point::new(8.0, frame.height() - 8.0),
+			size: 20.0,
+			color: self.color,
+			vertical_alignment: VerticalAlignment::Bottom,
+			..Text::default()
+		});
+		self.font.draw(&mut frame.as_target());
+	}
+
+	/// Draws a line of `content` starting at `position`, vertically centered on it.
+	pub fn draw_at(&mut self, frame: &mut Frame, content: &str, position: Point) {
+		self.font.add(Text {
+			content,
+			position,
+			size: 20.0,
+			color: self.color,
+			vertical_alignment: VerticalAlignment::Center,
+			..Text::default()
+		});
+		self.font.draw(&mut frame.as_target());
+	}
+
+	/// Draws a line of `content` in the top left corner of the frame.
+	pub fn draw_status(&mut self, frame: &mut Frame, content: &str) {
+		self.font.add(Text {
+			content,
+			position: Point::new(8.0, 8.0),
+			size: 20.0,
+			color: self.color,
+			..Text::default()
+		});
+		self.font.draw(&mut frame.as_target());
+	}
+}
@@ -0,0 +1,26 @@
+use crate::pattern::Pattern;
+
+/// Patterns built into the binary, as name and RLE.
+pub const PATTERNS: &[(&str, &str)] = &[
+	("glider", "x = 3, y = 3\nbo$2bo$3o!"),
+	("lightweight spaceship", "x = 5, y = 4\nbo2bo$o4b$o3bo$4o!"),
+	(
+		"pulsar",
+		"x = 13, y = 13\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+	),
+	("r-pentomino", "x = 3, y = 3\nb2o$2ob$bo!"),
+	("acorn", "x = 7, y = 3\nbo5b$3bo3b$2o2b3o!"),
+	("diehard", "x = 8, y = 3\n6bob$2o6b$bo3b3o!"),
+	(
+		"gosper glider gun",
+		"x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+	),
+];
+
+pub fn name(index: usize) -> &'static str {
+	PATTERNS[index].0
+}
+
+pub fn pattern(index: usize) -> Pattern {
+	Pattern::from_rle(PATTERNS[index].1).expect("built-in patterns are valid RLE")
+}
@@ -1,19 +1,70 @@
 #![warn(clippy::nursery)]
 #![warn(clippy::perf)]
+use crate::config::Config;
 use crate::conway::*;
 use anyhow::Result;
 use coffee::graphics::WindowSettings;
 use coffee::Game;
 
+mod bench;
+mod bitboard;
+mod browser;
+mod camera;
+mod config;
 mod conway;
+mod discover;
+mod export;
+#[cfg(test)]
+mod golden;
+mod golly;
+mod hashlife;
+mod hud;
+mod library;
+mod meter;
+mod minimap;
+mod pattern;
+mod report;
+mod rule;
+mod session;
+mod share;
+mod sparse;
+mod stream;
+mod theme;
+mod transform;
+mod turmite;
 
 fn main() -> Result<()> {
-	Conway::run(WindowSettings {
+	let mut args = std::env::args().skip(1).peekable();
+	if args.peek().map(String::as_str) == Some("bench") {
+		args.next();
+		return bench::run(args);
+	}
+	if args.peek().map(String::as_str) == Some("discover") {
+		args.next();
+		return discover::run(args);
+	}
+	let config = Config::from_args(args)?;
+	if let Some(generations) = config.headless {
+		config.install();
+		Conway::from_config(Config::get()).run_headless(generations);
+		return Ok(());
+	}
+	let settings = window_settings(&config);
+	config.install();
+	Conway::run(settings)?;
+	Ok(())
+}
+
+fn window_settings(config: &Config) -> WindowSettings {
+	// NOTE(Simon): neither coffee backend exposes multisampling yet, opengl hardcodes it to 0 and wgpu to 1 sample
+	if let Some(samples) = config.msaa.filter(|&samples| samples > 1) {
+		eprintln!("warning: --msaa {} is not supported by the rendering backend, continuing without multisampling", samples);
+	}
+	WindowSettings {
 		title: "Conway's game of life!".into(),
 		size: (WINDOW_SIZE_X as u32, WINDOW_SIZE_Y as u32),
-		resizable: false,
+		resizable: true,
 		fullscreen: false,
 		maximized: false,
-	})?;
-	Ok(())
+	}
 }
@@ -0,0 +1,28 @@
+//! Rates of recurring events, such as frames drawn or generations simulated, over the last second.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default)]
+pub struct RateMeter {
+	/// When events happened and how many, the latest last, none older than `WINDOW`
+	events: VecDeque<(Instant, u64)>,
+}
+
+impl RateMeter {
+	pub fn record(&mut self, count: u64) {
+		let now = Instant::now();
+		while self.events.front().is_some_and(|(at, _)| now.duration_since(*at) > WINDOW) {
+			self.events.pop_front();
+		}
+		self.events.push_back((now, count));
+	}
+
+	/// Events per second, over the last second
+	pub fn per_second(&self) -> u64 {
+		let now = Instant::now();
+		self.events.iter().filter(|(at, _)| now.duration_since(*at) <= WINDOW).map(|(_, count)| count).sum()
+	}
+}
@@ -0,0 +1,116 @@
+//! Overview of the whole board in the bottom right corner of the window, shown while the window only shows
+//! part of the board. Each pixel is shaded by the share of live cells under it.
+
+use coffee::graphics::{Color, Frame, Image, Mesh, Point, Quad, Rectangle, Shape};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::camera::Camera;
+use crate::conway::{Board, CELL_SIZE};
+use crate::theme::Theme;
+
+/// Longer side of the minimap in window pixels, at most a third of the shorter side of the window
+const SIZE: f32 = 160.0;
+/// Distance of the minimap from the corner of the window
+const INSET: f32 = 8.0;
+/// How often the live cells are counted again, counting every cell of a large board each frame is too slow
+const REFRESH: Duration = Duration::from_millis(250);
+const VIEWPORT_COLOR: Color = Color {
+	r: 0.9,
+	g: 0.3,
+	b: 0.2,
+	a: 1.0,
+};
+
+/// Where the minimap of a board of `size` cells goes in window pixels, nothing while the window shows all of it
+pub fn area(camera: &Camera, size: (usize, usize)) -> Option<Rectangle<f32>> {
+	if camera.fits(size) || size.0 == 0 || size.1 == 0 {
+		return None;
+	}
+	let scale = SIZE.min(camera.window.0.min(camera.window.1) / 3.0) / size.0.max(size.1) as f32;
+	let (width, height) = ((size.0 as f32 * scale).max(1.0), (size.1 as f32 * scale).max(1.0));
+	Some(Rectangle { x: camera.window.0 - INSET - width, y: camera.window.1 - INSET - height, width, height })
+}
+
+/// Board pixel shown at the window pixel `p` of a minimap at `area`
+pub fn board_point(area: Rectangle<f32>, size: (usize, usize), p: Point) -> Point {
+	let (width, height) = ((size.0 * CELL_SIZE) as f32, (size.1 * CELL_SIZE) as f32);
+	Point::new((p.x - area.x) / area.width * width, (p.y - area.y) / area.height * height)
+}
+
+#[derive(Default)]
+pub struct Minimap {
+	/// Shading drawn last and when the cells were counted for it
+	texture: Option<(Instant, Image)>,
+}
+
+impl fmt::Debug for Minimap {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Minimap").finish()
+	}
+}
+
+impl Minimap {
+	/// Draws the minimap of `board` with the part `camera` shows outlined, unless the window shows all of it
+	pub fn draw(&mut self, frame: &mut Frame, camera: &Camera, board: &Board, theme: &Theme) {
+		let size = (board.first().map_or(0, Vec::len), board.len());
+		let Some(area) = area(camera, size) else {
+			self.texture = None;
+			return;
+		};
+		let (columns, rows) = (area.width.round() as usize, area.height.round() as usize);
+		if self.texture.as_ref().is_none_or(|(counted, _)| counted.elapsed() >= REFRESH) {
+			let texels = shading(board, (columns, rows), theme.cell);
+			let image = image::RgbaImage::from_raw(columns as u32, rows as u32, texels)
+				.and_then(|texture| Image::from_image(frame.gpu(), &image::DynamicImage::ImageRgba8(texture)).ok());
+			self.texture = image.map(|image| (Instant::now(), image));
+		}
+		let mut mesh = Mesh::new();
+		mesh.fill(Shape::Rectangle(area), theme.panel);
+		mesh.draw(&mut frame.as_target());
+		if let Some((_, image)) = &self.texture {
+			let quad = Quad { position: Point::new(area.x, area.y), size: (area.width, area.height), ..Quad::default() };
+			image.draw(quad, &mut frame.as_target());
+		}
+		let (width, height) = ((size.0 * CELL_SIZE) as f32, (size.1 * CELL_SIZE) as f32);
+		let top_left = camera.board_point(Point::new(0.0, 0.0));
+		let bottom_right = camera.board_point(Point::new(camera.window.0, camera.window.1));
+		let (x0, y0) = (top_left.x.max(0.0) / width, top_left.y.max(0.0) / height);
+		let (x1, y1) = (bottom_right.x.min(width) / width, bottom_right.y.min(height) / height);
+		let viewport = Rectangle {
+			x: area.width.mul_add(x0, area.x),
+			y: area.height.mul_add(y0, area.y),
+			width: area.width * (x1 - x0),
+			height: area.height * (y1 - y0),
+		};
+		let mut mesh = Mesh::new();
+		mesh.stroke(Shape::Rectangle(area), theme.grid, 1.0);
+		mesh.stroke(Shape::Rectangle(viewport), VIEWPORT_COLOR, 1.5);
+		mesh.draw(&mut frame.as_target());
+	}
+}
+
+/// RGBA texels of `columns` by `rows` pixels covering `board`, each `color` as opaque as the share of live
+/// cells under it. Sparse areas get a boost so single spaceships stay visible.
+fn shading(board: &Board, (columns, rows): (usize, usize), color: Color) -> Vec<u8> {
+	let (width, height) = (board.first().map_or(0, Vec::len), board.len());
+	let mut alive = vec![0_u32; columns * rows];
+	let mut cells = vec![0_u32; columns * rows];
+	for (y, row) in board.iter().enumerate() {
+		let pixel_row = y * rows / height * columns;
+		for (x, cell) in row.iter().enumerate() {
+			let pixel = pixel_row + x * columns / width;
+			cells[pixel] += 1;
+			alive[pixel] += u32::from(cell.is_alive());
+		}
+	}
+	let [r, g, b, _] = color.to_rgba();
+	alive
+		.iter()
+		.zip(&cells)
+		.flat_map(|(alive, cells)| {
+			let share = if *cells == 0 { 0.0 } else { *alive as f32 / *cells as f32 };
+			[r, g, b, (share.sqrt() * 255.0).round() as u8]
+		})
+		.collect()
+}
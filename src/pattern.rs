@@ -0,0 +1,534 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+use crate::conway::{Board, Cell, MAX_BOARD_SIDE};
+
+/// Golly and most other tools keep RLE lines to 70 characters
+const RLE_LINE_LENGTH: usize = 70;
+
+/// Live cells of a pattern, relative to the top left corner of its bounding box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+	pub width: usize,
+	pub height: usize,
+	pub alive: Vec<(usize, usize)>,
+}
+
+impl Pattern {
+	/// Parses a run-length encoded pattern as used by Golly and LifeWiki.
+	///
+	/// The `x = <width>, y = <height>` header line is required, `#` lines before it are comments.
+	/// Runs are `b` for dead, `o` for alive and `$` for the end of a row, `!` ends the pattern.
+	pub fn from_rle(s: &str) -> Result<Self> {
+		let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+		let header = lines.next().context("missing `x = <width>, y = <height>` header")?;
+		let (width, height) = parse_rle_header(header).with_context(|| format!("invalid header `{}`", header))?;
+
+		let mut pattern = Self {
+			width,
+			height,
+			alive: vec![],
+		};
+		let (mut x, mut y) = (0, 0);
+		let mut count: Option<usize> = None;
+		'body: for line in lines {
+			for c in line.chars() {
+				if let Some(digit) = c.to_digit(10) {
+					let run = count.unwrap_or(0).checked_mul(10).and_then(|run| run.checked_add(digit as usize));
+					count = Some(run.context("run length overflows")?);
+					continue;
+				}
+				let run = count.take().unwrap_or(1);
+				match c {
+					'b' => x += run,
+					'o' => {
+						if x + run > width || y >= height {
+							bail!("cells outside the {}x{} bounding box in row {}", width, height, y + 1);
+						}
+						pattern.alive.extend((x..x + run).map(|x| (x, y)));
+						x += run;
+					}
+					'$' => {
+						x = 0;
+						y += run;
+					}
+					'!' => break 'body,
+					c if c.is_whitespace() => {},
+					c => bail!("unsupported cell state `{}`, only two-state patterns are supported", c),
+				}
+			}
+		}
+		Ok(pattern)
+	}
+
+	/// Parses a Life 1.06 file, a `#Life 1.06` header line followed by one `<x> <y>` pair per live cell.
+	/// Coordinates may be negative, the pattern is moved so its bounding box starts at 0,0.
+	pub fn from_life106(s: &str) -> Result<Self> {
+		let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+		if lines.next() != Some("#Life 1.06") {
+			bail!("missing `#Life 1.06` header");
+		}
+		let mut cells = vec![];
+		for line in lines.filter(|line| !line.starts_with('#')) {
+			let mut coordinates = line.split_whitespace().map(str::parse::<i64>);
+			match (coordinates.next(), coordinates.next(), coordinates.next()) {
+				(Some(Ok(x)), Some(Ok(y)), None) => cells.push((x, y)),
+				_ => bail!("expected `<x> <y>`, got `{}`", line),
+			}
+		}
+		let (x0, y0) = cells.iter().fold((i64::MAX, i64::MAX), |(x0, y0), &(x, y)| (x0.min(x), y0.min(y)));
+		let mut alive = vec![];
+		for (x, y) in cells {
+			let dx = usize::try_from(x.abs_diff(x0)).context("pattern is too wide")?;
+			let dy = usize::try_from(y.abs_diff(y0)).context("pattern is too tall")?;
+			alive.push((dx, dy));
+		}
+		Ok(Self::cropped(alive))
+	}
+
+	pub fn to_life106(&self) -> String {
+		let mut life = "#Life 1.06\n".to_string();
+		for (x, y) in &self.alive {
+			life += &format!("{} {}\n", x, y);
+		}
+		life
+	}
+
+	/// Parses a plaintext `.cells` file, one row per line with `.` for dead and `O` for alive cells.
+	/// Lines starting with `!` are comments, short rows are padded with dead cells.
+	pub fn from_cells(s: &str) -> Result<Self> {
+		let mut rows: Vec<_> = s.lines().map(str::trim_end).filter(|line| !line.starts_with('!')).collect();
+		while rows.last().is_some_and(|row| row.is_empty()) {
+			rows.pop();
+		}
+		let mut pattern = Self {
+			width: 0,
+			height: rows.len(),
+			alive: vec![],
+		};
+		for (y, row) in rows.into_iter().enumerate() {
+			for (x, c) in row.chars().enumerate() {
+				match c {
+					'.' => {},
+					'O' | '*' => pattern.alive.push((x, y)),
+					c => bail!("unexpected `{}` in row {}, expected `.` or `O`", c, y + 1),
+				}
+				pattern.width = pattern.width.max(x + 1);
+			}
+		}
+		Ok(pattern)
+	}
+
+	/// Encodes the pattern as plaintext, dead cells at the end of a row are left out
+	pub fn to_cells(&self) -> String {
+		let mut rows = vec![vec![]; self.height];
+		for &(x, y) in &self.alive {
+			let row = &mut rows[y];
+			if row.len() <= x {
+				row.resize(x + 1, '.');
+			}
+			row[x] = 'O';
+		}
+		let mut cells = String::new();
+		for row in rows {
+			// NOTE(Simon): an empty line reads fine, but a lone `.` survives editors that strip blank lines
+			if row.is_empty() {
+				cells.push('.');
+			}
+			cells.extend(row);
+			cells.push('\n');
+		}
+		cells
+	}
+
+	/// Parses Golly's macrocell format, see `Macrocell::parse`. The whole pattern has to fit on the largest
+	/// board.
+	pub fn from_macrocell(s: &str) -> Result<Self> {
+		Macrocell::parse(s)?.to_pattern()
+	}
+
+	/// Encodes the pattern as a macrocell quadtree, `rule` is written as `#R` if given
+	pub fn to_macrocell(&self, rule: Option<&str>) -> String {
+		let mut level = 3;
+		while 1 << level < self.width.max(self.height) {
+			level += 1;
+		}
+		let mut writer = MacrocellWriter::default();
+		writer.lines.push("[M2] (vitae)".to_string());
+		if let Some(rule) = rule {
+			writer.lines.push(format!("#R {}", rule));
+		}
+		let root = writer.node(level, self.alive.clone());
+		// NOTE(Simon): an empty pattern has no nodes at all, but the root has to be a line of its own
+		if root == 0 {
+			writer.lines.push("$".to_string());
+		}
+		writer.lines.join("\n") + "\n"
+	}
+
+	/// Live cells of `board`, cropped to their bounding box
+	pub fn from_board(board: &Board) -> Self {
+		Self::cropped(
+			board
+				.iter()
+				.enumerate()
+				.flat_map(|(y, row)| row.iter().enumerate().filter(|(_, cell)| cell.is_alive()).map(move |(x, _)| (x, y)))
+				.collect(),
+		)
+	}
+
+	/// Moves `alive` into the top left corner and drops duplicate cells
+	fn cropped(mut alive: Vec<(usize, usize)>) -> Self {
+		let (x0, y0) = alive.iter().fold((usize::MAX, usize::MAX), |(x0, y0), &(x, y)| (x0.min(x), y0.min(y)));
+		for (x, y) in &mut alive {
+			*x -= x0;
+			*y -= y0;
+		}
+		alive.sort_unstable_by_key(|&(x, y)| (y, x));
+		alive.dedup();
+		Self {
+			width: alive.iter().map(|&(x, _)| x + 1).max().unwrap_or(0),
+			height: alive.iter().map(|&(_, y)| y + 1).max().unwrap_or(0),
+			alive,
+		}
+	}
+
+	/// Encodes the pattern as RLE, `rule` is written to the header if given
+	pub fn to_rle(&self, rule: Option<&str>) -> String {
+		let mut rows = vec![vec![]; self.height];
+		for &(x, y) in &self.alive {
+			rows[y].push(x);
+		}
+		// NOTE(Simon): a run is `<count><tag>`, consecutive runs of the same tag are merged
+		let mut runs: Vec<(usize, char)> = vec![];
+		let mut push = |count: usize, tag: char| match runs.last_mut() {
+			Some((previous, last)) if *last == tag => *previous += count,
+			_ if count > 0 => runs.push((count, tag)),
+			_ => {},
+		};
+		for (y, row) in rows.iter_mut().enumerate() {
+			row.sort_unstable();
+			let mut x = 0;
+			for &alive in row.iter() {
+				push(alive - x, 'b');
+				push(1, 'o');
+				x = alive + 1;
+			}
+			if y + 1 < self.height {
+				push(1, '$');
+			}
+		}
+
+		let mut rle = format!("x = {}, y = {}", self.width, self.height);
+		if let Some(rule) = rule {
+			rle += &format!(", rule = {}", rule);
+		}
+		rle.push('\n');
+		let mut line = String::new();
+		let tokens = runs.into_iter().map(|(count, tag)| if count == 1 { tag.to_string() } else { format!("{}{}", count, tag) });
+		for run in tokens.chain(["!".to_string()]) {
+			if line.len() + run.len() > RLE_LINE_LENGTH {
+				rle += &line;
+				rle.push('\n');
+				line.clear();
+			}
+			line += &run;
+		}
+		rle += &line;
+		rle.push('\n');
+		rle
+	}
+
+	/// Sets the pattern's live cells on `board` with its top left corner at `x`, `y`
+	pub fn place(&self, board: &mut Board, x: usize, y: usize) -> Result<()> {
+		let fits = board.len().checked_sub(self.height).is_some_and(|space| y <= space)
+			&& board.first().map_or(0, Vec::len).checked_sub(self.width).is_some_and(|space| x <= space);
+		if !fits {
+			bail!("a {}x{} pattern does not fit on the board at {},{}", self.width, self.height, x, y);
+		}
+		for (dx, dy) in &self.alive {
+			board[y + dy][x + dx] = Cell::Alive;
+		}
+		Ok(())
+	}
+}
+
+fn parse_rle_header(header: &str) -> Result<(usize, usize)> {
+	let (mut width, mut height) = (None, None);
+	for field in header.split(',') {
+		let (key, value) = field.split_once('=').context("expected `<key> = <value>`")?;
+		let value = value.trim();
+		// NOTE(Simon): the rule field is ignored for now, the pattern runs under whatever rule is active
+		match key.trim() {
+			"x" => width = Some(value.parse().with_context(|| format!("invalid width `{}`", value))?),
+			"y" => height = Some(value.parse().with_context(|| format!("invalid height `{}`", value))?),
+			_ => {},
+		}
+	}
+	match (width, height) {
+		(Some(width), Some(height)) => Ok((width, height)),
+		_ => bail!("header needs both `x` and `y`"),
+	}
+}
+
+/// More live cells than this can't be placed on the largest board anyway, reading stops there
+const MAX_MACROCELL_CELLS: usize = MAX_BOARD_SIDE * MAX_BOARD_SIDE;
+
+#[derive(Debug, Clone)]
+pub enum MacroNode {
+	/// Live cells of an 8x8 block
+	Leaf(Vec<(usize, usize)>),
+	/// Level and the nw, ne, sw and se children, 0 for an empty quadrant
+	Inner(u32, [usize; 4]),
+}
+
+impl MacroNode {
+	pub const fn level(&self) -> u32 {
+		match self {
+			Self::Leaf(_) => 3,
+			Self::Inner(level, _) => *level,
+		}
+	}
+}
+
+/// Pattern read from a macrocell file, kept as the quadtree it is written as. HashLife can take it over
+/// directly, however far apart its cells are.
+#[derive(Debug, Clone)]
+pub struct Macrocell {
+	/// Every node after the nodes it is made of, the last one is the whole pattern. Node 0 is empty.
+	pub nodes: Vec<MacroNode>,
+}
+
+impl Macrocell {
+	/// Parses Golly's macrocell format, a quadtree with one node per line that shares identical subtrees.
+	///
+	/// Leaves are 8x8 blocks written as rows of `.` and `*` ended by `$`. Every other node is
+	/// `<level> <nw> <ne> <sw> <se>`, a `2^level` square made of four earlier nodes, numbered from 1
+	/// in file order with 0 for an empty quadrant. The last node is the whole pattern.
+	pub fn parse(s: &str) -> Result<Self> {
+		let mut lines = s.lines().map(str::trim).filter(|line| !line.is_empty());
+		if !lines.next().is_some_and(|line| line.starts_with("[M2]")) {
+			bail!("missing `[M2]` header");
+		}
+		// NOTE(Simon): index 0 is the empty node, so node numbers from the file index directly
+		let mut nodes = vec![MacroNode::Leaf(vec![])];
+		for line in lines.filter(|line| !line.starts_with('#')) {
+			let node = parse_macrocell_node(line, &nodes).with_context(|| format!("node {}: `{}`", nodes.len(), line))?;
+			nodes.push(node);
+		}
+		Ok(Self { nodes })
+	}
+
+	/// Level of the whole pattern, it covers `2^level` cells along each side
+	pub fn level(&self) -> u32 {
+		self.nodes.last().map_or(3, MacroNode::level)
+	}
+
+	/// Top left and bottom right live cell, nothing for an empty pattern. Every node is only looked at once,
+	/// so this is quick however large the pattern is.
+	pub fn bounds(&self) -> Option<Bounds> {
+		let mut known: Vec<Option<Bounds>> = Vec::with_capacity(self.nodes.len());
+		for node in &self.nodes {
+			let bounds = match node {
+				MacroNode::Leaf(cells) => cells.iter().fold(None, |bounds, &(x, y)| Some(extend(bounds, ((x, y), (x, y))))),
+				MacroNode::Inner(level, children) => {
+					let half = 1 << (level - 1);
+					let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+					children.iter().zip(offsets).fold(None, |bounds, (&child, (dx, dy))| match known[child] {
+						Some(((x0, y0), (x1, y1))) => Some(extend(bounds, ((x0 + dx, y0 + dy), (x1 + dx, y1 + dy)))),
+						None => bounds,
+					})
+				}
+			};
+			known.push(bounds);
+		}
+		known.last().copied().flatten()
+	}
+
+	/// Width and height of the live cells' bounding box
+	pub fn size(&self) -> (usize, usize) {
+		self.bounds().map_or((0, 0), |((x0, y0), (x1, y1))| (x1 - x0 + 1, y1 - y0 + 1))
+	}
+
+	/// Every live cell, this fails for patterns that can't be placed on the largest board
+	pub fn to_pattern(&self) -> Result<Pattern> {
+		let (width, height) = self.size();
+		if width.max(height) > MAX_BOARD_SIDE {
+			bail!("a {}x{} pattern does not fit on the largest board", width, height);
+		}
+		let mut alive = vec![];
+		let mut stack = vec![(self.nodes.len() - 1, 0usize, 0usize)];
+		while let Some((index, x, y)) = stack.pop() {
+			match &self.nodes[index] {
+				MacroNode::Leaf(cells) => alive.extend(cells.iter().map(|&(dx, dy)| (x + dx, y + dy))),
+				MacroNode::Inner(level, children) => {
+					let half = 1 << (level - 1);
+					let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+					for (&child, (dx, dy)) in children.iter().zip(offsets) {
+						if child != 0 {
+							stack.push((child, x + dx, y + dy));
+						}
+					}
+				}
+			}
+			if alive.len() > MAX_MACROCELL_CELLS {
+				bail!("pattern has more live cells than fit on the board");
+			}
+		}
+		Ok(Pattern::cropped(alive))
+	}
+}
+
+/// Top left and bottom right corner, both inclusive
+type Bounds = ((usize, usize), (usize, usize));
+
+/// `bounds` grown to cover `other` as well
+fn extend(bounds: Option<Bounds>, other: Bounds) -> Bounds {
+	match bounds {
+		Some(((x0, y0), (x1, y1))) => ((x0.min(other.0 .0), y0.min(other.0 .1)), (x1.max(other.1 .0), y1.max(other.1 .1))),
+		None => other,
+	}
+}
+
+fn parse_macrocell_node(line: &str, nodes: &[MacroNode]) -> Result<MacroNode> {
+	if line.starts_with(|c: char| c.is_ascii_digit()) {
+		let numbers = line.split_whitespace().map(str::parse::<usize>).collect::<Result<Vec<_>, _>>()?;
+		let (level, children) = match numbers[..] {
+			[level, nw, ne, sw, se] => (level, [nw, ne, sw, se]),
+			_ => bail!("expected `<level> <nw> <ne> <sw> <se>`"),
+		};
+		let level = u32::try_from(level).ok().filter(|level| (4..usize::BITS).contains(level));
+		let level = level.with_context(|| format!("level must be between 4 and {}", usize::BITS - 1))?;
+		for &child in &children {
+			match nodes.get(child) {
+				None => bail!("node {} is not defined yet", child),
+				Some(node) if child != 0 && node.level() != level - 1 => {
+					bail!("node {} has level {}, expected {}", child, node.level(), level - 1)
+				}
+				_ => {},
+			}
+		}
+		return Ok(MacroNode::Inner(level, children));
+	}
+	let mut cells = vec![];
+	let (mut x, mut y) = (0, 0);
+	for c in line.chars() {
+		match c {
+			'.' => x += 1,
+			'*' => {
+				cells.push((x, y));
+				x += 1;
+			}
+			'$' => {
+				x = 0;
+				y += 1;
+			}
+			c => bail!("unexpected `{}` in a leaf, only two-state patterns are supported", c),
+		}
+		if x > 8 || y > 8 || (y == 8 && x > 0) {
+			bail!("leaf is larger than 8x8");
+		}
+	}
+	Ok(MacroNode::Leaf(cells))
+}
+
+/// Builds the node lines bottom up, identical subtrees are written once
+#[derive(Debug, Default)]
+struct MacrocellWriter {
+	lines: Vec<String>,
+	/// Line of every node written so far, mapped to its node number
+	written: HashMap<String, usize>,
+}
+
+impl MacrocellWriter {
+	/// Writes the node of `2^level` cells holding `cells`, returns its number
+	fn node(&mut self, level: u32, cells: Vec<(usize, usize)>) -> usize {
+		if cells.is_empty() {
+			return 0;
+		}
+		let line = if level == 3 {
+			let mut rows = vec![vec!['.'; 8]; 8];
+			for (x, y) in cells {
+				rows[y][x] = '*';
+			}
+			let rows: Vec<_> = rows.iter().map(|row| row.iter().collect::<String>().trim_end_matches('.').to_string()).collect();
+			let used = rows.iter().rposition(|row| !row.is_empty()).map_or(0, |last| last + 1);
+			rows[..used].iter().map(|row| row.clone() + "$").collect()
+		} else {
+			let half = 1 << (level - 1);
+			let mut quadrants = [vec![], vec![], vec![], vec![]];
+			for (x, y) in cells {
+				let quadrant = usize::from(x >= half) + 2 * usize::from(y >= half);
+				quadrants[quadrant].push((x % half, y % half));
+			}
+			let [nw, ne, sw, se] = quadrants.map(|quadrant| self.node(level - 1, quadrant));
+			format!("{} {} {} {} {}", level, nw, ne, sw, se)
+		};
+		if let Some(&index) = self.written.get(&line) {
+			return index;
+		}
+		let index = self.written.len() + 1;
+		self.written.insert(line.clone(), index);
+		self.lines.push(line);
+		index
+	}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Format {
+	Rle,
+	Life106,
+	Cells,
+	Macrocell,
+}
+
+fn format(path: &Path) -> Result<Format> {
+	match path.extension().and_then(|extension| extension.to_str()) {
+		Some("rle") => Ok(Format::Rle),
+		Some("lif" | "life") => Ok(Format::Life106),
+		Some("cells") => Ok(Format::Cells),
+		Some("mc") => Ok(Format::Macrocell),
+		_ => bail!("unknown pattern format for {}, expected .rle, .lif, .life, .cells or .mc", path.display()),
+	}
+}
+
+/// Fails unless the extension of `path` is a known pattern format
+pub fn check_format(path: &Path) -> Result<()> {
+	format(path).map(drop)
+}
+
+/// Reads a pattern file, the format is picked by its extension
+pub fn read(path: &Path) -> Result<Pattern> {
+	let format = format(path)?;
+	let content = std::fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+	let pattern = match format {
+		Format::Rle => Pattern::from_rle(&content),
+		Format::Life106 => Pattern::from_life106(&content),
+		Format::Cells => Pattern::from_cells(&content),
+		Format::Macrocell => Pattern::from_macrocell(&content),
+	};
+	pattern.with_context(|| format!("invalid pattern {}", path.display()))
+}
+
+/// Reads a macrocell file as the quadtree it is written as, nothing if `path` is in another format
+pub fn read_macrocell(path: &Path) -> Result<Option<Macrocell>> {
+	if format(path)? != Format::Macrocell {
+		return Ok(None);
+	}
+	let content = std::fs::read_to_string(path).with_context(|| format!("could not read {}", path.display()))?;
+	let tree = Macrocell::parse(&content).with_context(|| format!("invalid pattern {}", path.display()))?;
+	Ok(Some(tree))
+}
+
+/// Writes a pattern file, the format is picked by its extension. `rule` is only kept by formats that record one.
+pub fn write(path: &Path, pattern: &Pattern, rule: Option<&str>) -> Result<()> {
+	let content = match format(path)? {
+		Format::Rle => pattern.to_rle(rule),
+		Format::Life106 => pattern.to_life106(),
+		Format::Cells => pattern.to_cells(),
+		Format::Macrocell => pattern.to_macrocell(rule),
+	};
+	std::fs::write(path, content).with_context(|| format!("could not write {}", path.display()))
+}
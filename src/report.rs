@@ -0,0 +1,157 @@
+//! Statistics about the current run and the JSON report summarizing them.
+//!
+//! The report is a single flat object. Fields are only ever added, never renamed or removed:
+//!
+//! | Field              | Type           | Meaning                                                        |
+//! |--------------------|----------------|----------------------------------------------------------------|
+//! | `version`          | number         | Schema version, currently 1                                    |
+//! | `initial_pattern`  | string         | Share code of the board when the run started                   |
+//! | `rule`             | string         | Rulestring such as `B3/S23` or `R5,C0,M1,S34..58,B34..45,NM`, `table` for a lookup-table rule, the `@RULE` name of a rule file, `wireworld`, or `ant <turns>` while turmites walk |
+//! | `topology`         | string         | `square`, `hex` or `von-neumann`                               |
+//! | `boundary`         | string         | Edge handling, `dead`, `wrap` or `mirror`                      |
+//! | `generations`      | number         | Generations simulated since the run started                    |
+//! | `final_population` | number         | Live cells in the last generation                              |
+//! | `peak_population`  | number         | Highest live-cell count seen during the run                    |
+//! | `peak_generation`  | number         | Run generation in which the peak was first reached             |
+//! | `stabilized`       | bool           | Whether the board has started repeating                        |
+//! | `period`           | number or null | Length of the repeating cycle, 1 for a still life or empty board |
+//!
+//! A run starts when the board is loaded and again whenever the simulation is started after an edit.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Default)]
+pub struct RunStats {
+	initial_pattern: String,
+	start_generation: u64,
+	generations: u64,
+	population: usize,
+	peak_population: usize,
+	peak_generation: u64,
+	period: Option<u64>,
+	/// Run generation in which each board checksum was last seen, until the board starts repeating
+	seen: HashMap<u64, u64>,
+}
+
+/// Everything about the session that is not tracked by `RunStats`
+pub struct Session<'a> {
+	pub rule: String,
+	pub topology: &'a str,
+	pub boundary: &'a str,
+}
+
+impl RunStats {
+	pub fn start(initial_pattern: String, generation: u64, population: usize, checksum: u64) -> Self {
+		let mut stats = Self {
+			initial_pattern,
+			start_generation: generation,
+			population,
+			peak_population: population,
+			..Self::default()
+		};
+		stats.seen.insert(checksum, 0);
+		stats
+	}
+
+	/// Records the board after a generation was simulated
+	pub fn record(&mut self, generation: u64, population: usize, checksum: u64) {
+		self.generations = generation.saturating_sub(self.start_generation);
+		self.population = population;
+		if population > self.peak_population {
+			self.peak_population = population;
+			self.peak_generation = self.generations;
+		}
+		if self.period.is_some() {
+			return;
+		}
+		if let Some(previous) = self.seen.insert(checksum, self.generations) {
+			self.period = Some(self.generations - previous);
+			self.seen = HashMap::new();
+		}
+	}
+
+	// NOTE(Simon): the rule can be the `@RULE` name of a user's rule file, so strings are escaped
+	pub fn to_json(&self, session: &Session) -> String {
+		let period = self.period.map_or_else(|| "null".to_string(), |period| period.to_string());
+		format!(
+			concat!(
+				"{{\n",
+				"  \"version\": 1,\n",
+				"  \"initial_pattern\": \"{}\",\n",
+				"  \"rule\": \"{}\",\n",
+				"  \"topology\": \"{}\",\n",
+				"  \"boundary\": \"{}\",\n",
+				"  \"generations\": {},\n",
+				"  \"final_population\": {},\n",
+				"  \"peak_population\": {},\n",
+				"  \"peak_generation\": {},\n",
+				"  \"stabilized\": {},\n",
+				"  \"period\": {}\n",
+				"}}\n"
+			),
+			escape(&self.initial_pattern),
+			escape(&session.rule),
+			escape(session.topology),
+			escape(session.boundary),
+			self.generations,
+			self.population,
+			self.peak_population,
+			self.peak_generation,
+			self.period.is_some(),
+			period,
+		)
+	}
+}
+
+/// `s` with the characters JSON strings can't hold as they are escaped
+fn escape(s: &str) -> String {
+	let mut escaped = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'"' => escaped.push_str("\\\""),
+			'\\' => escaped.push_str("\\\\"),
+			'\n' => escaped.push_str("\\n"),
+			'\r' => escaped.push_str("\\r"),
+			'\t' => escaped.push_str("\\t"),
+			c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn peak_and_period() {
+		let mut stats = RunStats::start("AAAA".to_string(), 10, 3, 100);
+		// NOTE(Simon): generation, population and checksum, generation 16 repeats the board of generation 14
+		let run = [(11, 4, 101), (12, 6, 102), (13, 9, 103), (14, 7, 104), (15, 5, 105), (16, 7, 104), (17, 5, 105)];
+		for (generation, population, checksum) in run {
+			stats.record(generation, population, checksum);
+		}
+		let session = Session { rule: "B3/S23".to_string(), topology: "square", boundary: "dead" };
+		let json = stats.to_json(&session);
+		for field in [
+			"\"generations\": 7,",
+			"\"final_population\": 5,",
+			"\"peak_population\": 9,",
+			"\"peak_generation\": 3,",
+			"\"stabilized\": true,",
+			"\"period\": 2\n",
+		] {
+			assert!(json.contains(field), "{} missing from {}", field, json);
+		}
+		let unsettled = RunStats::start(String::new(), 0, 1, 1).to_json(&session);
+		assert!(unsettled.contains("\"stabilized\": false,") && unsettled.contains("\"period\": null"));
+	}
+
+	#[test]
+	fn escapes_rule_names() {
+		let session = Session { rule: "my \"best\"\\rule\n\u{1}".to_string(), topology: "square", boundary: "dead" };
+		let json = RunStats::default().to_json(&session);
+		assert!(json.contains(r#""rule": "my \"best\"\\rule\n\u0001","#), "{}", json);
+	}
+}
@@ -0,0 +1,668 @@
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::conway::{Cell, Topology};
+
+/// Outer-totalistic birth/survival rule, stored as bitmasks over neighbor counts 0..=8.
+///
+/// Rules with more than two states are Generations rules: a live cell that doesn't survive decays
+/// through `states - 2` dying states before it is dead. Dying cells don't count as neighbors and
+/// can't be born or survive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+	birth: u16,
+	survive: u16,
+	states: u8,
+}
+
+impl Ruleset {
+	/// Classic Life, B3/S23.
+	pub const CONWAY: Self = Self::new(&[3], &[2, 3]);
+	/// Hexagonal Life, B2/S34. Works on the six-cell hex neighborhood.
+	pub const HEX: Self = Self::new(&[2], &[3, 4]);
+	/// Brian's Brain, B2/S/C3. Every live cell dies after one generation and then can't be born for one.
+	pub const BRIANS_BRAIN: Self = Self::generations(&[2], &[], 3);
+
+	/// Well-known rules to cycle through, with their names.
+	pub const PRESETS: &'static [(&'static str, Self)] = &[
+		("Life", Self::CONWAY),
+		("HighLife", Self::new(&[3, 6], &[2, 3])),
+		("Seeds", Self::new(&[2], &[])),
+		("Day & Night", Self::new(&[3, 6, 7, 8], &[3, 4, 6, 7, 8])),
+		("Diamoeba", Self::new(&[3, 5, 6, 7, 8], &[5, 6, 7, 8])),
+		("Brian's Brain", Self::BRIANS_BRAIN),
+	];
+
+	pub const fn new(birth: &[usize], survive: &[usize]) -> Self {
+		Self {
+			birth: mask(birth),
+			survive: mask(survive),
+			states: 2,
+		}
+	}
+
+	pub const fn generations(birth: &[usize], survive: &[usize], states: u8) -> Self {
+		Self {
+			states,
+			..Self::new(birth, survive)
+		}
+	}
+
+	/// Number of states including dead and alive, 2 unless this is a Generations rule
+	pub const fn states(&self) -> u8 {
+		self.states
+	}
+
+	pub const fn born(&self, n: usize) -> bool {
+		self.birth & (1 << n) != 0
+	}
+
+	pub const fn survives(&self, n: usize) -> bool {
+		self.survive & (1 << n) != 0
+	}
+
+	/// The same rule with birth on `n` neighbors, or survival when `birth` is false, switched on or off
+	pub const fn toggled(&self, birth: bool, n: usize) -> Self {
+		if birth {
+			Self { birth: self.birth ^ 1 << n, ..*self }
+		} else {
+			Self { survive: self.survive ^ 1 << n, ..*self }
+		}
+	}
+
+	/// Next state of a `center` cell with `n` live neighbors
+	pub const fn next(&self, center: Cell, n: usize) -> Cell {
+		match center {
+			Cell::Dead if self.born(n) => Cell::Alive,
+			Cell::Alive | Cell::Colored(_) if self.survives(n) => Cell::Alive,
+			Cell::Alive | Cell::Colored(_) => decay(1, self.states),
+			Cell::Dying(stage) => decay(stage + 1, self.states),
+			Cell::Dead => Cell::Dead,
+			Cell::Wire => Cell::Wire,
+		}
+	}
+
+	/// Rule to step `generation` with, for a neighborhood of `neighbors` cells.
+	///
+	/// A B0 rule turns the whole background alive every generation. Like Golly we keep the board
+	/// relative to the background instead: where the background is alive the board is stored
+	/// inverted, and we step it with an equivalent rule that keeps the stored background dead.
+	/// Without S`neighbors` the background alternates every generation, with it the background
+	/// stays alive from generation one on.
+	pub const fn for_generation(&self, generation: u64, neighbors: usize) -> Self {
+		if !self.born(0) {
+			return *self;
+		}
+		let all = (1 << (neighbors + 1)) - 1;
+		let odd = generation % 2 == 1;
+		let (alive_now, alive_next) = if self.survives(neighbors) {
+			(generation > 0, true)
+		} else {
+			(odd, !odd)
+		};
+		match (alive_now, alive_next) {
+			(false, _) => Self {
+				birth: !self.birth & all,
+				survive: !self.survive & all,
+				..*self
+			},
+			(true, false) => Self {
+				birth: reflect(self.survive, neighbors),
+				survive: reflect(self.birth, neighbors),
+				..*self
+			},
+			(true, true) => Self {
+				birth: !reflect(self.survive, neighbors) & all,
+				survive: !reflect(self.birth, neighbors) & all,
+				..*self
+			},
+		}
+	}
+
+	/// Earliest generation stepped with the same rule as `generation`, see `for_generation`. Counting from
+	/// there after an edit keeps the stored board of a B0 rule relative to the same background.
+	pub const fn restart_generation(&self, generation: u64, neighbors: usize) -> u64 {
+		if !self.born(0) {
+			0
+		} else if self.survives(neighbors) {
+			if generation > 0 { 1 } else { 0 }
+		} else {
+			generation % 2
+		}
+	}
+}
+
+impl std::fmt::Display for Ruleset {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let counts = |mask: u16| (0..=8).filter(|n| mask & (1 << n) != 0).map(|n| n.to_string()).collect::<String>();
+		write!(f, "B{}/S{}", counts(self.birth), counts(self.survive))?;
+		if self.states > 2 {
+			write!(f, "/C{}", self.states)?;
+		}
+		Ok(())
+	}
+}
+
+/// Parses a rulestring in `B3/S23` notation, or the older survival/birth notation `23/3`. Generations
+/// rules add the number of states, `B2/S345/C4` or `345/2/4`. The name of a preset works as well.
+impl std::str::FromStr for Ruleset {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		if let Some((_, rule)) = Self::PRESETS.iter().find(|(name, _)| name.eq_ignore_ascii_case(s.trim())) {
+			return Ok(*rule);
+		}
+		let s = s.trim().to_ascii_uppercase();
+		let counts = |digits: &str| -> Result<u16> {
+			digits.chars().try_fold(0, |mask, c| match c.to_digit(10) {
+				Some(n) if n <= 8 => Ok(mask | 1 << n),
+				_ => bail!("invalid neighbor count `{}`, expected 0-8", c),
+			})
+		};
+		let states = |count: &str| -> Result<u8> {
+			match count.parse() {
+				Ok(states) if states >= 2 => Ok(states),
+				_ => bail!("invalid number of states `{}`, expected at least 2", count),
+			}
+		};
+		let rule = if !s.contains(['B', 'S']) {
+			let mut parts = s.split('/');
+			match (parts.next(), parts.next(), parts.next(), parts.next()) {
+				(Some(survive), Some(birth), states_part, None) => Self {
+					birth: counts(birth)?,
+					survive: counts(survive)?,
+					states: states_part.map_or(Ok(2), states)?,
+				},
+				_ => bail!("expected `B<counts>/S<counts>` or `<survive>/<birth>`"),
+			}
+		} else {
+			let (mut birth, mut survive, mut generations) = (None, None, None);
+			for part in s.split('/') {
+				match part.chars().next() {
+					Some('B') if birth.is_none() => birth = Some(counts(&part[1..])?),
+					Some('S') if survive.is_none() => survive = Some(counts(&part[1..])?),
+					Some('C') if generations.is_none() => generations = Some(states(&part[1..])?),
+					Some(section @ ('B' | 'S' | 'C')) => bail!("`{}` is given twice", section),
+					_ => bail!("expected `B<counts>`, `S<counts>` or `C<states>`, got `{}`", part),
+				}
+			}
+			match (birth, survive) {
+				(Some(birth), Some(survive)) => Self {
+					birth,
+					survive,
+					states: generations.unwrap_or(2),
+				},
+				_ => bail!("rulestring needs both a `B` and an `S` part"),
+			}
+		};
+		if rule.states > 2 && rule.born(0) {
+			bail!("B0 is not supported for Generations rules");
+		}
+		Ok(rule)
+	}
+}
+
+/// Splits off Golly's neighborhood suffix, `B2/S34H` is `B2/S34` on the hex grid and `B1/S1V` is `B1/S1` on
+/// the von Neumann neighborhood
+pub fn strip_topology(rule: &str) -> (&str, Option<Topology>) {
+	let rule = rule.trim();
+	match rule.chars().last().map(|c| c.to_ascii_uppercase()) {
+		Some('H') => (&rule[..rule.len() - 1], Some(Topology::Hex)),
+		Some('V') => (&rule[..rule.len() - 1], Some(Topology::VonNeumann)),
+		_ => (rule, None),
+	}
+}
+
+/// Wireworld: an electron head (`Alive`) becomes a tail (`Dying(1)`), a tail turns back into wire and
+/// wire turns into a head next to one or two heads. Empty cells stay empty.
+pub const fn wireworld(center: Cell, heads: usize) -> Cell {
+	match center {
+		Cell::Alive | Cell::Colored(_) => Cell::Dying(1),
+		Cell::Dying(_) => Cell::Wire,
+		Cell::Wire if heads == 1 || heads == 2 => Cell::Alive,
+		Cell::Wire => Cell::Wire,
+		Cell::Dead => Cell::Dead,
+	}
+}
+
+/// Largest radius a Larger than Life rule may have, the neighborhood has to fit on the board
+pub const MAX_RADIUS: usize = 25;
+
+/// Shape of a Larger than Life neighborhood
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Reach {
+	/// Every cell within `radius` in both directions, a square
+	Moore,
+	/// Every cell within `radius` steps along the grid, a diamond
+	VonNeumann,
+}
+
+/// Larger than Life rule: like a birth/survival rule, but over every cell within `radius` and with
+/// a range of counts for birth and survival instead of a set of single counts.
+///
+/// Rules are written in Golly's notation, Bugs is `R5,C0,M1,S34..58,B34..45,NM`. `C` is the number
+/// of states like in Generations rules, where 0 means 2. With `M1` the center cell counts as its own
+/// neighbor. `NM` is the square Moore neighborhood, `NN` the von Neumann diamond.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LargerThanLife {
+	pub radius: usize,
+	states: u8,
+	pub middle: bool,
+	survive: (usize, usize),
+	birth: (usize, usize),
+	pub reach: Reach,
+}
+
+impl LargerThanLife {
+	/// Well-known rules, with their names.
+	pub const PRESETS: &'static [(&'static str, Self)] = &[
+		("Bugs", Self::moore(5, (34, 58), (34, 45))),
+		("Bosco's Rule", Self::moore(5, (33, 57), (34, 45))),
+		("Majority", Self::moore(4, (41, 81), (41, 81))),
+	];
+
+	const fn moore(radius: usize, survive: (usize, usize), birth: (usize, usize)) -> Self {
+		Self {
+			radius,
+			states: 2,
+			middle: true,
+			survive,
+			birth,
+			reach: Reach::Moore,
+		}
+	}
+
+	pub const fn states(&self) -> u8 {
+		self.states
+	}
+
+	/// Next state of a `center` cell with `n` live cells in its neighborhood
+	pub const fn next(&self, center: Cell, n: usize) -> Cell {
+		let (survive, birth) = (self.survive, self.birth);
+		match center {
+			Cell::Dead if birth.0 <= n && n <= birth.1 => Cell::Alive,
+			Cell::Alive | Cell::Colored(_) if survive.0 <= n && n <= survive.1 => Cell::Alive,
+			Cell::Alive | Cell::Colored(_) => decay(1, self.states),
+			Cell::Dying(stage) => decay(stage + 1, self.states),
+			Cell::Dead => Cell::Dead,
+			Cell::Wire => Cell::Wire,
+		}
+	}
+
+	/// Whether `s` is meant as a Larger than Life rule rather than a birth/survival rule
+	pub fn matches(s: &str) -> bool {
+		let s = s.trim();
+		let mut chars = s.chars();
+		matches!((chars.next(), chars.next()), (Some('R' | 'r'), Some('0'..='9')))
+			|| Self::PRESETS.iter().any(|(name, _)| name.eq_ignore_ascii_case(s))
+	}
+}
+
+impl std::fmt::Display for LargerThanLife {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(
+			f,
+			"R{},C{},M{},S{}..{},B{}..{},N{}",
+			self.radius,
+			if self.states > 2 { self.states } else { 0 },
+			u8::from(self.middle),
+			self.survive.0,
+			self.survive.1,
+			self.birth.0,
+			self.birth.1,
+			match self.reach {
+				Reach::Moore => 'M',
+				Reach::VonNeumann => 'N',
+			}
+		)
+	}
+}
+
+impl std::str::FromStr for LargerThanLife {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		if let Some((_, rule)) = Self::PRESETS.iter().find(|(name, _)| name.eq_ignore_ascii_case(s.trim())) {
+			return Ok(*rule);
+		}
+		let s = s.trim().to_ascii_uppercase();
+		let number = |part: &str| -> Result<usize> { part.parse().with_context(|| format!("invalid number `{}`", part)) };
+		let range = |part: &str| -> Result<(usize, usize)> {
+			let (min, max) = part.split_once("..").ok_or_else(|| anyhow!("expected `<min>..<max>`, got `{}`", part))?;
+			match (number(min)?, number(max)?) {
+				(min, max) if min <= max => Ok((min, max)),
+				_ => bail!("empty range `{}`", part),
+			}
+		};
+		let (mut radius, mut states, mut middle, mut survive, mut birth, mut reach) = (None, None, None, None, None, None);
+		for part in s.split(',') {
+			let value = part.get(1..).unwrap_or_default();
+			match part.chars().next() {
+				Some('R') if radius.is_none() => radius = Some(number(value)?),
+				Some('C') if states.is_none() => states = Some(number(value)?),
+				Some('M') if middle.is_none() => {
+					middle = Some(match value {
+						"0" => false,
+						"1" => true,
+						_ => bail!("expected `M0` or `M1`, got `{}`", part),
+					})
+				}
+				Some('S') if survive.is_none() => survive = Some(range(value)?),
+				Some('B') if birth.is_none() => birth = Some(range(value)?),
+				Some('N') if reach.is_none() => {
+					reach = Some(match value {
+						"M" => Reach::Moore,
+						"N" => Reach::VonNeumann,
+						_ => bail!("expected `NM` or `NN`, got `{}`", part),
+					})
+				}
+				Some(section @ ('R' | 'C' | 'M' | 'S' | 'B' | 'N')) => bail!("`{}` is given twice", section),
+				_ => bail!("expected `R`, `C`, `M`, `S`, `B` or `N`, got `{}`", part),
+			}
+		}
+		let (Some(radius), Some(survive), Some(birth)) = (radius, survive, birth) else {
+			bail!("rule needs at least an `R`, an `S` and a `B` part");
+		};
+		if !(1..=MAX_RADIUS).contains(&radius) {
+			bail!("radius must be between 1 and {}", MAX_RADIUS);
+		}
+		if birth.0 == 0 {
+			bail!("births with 0 live neighbors are not supported");
+		}
+		let states = match states.unwrap_or(0) {
+			0 | 1 => 2,
+			states @ 2..=255 => states as u8,
+			states => bail!("invalid number of states {}, expected at most 255", states),
+		};
+		Ok(Self {
+			radius,
+			states,
+			middle: middle.unwrap_or(false),
+			survive,
+			birth,
+			reach: reach.unwrap_or(Reach::Moore),
+		})
+	}
+}
+
+/// Cell in dying `stage` under a rule with `states` states, or dead once it has decayed through all of them
+const fn decay(stage: u8, states: u8) -> Cell {
+	if stage + 1 < states {
+		Cell::Dying(stage)
+	} else {
+		Cell::Dead
+	}
+}
+
+/// Maps neighbor count `n` to `neighbors - n`, the count as seen from the inverted board
+const fn reflect(mask: u16, neighbors: usize) -> u16 {
+	let mut reflected = 0;
+	let mut n = 0;
+	while n <= neighbors {
+		if mask & (1 << n) != 0 {
+			reflected |= 1 << (neighbors - n);
+		}
+		n += 1;
+	}
+	reflected
+}
+
+const fn mask(counts: &[usize]) -> u16 {
+	let mut mask = 0;
+	let mut i = 0;
+	while i < counts.len() {
+		mask |= 1 << counts[i];
+		i += 1;
+	}
+	mask
+}
+
+/// Farthest a neighbor in a custom kernel may be from the cell, in either direction
+pub const MAX_KERNEL_REACH: isize = 8;
+
+/// Custom neighborhood, replacing the square, hex or von Neumann one. At most eight offsets, so counts
+/// and rule tables work the same as on the Moore neighborhood.
+///
+/// Kernels are read from a text file listing `<dx>,<dy>` offsets separated by whitespace, `#` starts a
+/// comment. The knight's move neighborhood is
+///
+/// ```text
+/// 1,-2  2,-1  2,1  1,2  -1,2  -2,1  -2,-1  -1,-2
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kernel {
+	offsets: Vec<(isize, isize)>,
+}
+
+impl Kernel {
+	pub fn offsets(&self) -> &[(isize, isize)] {
+		&self.offsets
+	}
+}
+
+impl std::str::FromStr for Kernel {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let mut offsets = vec![];
+		for line in s.lines() {
+			let line = line.split('#').next().unwrap_or_default();
+			for offset in line.split_whitespace() {
+				let (dx, dy) = offset.split_once(',').ok_or_else(|| anyhow!("expected `<dx>,<dy>`, got `{}`", offset))?;
+				let parse = |d: &str| -> Result<isize> {
+					match d.parse() {
+						Ok(d) if (-MAX_KERNEL_REACH..=MAX_KERNEL_REACH).contains(&d) => Ok(d),
+						_ => bail!("invalid offset `{}`, expected a number from {} to {}", d, -MAX_KERNEL_REACH, MAX_KERNEL_REACH),
+					}
+				};
+				let parsed = (parse(dx)?, parse(dy)?);
+				if parsed == (0, 0) {
+					bail!("a cell can't be its own neighbor");
+				}
+				if offsets.contains(&parsed) {
+					bail!("offset `{}` is given twice", offset);
+				}
+				offsets.push(parsed);
+			}
+		}
+		if !(1..=8).contains(&offsets.len()) {
+			bail!("a kernel needs 1 to 8 offsets, got {}", offsets.len());
+		}
+		Ok(Self { offsets })
+	}
+}
+
+/// General two-state rule: the next state of a cell is looked up from its own state and the exact
+/// arrangement of its live neighbors, given as a bitmask in kernel order.
+///
+/// Tables are read from a text file with one transition per line, `<center> <neighbors> -> <next>`.
+/// `<neighbors>` is either a string of up to eight `0`/`1` for one specific arrangement, or a plain
+/// count that matches every arrangement with that many live neighbors. Later lines override earlier
+/// ones, anything not listed dies. `#` starts a comment. Conway's Life is
+///
+/// ```text
+/// 0 3 -> 1
+/// 1 2 -> 1
+/// 1 3 -> 1
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleTable {
+	/// One bit per neighbor arrangement, indexed by the center state
+	alive: [[u64; 4]; 2],
+}
+
+impl RuleTable {
+	pub const fn next(&self, center: Cell, neighbors: u8) -> Cell {
+		// NOTE(Simon): tables are two-state, a dying cell left over from a Generations rule counts as dead
+		let words = &self.alive[center.is_alive() as usize];
+		if words[neighbors as usize / 64] & (1 << (neighbors % 64)) != 0 {
+			Cell::Alive
+		} else {
+			Cell::Dead
+		}
+	}
+
+	const fn set(&mut self, center: Cell, neighbors: u8, next: Cell) {
+		let word = &mut self.alive[matches!(center, Cell::Alive) as usize][neighbors as usize / 64];
+		match next {
+			Cell::Alive => *word |= 1 << (neighbors % 64),
+			_ => *word &= !(1 << (neighbors % 64)),
+		}
+	}
+}
+
+impl std::str::FromStr for RuleTable {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let mut table = Self { alive: [[0; 4]; 2] };
+		for (i, line) in s.lines().enumerate() {
+			let line = line.split('#').next().unwrap_or_default().trim();
+			if line.is_empty() {
+				continue;
+			}
+			parse_transition(&mut table, line).with_context(|| format!("line {}: `{}`", i + 1, line))?;
+		}
+		Ok(table)
+	}
+}
+
+fn parse_transition(table: &mut RuleTable, line: &str) -> Result<()> {
+	let (lhs, next) = line.split_once("->").ok_or_else(|| anyhow!("expected `<center> <neighbors> -> <next>`"))?;
+	let mut lhs = lhs.split_whitespace();
+	let (center, neighbors) = match (lhs.next(), lhs.next(), lhs.next()) {
+		(Some(center), Some(neighbors), None) => (parse_state(center)?, neighbors),
+		_ => bail!("expected `<center> <neighbors>` before `->`"),
+	};
+	let next = parse_state(next.trim())?;
+
+	if neighbors.len() == 1 {
+		let count: u32 = neighbors.parse().with_context(|| format!("invalid neighbor count `{}`", neighbors))?;
+		if count > 8 {
+			bail!("a cell has at most 8 neighbors");
+		}
+		for arrangement in 0..=u8::MAX {
+			if arrangement.count_ones() == count {
+				table.set(center, arrangement, next);
+			}
+		}
+		return Ok(());
+	}
+	if neighbors.len() > 8 || neighbors.chars().any(|c| c != '0' && c != '1') {
+		bail!("neighbors must be a count or up to eight `0`/`1`");
+	}
+	let arrangement = neighbors
+		.chars()
+		.enumerate()
+		.filter(|(_, c)| *c == '1')
+		.fold(0u8, |mask, (i, _)| mask | 1 << i);
+	table.set(center, arrangement, next);
+	Ok(())
+}
+
+fn parse_state(state: &str) -> Result<Cell> {
+	match state {
+		"0" => Ok(Cell::Dead),
+		"1" => Ok(Cell::Alive),
+		_ => bail!("invalid cell state `{}`, expected 0 or 1", state),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn b0_background() {
+		for rule in ["B0/S", "B03/S23", "B0123478/S01234678", "B01/S8"] {
+			let rule: Ruleset = rule.parse().unwrap();
+			// NOTE(Simon): where the actual background is alive, see `for_generation`
+			let background = |generation: u64| if rule.survives(8) { generation > 0 } else { generation % 2 == 1 };
+			let flip = |cell: bool, flip: bool| cell != flip;
+			for generation in 0..6 {
+				let stored_rule = rule.for_generation(generation, 8);
+				assert!(!stored_rule.born(0), "{} leaves the stored background dead", rule);
+				let (now, next) = (background(generation), background(generation + 1));
+				for alive in [false, true] {
+					for n in 0..=8 {
+						let actual = rule.next(if alive { Cell::Alive } else { Cell::Dead }, n).is_alive();
+						let stored = if flip(alive, now) { Cell::Alive } else { Cell::Dead };
+						let stored_n = if now { 8 - n } else { n };
+						assert_eq!(
+							stored_rule.next(stored, stored_n).is_alive(),
+							flip(actual, next),
+							"{} in generation {} with {} neighbors",
+							rule,
+							generation,
+							n
+						);
+					}
+				}
+				let restart = rule.restart_generation(generation, 8);
+				assert!(restart <= generation && background(restart) == now);
+				assert_eq!(rule.for_generation(restart, 8), stored_rule);
+			}
+		}
+	}
+
+	#[test]
+	fn life_exhaustive() {
+		let rule = Ruleset::CONWAY;
+		for n in 0..=8 {
+			assert_eq!(rule.next(Cell::Dead, n), if n == 3 { Cell::Alive } else { Cell::Dead }, "dead with {}", n);
+			let survives = if n == 2 || n == 3 { Cell::Alive } else { Cell::Dead };
+			assert_eq!(rule.next(Cell::Alive, n), survives, "alive with {}", n);
+			assert_eq!(rule.next(Cell::Colored(2), n), survives, "colored with {}", n);
+			assert_eq!(rule.next(Cell::Wire, n), Cell::Wire, "wire with {}", n);
+		}
+	}
+
+	#[test]
+	fn generations_exhaustive() {
+		let rule: Ruleset = "B2/S34/C4".parse().unwrap();
+		for n in 0..=8 {
+			assert_eq!(rule.next(Cell::Dead, n), if n == 2 { Cell::Alive } else { Cell::Dead }, "dead with {}", n);
+			let survives = if n == 3 || n == 4 { Cell::Alive } else { Cell::Dying(1) };
+			assert_eq!(rule.next(Cell::Alive, n), survives, "alive with {}", n);
+			// NOTE(Simon): dying cells decay whatever their neighbors, the last stage is followed by death
+			assert_eq!(rule.next(Cell::Dying(1), n), Cell::Dying(2), "dying(1) with {}", n);
+			assert_eq!(rule.next(Cell::Dying(2), n), Cell::Dead, "dying(2) with {}", n);
+		}
+		for n in 0..=8 {
+			assert_eq!(Ruleset::BRIANS_BRAIN.next(Cell::Alive, n), Cell::Dying(1), "alive with {}", n);
+			assert_eq!(Ruleset::BRIANS_BRAIN.next(Cell::Dying(1), n), Cell::Dead, "dying with {}", n);
+		}
+	}
+
+	#[test]
+	fn table_reproduces_life() {
+		let table: RuleTable = "0 3 -> 1\n1 2 -> 1\n1 3 -> 1\n".parse().unwrap();
+		const MOORE: [(isize, isize); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+		let side = 32;
+		let mut board: Vec<Vec<Cell>> = (0..side)
+			.map(|y| (0..side).map(|x| if crate::conway::mix((y * side + x) as u64).is_multiple_of(3) { Cell::Alive } else { Cell::Dead }).collect())
+			.collect();
+		for generation in 0..16 {
+			let next: Vec<Vec<Cell>> = (0..side)
+				.map(|y| {
+					(0..side)
+						.map(|x| {
+							// NOTE(Simon): neighbors on a torus, in kernel order for the arrangement bitmask
+							let mut arrangement = 0u8;
+							for (i, (dx, dy)) in MOORE.iter().enumerate() {
+								let (nx, ny) = ((x as isize + dx).rem_euclid(side as isize), (y as isize + dy).rem_euclid(side as isize));
+								if board[ny as usize][nx as usize].is_alive() {
+									arrangement |= 1 << i;
+								}
+							}
+							let from_table = table.next(board[y][x], arrangement);
+							assert_eq!(from_table, Ruleset::CONWAY.next(board[y][x], arrangement.count_ones() as usize), "generation {}", generation);
+							from_table
+						})
+						.collect()
+				})
+				.collect();
+			board = next;
+		}
+		assert!(board.iter().flatten().any(|cell| cell.is_alive()), "the soup died out, the test proves nothing");
+	}
+}
@@ -0,0 +1,200 @@
+use anyhow::{anyhow, bail, Context, Result};
+use std::path::PathBuf;
+
+use crate::conway::{Boundary, Topology, DEFAULT_BOARD_SIZE, MAX_LIFE_COLORS};
+use crate::golly::GollyRule;
+use crate::rule::{Kernel, LargerThanLife, RuleTable, Ruleset};
+use crate::share;
+
+/// Everything `--session` restores on launch, written as one `<key> <value>` pair per line.
+///
+/// Rule tables, Golly rules and kernels are saved as the file they were read from and read again on launch.
+#[derive(Debug, Clone)]
+pub struct SavedSession {
+	/// Every cell of the board as a states code, see `share::encode_states`
+	pub board: String,
+	/// Width and height of the board
+	pub size: (usize, usize),
+	pub generation: u64,
+	pub simulating: bool,
+	pub topology: Topology,
+	pub boundary: Boundary,
+	pub rule: Ruleset,
+	pub ltl: Option<LargerThanLife>,
+	pub rule_table: Option<(PathBuf, RuleTable)>,
+	pub rule_file: Option<(PathBuf, GollyRule)>,
+	pub kernel: Option<(PathBuf, Kernel)>,
+	pub colors: u8,
+	pub wireworld: bool,
+}
+
+/// Files the rule table, Golly rule and kernel in use were read from, sessions save these instead of the rules
+#[derive(Debug, Clone, Default)]
+pub struct RuleSources {
+	pub rule_table: Option<PathBuf>,
+	pub rule_file: Option<PathBuf>,
+	pub kernel: Option<PathBuf>,
+}
+
+impl std::fmt::Display for SavedSession {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		writeln!(f, "version 2")?;
+		writeln!(f, "cells {}", self.board)?;
+		writeln!(f, "size {}x{}", self.size.0, self.size.1)?;
+		writeln!(f, "generation {}", self.generation)?;
+		writeln!(f, "mode {}", if self.simulating { "simulation" } else { "editor" })?;
+		writeln!(f, "topology {}", self.topology.name())?;
+		writeln!(f, "boundary {}", self.boundary.name())?;
+		writeln!(f, "rule {}", self.rule)?;
+		if let Some(ltl) = &self.ltl {
+			writeln!(f, "ltl {}", ltl)?;
+		}
+		if let Some((path, _)) = &self.rule_table {
+			writeln!(f, "rule-table {}", path.display())?;
+		}
+		if let Some((path, _)) = &self.rule_file {
+			writeln!(f, "rule-file {}", path.display())?;
+		}
+		if let Some((path, _)) = &self.kernel {
+			writeln!(f, "kernel {}", path.display())?;
+		}
+		writeln!(f, "colors {}", self.colors)?;
+		writeln!(f, "wireworld {}", if self.wireworld { "on" } else { "off" })
+	}
+}
+
+impl std::str::FromStr for SavedSession {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let mut session = Self {
+			board: String::new(),
+			// NOTE(Simon): sessions saved before the board could be resized don't have a size line
+			size: DEFAULT_BOARD_SIZE,
+			generation: 0,
+			simulating: false,
+			topology: Topology::Square,
+			// NOTE(Simon): sessions saved before the board could wrap don't have a boundary line
+			boundary: Boundary::Dead,
+			rule: Ruleset::CONWAY,
+			ltl: None,
+			rule_table: None,
+			rule_file: None,
+			kernel: None,
+			colors: 1,
+			wireworld: false,
+		};
+		let mut version = None;
+		let mut rule = None;
+		let mut live_cells = None;
+		for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+			let (key, value) = line.split_once(' ').ok_or_else(|| anyhow!("expected `<key> <value>`, got `{}`", line))?;
+			match (key, value.trim()) {
+				("version", value) => version = Some(value.to_string()),
+				// NOTE(Simon): version 1 saved a share code, which only keeps live cells
+				("board", value) => live_cells = Some(value.to_string()),
+				("cells", value) => session.board = value.to_string(),
+				("size", value) => session.size = parse_size(value)?,
+				("generation", value) => session.generation = value.parse().with_context(|| format!("invalid generation `{}`", value))?,
+				("mode", "editor") => session.simulating = false,
+				("mode", "simulation") => session.simulating = true,
+				("topology", "square") => session.topology = Topology::Square,
+				("topology", "hex") => session.topology = Topology::Hex,
+				("topology", "von-neumann") => session.topology = Topology::VonNeumann,
+				("boundary", "dead") => session.boundary = Boundary::Dead,
+				("boundary", "wrap") => session.boundary = Boundary::Wrap,
+				("boundary", "mirror") => session.boundary = Boundary::Mirror,
+				("rule", value) => rule = Some(value.parse().with_context(|| format!("invalid rule `{}`", value))?),
+				("ltl", value) => session.ltl = Some(value.parse().with_context(|| format!("invalid rule `{}`", value))?),
+				("rule-table", path) => session.rule_table = Some(read(path, "rule table")?),
+				("rule-file", path) => session.rule_file = Some(read(path, "rule file")?),
+				("kernel", path) => session.kernel = Some(read(path, "kernel")?),
+				("colors", value) => session.colors = value.parse().with_context(|| format!("invalid colors `{}`", value))?,
+				("wireworld", "on") => session.wireworld = true,
+				("wireworld", "off") => session.wireworld = false,
+				(key, value) => bail!("unknown setting `{} {}`", key, value),
+			}
+		}
+		// NOTE(Simon): sessions saved before rules could be changed don't have a rule line
+		session.rule = rule.unwrap_or_else(|| session.topology.default_rule());
+		if !(1..=MAX_LIFE_COLORS as u8).contains(&session.colors) {
+			bail!("colors must be between 1 and {}", MAX_LIFE_COLORS);
+		}
+		if let Some(code) = live_cells {
+			session.board = share::encode_states(&share::decode(&code, session.size).context("invalid board")?);
+		}
+		match version.as_deref() {
+			Some("1" | "2") => Ok(session),
+			Some(version) => bail!("unsupported session version {}", version),
+			None => bail!("missing session version"),
+		}
+	}
+}
+
+/// The file at `path` parsed as a `what`, along with its absolute path so sessions find it from anywhere
+pub fn read<T>(path: &str, what: &str) -> Result<(PathBuf, T)>
+where
+	T: std::str::FromStr<Err = anyhow::Error>,
+{
+	let source = std::fs::read_to_string(path).with_context(|| format!("could not read {}", path))?;
+	let parsed = source.parse().with_context(|| format!("invalid {} {}", what, path))?;
+	Ok((std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path)), parsed))
+}
+
+/// `<width>x<height>`, as written by sessions and given to `--board`
+pub fn parse_size(s: &str) -> Result<(usize, usize)> {
+	let (width, height) = s.split_once('x').ok_or_else(|| anyhow!("expected `<width>x<height>`, got `{}`", s))?;
+	let side = |side: &str| side.trim().parse::<usize>().with_context(|| format!("invalid board size `{}`", s));
+	Ok((side(width)?, side(height)?))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::conway::Cell;
+
+	#[test]
+	fn round_trip() {
+		let mut board = vec![vec![Cell::Dead; 12]; 10];
+		board[2][3] = Cell::Alive;
+		board[2][4] = Cell::Dying(3);
+		board[7][1] = Cell::Wire;
+		board[9][11] = Cell::Colored(1);
+		let path = std::env::temp_dir().join(format!("vitae-kernel-{}.txt", std::process::id()));
+		std::fs::write(&path, "1,0 0,1 -1,0 0,-1").unwrap();
+		let session = SavedSession {
+			board: share::encode_states(&board),
+			size: (12, 10),
+			generation: 17,
+			simulating: true,
+			topology: Topology::Hex,
+			boundary: Boundary::Wrap,
+			rule: "B2/S/C4".parse().unwrap(),
+			ltl: Some("R2,C0,M1,S3..5,B3..4,NM".parse().unwrap()),
+			rule_table: None,
+			rule_file: None,
+			kernel: Some(read(path.to_str().unwrap(), "kernel").unwrap()),
+			colors: 2,
+			wireworld: true,
+		};
+		let text = session.to_string();
+		let parsed: SavedSession = text.parse().unwrap();
+		std::fs::remove_file(&path).unwrap();
+		assert_eq!(parsed.to_string(), text);
+		assert_eq!(share::decode_states(&parsed.board, parsed.size).unwrap(), board);
+		assert_eq!((parsed.rule, parsed.ltl, parsed.colors, parsed.wireworld), (session.rule, session.ltl, 2, true));
+		assert_eq!(parsed.kernel.map(|(_, kernel)| kernel), session.kernel.map(|(_, kernel)| kernel));
+	}
+
+	#[test]
+	fn version_1() {
+		let mut board = vec![vec![Cell::Dead; 8]; 6];
+		board[1][2] = Cell::Alive;
+		board[4][5] = Cell::Alive;
+		let text = format!("version 1\nboard {}\nsize 8x6\ngeneration 3\nrule B36/S23\n", share::encode(&board));
+		let session: SavedSession = text.parse().unwrap();
+		assert_eq!(share::decode_states(&session.board, (8, 6)).unwrap(), board);
+		assert_eq!((session.generation, session.rule.to_string().as_str(), session.colors), (3, "B36/S23", 1));
+		assert!(format!("version 1\nboard {}\nsize 2x2\n", share::encode(&board)).parse::<SavedSession>().is_err());
+	}
+}
@@ -0,0 +1,292 @@
+//! Compact, URL-safe encoding of a board.
+//!
+//! A share code is the base64url (unpadded) encoding of a list of LEB128 varints:
+//! the bounding box of the live cells as `x y width height`, followed by the run lengths
+//! of alternating dead and alive cells inside the box in row-major order, starting with dead.
+//! Trailing dead cells are not stored.
+//!
+//! A states code, which sessions use, is laid out the same but keeps every state: the bounding box covers
+//! every cell that is not dead, and each run of equal cells is followed by the code of its state.
+
+use anyhow::{bail, Result};
+
+use crate::conway::{Board, Cell};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn encode(board: &Board) -> String {
+	let mut bytes = vec![];
+	let (x0, y0, w, h) = bounding_box(board, Cell::is_alive).unwrap_or_default();
+	for n in &[x0, y0, w, h] {
+		write_varint(&mut bytes, *n);
+	}
+
+	let mut current = Cell::Dead;
+	let mut run = 0;
+	for row in &board[y0..y0 + h] {
+		// NOTE(Simon): share codes only keep live cells, dying cells of a Generations rule are dropped
+		for cell in row[x0..x0 + w].iter().map(|cell| if cell.is_alive() { Cell::Alive } else { Cell::Dead }) {
+			if cell != current {
+				write_varint(&mut bytes, run);
+				current = cell;
+				run = 0;
+			}
+			run += 1;
+		}
+	}
+	if current == Cell::Alive {
+		write_varint(&mut bytes, run);
+	}
+	to_base64(&bytes)
+}
+
+/// Board of `width` x `height` cells with the pattern of `code` on it
+pub fn decode(code: &str, (width, height): (usize, usize)) -> Result<Board> {
+	let bytes = from_base64(code)?;
+	let mut reader = bytes.iter().copied();
+	let mut next = || read_varint(&mut reader);
+	let (x0, y0, w, h) = match (next()?, next()?, next()?, next()?) {
+		(Some(x0), Some(y0), Some(w), Some(h)) => (x0, y0, w, h),
+		_ => bail!("share code is truncated"),
+	};
+	if x0.saturating_add(w) > width || y0.saturating_add(h) > height {
+		bail!("pattern of {}x{} at ({}, {}) does not fit on the board", w, h, x0, y0);
+	}
+
+	let mut board = vec![vec![Cell::Dead; width]; height];
+	let mut current = Cell::Dead;
+	let mut offset = 0;
+	let mut first = true;
+	while let Some(run) = next()? {
+		if run == 0 && !first {
+			bail!("share code contains an empty run");
+		}
+		if run > w * h - offset {
+			bail!("share code runs past the end of its bounding box");
+		}
+		if current == Cell::Alive {
+			for i in offset..offset + run {
+				board[y0 + i / w][x0 + i % w] = Cell::Alive;
+			}
+		}
+		offset += run;
+		current = if current == Cell::Alive { Cell::Dead } else { Cell::Alive };
+		first = false;
+	}
+	Ok(board)
+}
+
+/// Every cell of `board` with its state, dying cells and wire included
+pub fn encode_states(board: &Board) -> String {
+	let mut bytes = vec![];
+	let (x0, y0, w, h) = bounding_box(board, |cell| cell != Cell::Dead).unwrap_or_default();
+	for n in &[x0, y0, w, h] {
+		write_varint(&mut bytes, *n);
+	}
+
+	let mut cells = board[y0..y0 + h].iter().flat_map(|row| &row[x0..x0 + w]).copied().peekable();
+	while let Some(cell) = cells.next() {
+		let mut run = 1;
+		while cells.next_if_eq(&cell).is_some() {
+			run += 1;
+		}
+		// NOTE(Simon): the box ends on a cell that is not dead, so the last run never is dead
+		write_varint(&mut bytes, run);
+		write_varint(&mut bytes, state_code(cell));
+	}
+	to_base64(&bytes)
+}
+
+/// Board of `width` x `height` cells with the states of `code` on it, see `encode_states`
+pub fn decode_states(code: &str, (width, height): (usize, usize)) -> Result<Board> {
+	let bytes = from_base64(code)?;
+	let mut reader = bytes.iter().copied();
+	let mut next = || read_varint(&mut reader);
+	let (x0, y0, w, h) = match (next()?, next()?, next()?, next()?) {
+		(Some(x0), Some(y0), Some(w), Some(h)) => (x0, y0, w, h),
+		_ => bail!("states code is truncated"),
+	};
+	if x0.saturating_add(w) > width || y0.saturating_add(h) > height {
+		bail!("pattern of {}x{} at ({}, {}) does not fit on the board", w, h, x0, y0);
+	}
+
+	let mut board = vec![vec![Cell::Dead; width]; height];
+	let mut offset = 0;
+	while let Some(run) = next()? {
+		let Some(code) = next()? else {
+			bail!("states code is truncated");
+		};
+		let cell = from_state_code(code)?;
+		if run == 0 {
+			bail!("states code contains an empty run");
+		}
+		if run > w * h - offset {
+			bail!("states code runs past the end of its bounding box");
+		}
+		for i in offset..offset + run {
+			board[y0 + i / w][x0 + i % w] = cell;
+		}
+		offset += run;
+	}
+	Ok(board)
+}
+
+fn state_code(cell: Cell) -> usize {
+	match cell {
+		Cell::Dead => 0,
+		Cell::Alive => 1,
+		Cell::Wire => 2,
+		Cell::Dying(stage) => 0x100 | usize::from(stage),
+		Cell::Colored(color) => 0x200 | usize::from(color),
+	}
+}
+
+fn from_state_code(code: usize) -> Result<Cell> {
+	Ok(match (code >> 8, code as u8) {
+		(0, 0) => Cell::Dead,
+		(0, 1) => Cell::Alive,
+		(0, 2) => Cell::Wire,
+		(1, stage) if stage > 0 => Cell::Dying(stage),
+		(2, color) if color > 0 => Cell::Colored(color),
+		_ => bail!("states code contains unknown state {}", code),
+	})
+}
+
+fn bounding_box(board: &Board, keep: impl Fn(Cell) -> bool) -> Option<(usize, usize, usize, usize)> {
+	let mut bounds: Option<(usize, usize, usize, usize)> = None;
+	for (y, row) in board.iter().enumerate() {
+		for (x, cell) in row.iter().enumerate() {
+			if !keep(*cell) {
+				continue;
+			}
+			bounds = Some(match bounds {
+				Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+				None => (x, y, x, y),
+			});
+		}
+	}
+	bounds.map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: usize) {
+	while n >= 0x80 {
+		bytes.push((n as u8 & 0x7f) | 0x80);
+		n >>= 7;
+	}
+	bytes.push(n as u8);
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Result<Option<usize>> {
+	let mut n = 0usize;
+	let mut shift = 0;
+	let mut started = false;
+	for byte in bytes {
+		started = true;
+		if shift >= usize::BITS || (byte & 0x7f) as usize > usize::MAX >> shift {
+			bail!("share code contains an oversized number");
+		}
+		n |= ((byte & 0x7f) as usize) << shift;
+		if byte & 0x80 == 0 {
+			return Ok(Some(n));
+		}
+		shift += 7;
+	}
+	if started {
+		bail!("share code is truncated");
+	}
+	Ok(None)
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+	let mut out = String::with_capacity((bytes.len() * 4).div_ceil(3));
+	for chunk in bytes.chunks(3) {
+		let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+		for i in 0..=chunk.len() {
+			out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+		}
+	}
+	out
+}
+
+fn from_base64(code: &str) -> Result<Vec<u8>> {
+	let code = code.trim().as_bytes();
+	if code.len() % 4 == 1 {
+		bail!("share code has an invalid length");
+	}
+	let mut bytes = Vec::with_capacity(code.len() * 3 / 4);
+	for chunk in code.chunks(4) {
+		let mut n = 0u32;
+		for (i, c) in chunk.iter().enumerate() {
+			let value = match ALPHABET.iter().position(|a| a == c) {
+				Some(value) => value as u32,
+				None => bail!("share code contains invalid character `{}`", *c as char),
+			};
+			n |= value << (18 - 6 * i);
+		}
+		bytes.extend_from_slice(&n.to_be_bytes()[1..chunk.len()]);
+	}
+	Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn board() -> Board {
+		let mut board = vec![vec![Cell::Dead; 40]; 30];
+		for (x, y) in [(3, 4), (4, 4), (5, 4), (20, 10), (21, 11), (19, 12), (20, 12), (21, 12), (39, 29)] {
+			board[y][x] = Cell::Alive;
+		}
+		board
+	}
+
+	#[test]
+	fn round_trip() {
+		let board = board();
+		assert_eq!(decode(&encode(&board), (40, 30)).unwrap(), board);
+		let empty = vec![vec![Cell::Dead; 8]; 8];
+		assert_eq!(decode(&encode(&empty), (8, 8)).unwrap(), empty);
+	}
+
+	#[test]
+	fn states_round_trip() {
+		let mut board = board();
+		board[5][6] = Cell::Dying(1);
+		board[5][7] = Cell::Dying(200);
+		board[0][0] = Cell::Wire;
+		board[29][38] = Cell::Colored(3);
+		assert_eq!(decode_states(&encode_states(&board), (40, 30)).unwrap(), board);
+		let empty = vec![vec![Cell::Dead; 8]; 8];
+		assert_eq!(decode_states(&encode_states(&empty), (8, 8)).unwrap(), empty);
+		// NOTE(Simon): a 1x1 box holding a state that does not exist
+		assert!(decode_states(&to_base64(&[0, 0, 1, 1, 1, 0x83, 0x06]), (40, 30)).is_err());
+	}
+
+	#[test]
+	fn truncated() {
+		let code = encode(&board());
+		for end in 0..code.len() {
+			// NOTE(Simon): a prefix may still happen to be a valid code, it just must not panic
+			let _ = decode(&code[..end], (40, 30));
+		}
+		assert!(decode(&code[..2], (40, 30)).is_err());
+		assert!(decode(&to_base64(&[1, 2, 3, 0x80]), (40, 30)).is_err());
+	}
+
+	#[test]
+	fn not_base64() {
+		assert!(decode("AAAA*AAA", (40, 30)).is_err());
+		assert!(decode("AAAAA", (40, 30)).is_err());
+		assert!(decode("AAA=", (40, 30)).is_err());
+	}
+
+	#[test]
+	fn oversized() {
+		assert!(decode(&to_base64(&[0xff; 12]), (40, 30)).is_err());
+		// NOTE(Simon): a 100x100 pattern on a 40x30 board
+		assert!(decode(&to_base64(&[0, 0, 100, 100, 0, 1]), (40, 30)).is_err());
+		// NOTE(Simon): a run of 5 in a 2x2 box
+		assert!(decode(&to_base64(&[0, 0, 2, 2, 0, 5]), (40, 30)).is_err());
+	}
+}
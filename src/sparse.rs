@@ -0,0 +1,134 @@
+//! Unbounded plane that only stores its live cells, see `--infinite`.
+//!
+//! The regular board becomes a window onto the plane: before every generation its cells are written into
+//! the plane, and afterwards the plane is read back into it. Only two-state rules are supported.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::conway::{self, Board, Cell};
+use crate::hashlife::HashLife;
+use crate::rule::Ruleset;
+
+/// How the plane behind the board is stored
+#[derive(Debug, Clone)]
+pub enum Plane {
+	/// Set of live cells, see `SparseBoard`
+	Sparse(SparseBoard),
+	/// Quadtree that can jump far ahead, see `--hashlife`
+	HashLife(HashLife),
+}
+
+impl Plane {
+	/// Whether the plane can simulate the neighborhood with offsets `kernels` in even and odd rows
+	pub fn supports(&self, kernels: [&[(isize, isize)]; 2]) -> bool {
+		match self {
+			Self::Sparse(_) => true,
+			Self::HashLife(_) => HashLife::supports(kernels),
+		}
+	}
+
+	pub fn store(&mut self, origin: (i64, i64), board: &Board) {
+		match self {
+			Self::Sparse(sparse) => sparse.store(origin, board),
+			Self::HashLife(life) => life.store(origin, board),
+		}
+	}
+
+	pub fn load(&self, origin: (i64, i64), board: &mut Board) {
+		match self {
+			Self::Sparse(sparse) => sparse.load(origin, board),
+			Self::HashLife(life) => life.load(origin, board),
+		}
+	}
+
+	pub fn advance(&mut self, rule: &Ruleset, kernels: [&[(isize, isize)]; 2], generations: u64) {
+		match self {
+			Self::Sparse(sparse) => {
+				for _ in 0..generations {
+					sparse.step(rule, kernels);
+				}
+			}
+			Self::HashLife(life) => life.advance(rule, kernels[0], generations),
+		}
+	}
+
+	/// Live cells outside the board when its top left corner sits at `origin`
+	pub fn population_outside(&self, origin: (i64, i64), board: &Board) -> usize {
+		match self {
+			Self::Sparse(sparse) => sparse.outside(origin, board).count(),
+			Self::HashLife(life) => life.population_outside(origin, board) as usize,
+		}
+	}
+
+	/// Adds to the board's checksum to cover the rest of the plane
+	pub fn checksum_outside(&self, origin: (i64, i64), board: &Board) -> u64 {
+		// NOTE(Simon): both planes hash the same cells the same way, so switching engines keeps the checksum
+		let hash = |sum: u64, (x, y): (i64, i64)| sum.wrapping_add(conway::mix(((y - origin.1) as u64) << 32 ^ (x - origin.0) as u64));
+		match self {
+			Self::Sparse(sparse) => sparse.outside(origin, board).fold(0, hash),
+			Self::HashLife(life) => life.outside(origin, board).into_iter().fold(0, hash),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SparseBoard {
+	alive: HashSet<(i64, i64)>,
+}
+
+impl SparseBoard {
+	/// Live cells outside the board when its top left corner sits at `origin`
+	pub fn outside<'a>(&'a self, origin: (i64, i64), board: &Board) -> impl Iterator<Item = (i64, i64)> + 'a {
+		let under = under(origin, board);
+		self.alive.iter().copied().filter(move |cell| !under(*cell))
+	}
+
+	/// Replaces the part of the plane under the board with the board's live cells
+	pub fn store(&mut self, origin: (i64, i64), board: &Board) {
+		let under = under(origin, board);
+		self.alive.retain(|cell| !under(*cell));
+		for (y, row) in board.iter().enumerate() {
+			for (x, cell) in row.iter().enumerate() {
+				if cell.is_alive() {
+					self.alive.insert((origin.0 + x as i64, origin.1 + y as i64));
+				}
+			}
+		}
+	}
+
+	/// Copies the part of the plane under the board into it
+	pub fn load(&self, origin: (i64, i64), board: &mut Board) {
+		for (y, row) in board.iter_mut().enumerate() {
+			for (x, cell) in row.iter_mut().enumerate() {
+				let alive = self.alive.contains(&(origin.0 + x as i64, origin.1 + y as i64));
+				*cell = if alive { Cell::Alive } else { Cell::Dead };
+			}
+		}
+	}
+
+	/// Simulates one generation of `rule`. `kernels` are the neighbor offsets of cells in even and odd rows,
+	/// which only differ on the hex grid.
+	pub fn step(&mut self, rule: &Ruleset, kernels: [&[(isize, isize)]; 2]) {
+		// NOTE(Simon): kernels need not be symmetric, so every live cell is counted by the cells that see it
+		let mut counts: HashMap<(i64, i64), usize> = HashMap::new();
+		for (x, y) in &self.alive {
+			for (parity, kernel) in kernels.iter().enumerate() {
+				for (dx, dy) in kernel.iter() {
+					let (nx, ny) = (x - *dx as i64, y - *dy as i64);
+					if ny.rem_euclid(2) as usize == parity {
+						*counts.entry((nx, ny)).or_default() += 1;
+					}
+				}
+			}
+		}
+		let survivors = self.alive.iter().copied().filter(|cell| rule.survives(counts.get(cell).copied().unwrap_or_default()));
+		let births = counts.iter().filter(|(cell, n)| !self.alive.contains(cell) && rule.born(**n)).map(|(cell, _)| *cell);
+		self.alive = survivors.chain(births).collect();
+	}
+}
+
+/// Whether a cell of the plane is under the board when its top left corner sits at `origin`
+fn under(origin: (i64, i64), board: &Board) -> impl Fn((i64, i64)) -> bool {
+	let (width, height) = (board.first().map_or(0, Vec::len) as i64, board.len() as i64);
+	move |(x, y)| (origin.0..origin.0 + width).contains(&x) && (origin.1..origin.1 + height).contains(&y)
+}
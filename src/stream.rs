@@ -0,0 +1,88 @@
+//! Streams every simulated generation to another process, see `--stream`.
+//!
+//! The sink is a Unix socket if the path is one, otherwise the path is opened as a file, which also
+//! covers named pipes. Opening a named pipe blocks until a reader has opened it. All numbers are
+//! little endian. Every frame is a `u32` payload length followed by the payload:
+//!
+//! | Field        | Type        | Meaning                                                      |
+//! |--------------|-------------|--------------------------------------------------------------|
+//! | `generation` | `u64`       | Generation shown by the frame                                |
+//! | `width`      | `u16`       | Board width in cells                                         |
+//! | `height`     | `u16`       | Board height in cells                                        |
+//! | `cells`      | bytes       | `ceil(width * height / 8)` bytes, one bit per cell in row-major order, least significant bit first, 1 is alive |
+//!
+//! If the consumer goes away the stream is closed and the simulation carries on without it.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::conway::Board;
+
+pub struct Stream {
+	path: PathBuf,
+	sink: Box<dyn Write>,
+}
+
+impl std::fmt::Debug for Stream {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("Stream").field("path", &self.path).finish()
+	}
+}
+
+impl Stream {
+	pub fn open(path: &Path) -> io::Result<Self> {
+		Ok(Self {
+			path: path.to_path_buf(),
+			sink: open_sink(path)?,
+		})
+	}
+
+	/// Writes one frame, returns `false` once the consumer is gone and the stream should be dropped
+	pub fn send(&mut self, generation: u64, board: &Board) -> bool {
+		let frame = encode_frame(generation, board);
+		match self.sink.write_all(&frame).and_then(|()| self.sink.flush()) {
+			Ok(()) => true,
+			Err(err) => {
+				eprintln!("stopped streaming to {}: {}", self.path.display(), err);
+				false
+			}
+		}
+	}
+}
+
+#[cfg(unix)]
+fn open_sink(path: &Path) -> io::Result<Box<dyn Write>> {
+	use std::os::unix::fs::FileTypeExt;
+	use std::os::unix::net::UnixStream;
+
+	if path.metadata().is_ok_and(|metadata| metadata.file_type().is_socket()) {
+		return Ok(Box::new(UnixStream::connect(path)?));
+	}
+	Ok(Box::new(OpenOptions::new().write(true).create(true).truncate(true).open(path)?))
+}
+
+#[cfg(not(unix))]
+fn open_sink(path: &Path) -> io::Result<Box<dyn Write>> {
+	Ok(Box::new(OpenOptions::new().write(true).create(true).truncate(true).open(path)?))
+}
+
+fn encode_frame(generation: u64, board: &Board) -> Vec<u8> {
+	let height = board.len();
+	let width = board.first().map_or(0, Vec::len);
+	let mut cells = vec![0u8; (width * height).div_ceil(8)];
+	for (i, cell) in board.iter().flatten().enumerate() {
+		if cell.is_alive() {
+			cells[i / 8] |= 1 << (i % 8);
+		}
+	}
+
+	let payload_len = 8 + 2 + 2 + cells.len();
+	let mut frame = Vec::with_capacity(4 + payload_len);
+	frame.extend_from_slice(&(payload_len as u32).to_le_bytes());
+	frame.extend_from_slice(&generation.to_le_bytes());
+	frame.extend_from_slice(&(width as u16).to_le_bytes());
+	frame.extend_from_slice(&(height as u16).to_le_bytes());
+	frame.extend_from_slice(&cells);
+	frame
+}
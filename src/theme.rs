@@ -0,0 +1,101 @@
+//! Built-in color schemes for the board and the text drawn over it.
+
+use anyhow::{bail, Context, Result};
+use coffee::graphics::Color;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Theme {
+	pub name: &'static str,
+	pub background: Color,
+	/// Live cells, the first color of Immigration and QuadLife
+	pub cell: Color,
+	pub grid: Color,
+	/// Status line, counters and other text
+	pub text: Color,
+	/// Every other dead cell with `--checkerboard`
+	pub checker: Color,
+	pub margin: Color,
+	/// Behind the population graph
+	pub panel: Color,
+}
+
+/// The first one is the default
+pub const THEMES: [Theme; 4] = [
+	Theme {
+		name: "light",
+		background: Color::WHITE,
+		cell: Color::BLACK,
+		grid: Color::BLACK,
+		text: Color::BLACK,
+		checker: Color { r: 0.96, g: 0.96, b: 0.96, a: 1.0 },
+		margin: Color { r: 0.9, g: 0.9, b: 0.9, a: 1.0 },
+		panel: Color { r: 1.0, g: 1.0, b: 1.0, a: 0.8 },
+	},
+	Theme {
+		name: "dark",
+		background: Color { r: 0.08, g: 0.08, b: 0.1, a: 1.0 },
+		cell: Color { r: 0.9, g: 0.9, b: 0.88, a: 1.0 },
+		grid: Color { r: 0.3, g: 0.3, b: 0.35, a: 1.0 },
+		text: Color { r: 0.9, g: 0.9, b: 0.88, a: 1.0 },
+		checker: Color { r: 0.12, g: 0.12, b: 0.15, a: 1.0 },
+		margin: Color { r: 0.2, g: 0.2, b: 0.24, a: 1.0 },
+		panel: Color { r: 0.1, g: 0.1, b: 0.12, a: 0.8 },
+	},
+	Theme {
+		name: "solarized",
+		background: Color { r: 0.99, g: 0.96, b: 0.89, a: 1.0 },
+		cell: Color { r: 0.03, g: 0.21, b: 0.26, a: 1.0 },
+		grid: Color { r: 0.58, g: 0.63, b: 0.63, a: 1.0 },
+		text: Color { r: 0.35, g: 0.43, b: 0.46, a: 1.0 },
+		checker: Color { r: 0.93, g: 0.91, b: 0.84, a: 1.0 },
+		margin: Color { r: 0.87, g: 0.85, b: 0.78, a: 1.0 },
+		panel: Color { r: 0.99, g: 0.96, b: 0.89, a: 0.8 },
+	},
+	Theme {
+		name: "terminal",
+		background: Color::BLACK,
+		cell: Color { r: 0.2, g: 0.9, b: 0.3, a: 1.0 },
+		grid: Color { r: 0.05, g: 0.25, b: 0.08, a: 1.0 },
+		text: Color { r: 0.2, g: 0.9, b: 0.3, a: 1.0 },
+		checker: Color { r: 0.03, g: 0.08, b: 0.03, a: 1.0 },
+		margin: Color { r: 0.1, g: 0.16, b: 0.1, a: 1.0 },
+		panel: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.8 },
+	},
+];
+
+impl Theme {
+	pub fn named(name: &str) -> Option<&'static Self> {
+		THEMES.iter().find(|theme| theme.name.eq_ignore_ascii_case(name))
+	}
+
+	/// The theme after this one in `THEMES`, wrapping around to the first
+	pub fn next(&self) -> &'static Self {
+		let index = THEMES.iter().position(|theme| theme.name == self.name).unwrap_or_default();
+		&THEMES[(index + 1) % THEMES.len()]
+	}
+
+	/// Every color of the theme inverted, a light theme turns dark and a dark one light. Keeps the name.
+	pub fn inverted(&self) -> Self {
+		let invert = |color: Color| Color { r: 1.0 - color.r, g: 1.0 - color.g, b: 1.0 - color.b, a: color.a };
+		Self {
+			name: self.name,
+			background: invert(self.background),
+			cell: invert(self.cell),
+			grid: invert(self.grid),
+			text: invert(self.text),
+			checker: invert(self.checker),
+			margin: invert(self.margin),
+			panel: invert(self.panel),
+		}
+	}
+}
+
+/// Parses a color written as `rrggbb` hex digits, optionally after a `#`
+pub fn parse_color(value: &str) -> Result<Color> {
+	let digits = value.strip_prefix('#').unwrap_or(value);
+	if digits.len() != 6 || !digits.is_ascii() {
+		bail!("expected a color like `#336699`, got `{}`", value);
+	}
+	let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).with_context(|| format!("invalid color `{}`", value));
+	Ok(Color::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
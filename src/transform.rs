@@ -0,0 +1,132 @@
+//! Rotations and reflections of row-major grids. Grids are indexed `grid[y][x]` and must be rectangular.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Transform {
+	RotateClockwise,
+	RotateCounterclockwise,
+	FlipHorizontal,
+	FlipVertical,
+}
+
+impl Transform {
+	/// Whether the transform swaps the width and height of the grid
+	pub const fn swaps_dimensions(self) -> bool {
+		matches!(self, Self::RotateClockwise | Self::RotateCounterclockwise)
+	}
+
+	pub fn apply<T: Copy>(self, grid: &[Vec<T>]) -> Vec<Vec<T>> {
+		match self {
+			Self::RotateClockwise => rotate_clockwise(grid),
+			Self::RotateCounterclockwise => rotate_counterclockwise(grid),
+			Self::FlipHorizontal => flip_horizontal(grid),
+			Self::FlipVertical => flip_vertical(grid),
+		}
+	}
+}
+
+pub fn rotate_clockwise<T: Copy>(grid: &[Vec<T>]) -> Vec<Vec<T>> {
+	let height = grid.len();
+	let width = grid.first().map_or(0, Vec::len);
+	(0..width).map(|x| (0..height).rev().map(|y| grid[y][x]).collect()).collect()
+}
+
+pub fn rotate_counterclockwise<T: Copy>(grid: &[Vec<T>]) -> Vec<Vec<T>> {
+	let height = grid.len();
+	let width = grid.first().map_or(0, Vec::len);
+	(0..width).rev().map(|x| (0..height).map(|y| grid[y][x]).collect()).collect()
+}
+
+/// Mirrors left and right
+pub fn flip_horizontal<T: Copy>(grid: &[Vec<T>]) -> Vec<Vec<T>> {
+	grid.iter().map(|row| row.iter().rev().copied().collect()).collect()
+}
+
+/// Mirrors top and bottom
+pub fn flip_vertical<T: Copy>(grid: &[Vec<T>]) -> Vec<Vec<T>> {
+	grid.iter().rev().cloned().collect()
+}
+
+/// Drawing symmetry around the middle of the board, every painted cell is painted at all its images too
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Symmetry {
+	None,
+	/// Mirrored left and right
+	Horizontal,
+	/// Mirrored top and bottom
+	Vertical,
+	/// Mirrored both ways
+	FourFold,
+	/// Turned by half a turn
+	Rotate2,
+	/// Turned by quarter turns
+	Rotate4,
+}
+
+impl Symmetry {
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::None => "off",
+			Self::Horizontal => "mirrored left and right",
+			Self::Vertical => "mirrored top and bottom",
+			Self::FourFold => "mirrored both ways",
+			Self::Rotate2 => "half turns",
+			Self::Rotate4 => "quarter turns",
+		}
+	}
+
+	pub const fn next(self) -> Self {
+		match self {
+			Self::None => Self::Horizontal,
+			Self::Horizontal => Self::Vertical,
+			Self::Vertical => Self::FourFold,
+			Self::FourFold => Self::Rotate2,
+			Self::Rotate2 => Self::Rotate4,
+			Self::Rotate4 => Self::None,
+		}
+	}
+
+	/// `x`, `y` and its images on a board of `width` by `height` cells. Quarter turns of a board whose sides
+	/// differ leave out the images that fall off the board or between two cells.
+	pub fn images(self, (x, y): (usize, usize), (width, height): (usize, usize)) -> Vec<(usize, usize)> {
+		// NOTE(Simon): doubled coordinates relative to the middle of the board keep half cells integral
+		let (w, h) = (width as isize - 1, height as isize - 1);
+		let (dx, dy) = (2 * x as isize - w, 2 * y as isize - h);
+		let offsets: &[(isize, isize)] = match self {
+			Self::None => &[(dx, dy)],
+			Self::Horizontal => &[(dx, dy), (-dx, dy)],
+			Self::Vertical => &[(dx, dy), (dx, -dy)],
+			Self::FourFold => &[(dx, dy), (-dx, dy), (dx, -dy), (-dx, -dy)],
+			Self::Rotate2 => &[(dx, dy), (-dx, -dy)],
+			Self::Rotate4 => &[(dx, dy), (-dy, dx), (-dx, -dy), (dy, -dx)],
+		};
+		let cell = |doubled: isize, max: isize| ((doubled + max) % 2 == 0 && (-max..=max).contains(&doubled)).then(|| ((doubled + max) / 2) as usize);
+		offsets.iter().filter_map(|(dx, dy)| Some((cell(*dx, w)?, cell(*dy, h)?))).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn four_rotations() {
+		for (width, height) in [(4, 4), (5, 3), (1, 7)] {
+			let grid: Vec<Vec<_>> = (0..height).map(|y| (0..width).map(|x| y * width + x).collect()).collect();
+			for rotate in [Transform::RotateClockwise, Transform::RotateCounterclockwise] {
+				let turned = (0..4).fold(grid.clone(), |grid, _| rotate.apply(&grid));
+				assert_eq!(turned, grid, "four {:?} of a {}x{} grid", rotate, width, height);
+			}
+			let back = Transform::RotateCounterclockwise.apply(&Transform::RotateClockwise.apply(&grid));
+			assert_eq!(back, grid);
+		}
+	}
+
+	#[test]
+	fn rotation_swaps_dimensions() {
+		let grid = vec![vec![1, 2, 3], vec![4, 5, 6]];
+		assert_eq!(Transform::RotateClockwise.apply(&grid), vec![vec![4, 1], vec![5, 2], vec![6, 3]]);
+		assert_eq!(Transform::RotateCounterclockwise.apply(&grid), vec![vec![3, 6], vec![2, 5], vec![1, 4]]);
+		assert!(Transform::RotateClockwise.swaps_dimensions());
+		assert!(!Transform::FlipHorizontal.swaps_dimensions());
+	}
+}
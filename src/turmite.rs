@@ -0,0 +1,112 @@
+use anyhow::{bail, Result};
+
+use crate::conway::{Board, Cell};
+
+/// Most colors a turmite rule can cycle through.
+pub const MAX_COLORS: usize = 8;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Turn {
+	Left,
+	Right,
+	/// Keep going straight
+	None,
+	/// Turn around
+	U,
+}
+
+/// Rule for Langton's ant and its multi-color generalizations, e.g. `RL` for the classic ant.
+///
+/// Letter `i` says how an ant turns on a cell of color `i`. The ant then moves the cell on to the next color
+/// and steps forward. Color 0 is a dead cell and color 1 a live one. The other colors are stored as dying cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turmite {
+	turns: Vec<Turn>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ant {
+	pub x: usize,
+	pub y: usize,
+	/// 0 is up, counting clockwise in quarter turns
+	pub heading: u8,
+}
+
+impl Ant {
+	pub const fn new(x: usize, y: usize) -> Self {
+		Self { x, y, heading: 0 }
+	}
+}
+
+impl Turmite {
+	/// `RL`, the classic ant
+	pub fn langtons_ant() -> Self {
+		Self {
+			turns: vec![Turn::Right, Turn::Left],
+		}
+	}
+
+	pub const fn colors(&self) -> usize {
+		self.turns.len()
+	}
+
+	/// Moves every ant one step. Ants wrap around the edges of the board and ignore the margin and region.
+	pub fn step(&self, board: &mut Board, ants: &mut [Ant]) {
+		let height = board.len();
+		let width = board.first().map_or(0, Vec::len);
+		for ant in ants {
+			let cell = &mut board[ant.y][ant.x];
+			let color = usize::from(cell.state()) % self.colors();
+			ant.heading = match self.turns[color] {
+				Turn::Left => (ant.heading + 3) % 4,
+				Turn::Right => (ant.heading + 1) % 4,
+				Turn::None => ant.heading,
+				Turn::U => (ant.heading + 2) % 4,
+			};
+			*cell = Cell::from_state(((color + 1) % self.colors()) as u8);
+			(ant.x, ant.y) = match ant.heading {
+				0 => (ant.x, (ant.y + height - 1) % height),
+				1 => ((ant.x + 1) % width, ant.y),
+				2 => (ant.x, (ant.y + 1) % height),
+				_ => ((ant.x + width - 1) % width, ant.y),
+			};
+		}
+	}
+}
+
+impl std::str::FromStr for Turmite {
+	type Err = anyhow::Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		let turns = s
+			.trim()
+			.chars()
+			.map(|c| match c.to_ascii_uppercase() {
+				'L' => Ok(Turn::Left),
+				'R' => Ok(Turn::Right),
+				'N' => Ok(Turn::None),
+				'U' => Ok(Turn::U),
+				c => bail!("invalid turn `{}`, expected L, R, N or U", c),
+			})
+			.collect::<Result<Vec<_>>>()?;
+		if !(2..=MAX_COLORS).contains(&turns.len()) {
+			bail!("a turmite rule needs 2 to {} turns, one per color", MAX_COLORS);
+		}
+		Ok(Self { turns })
+	}
+}
+
+impl std::fmt::Display for Turmite {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		for turn in &self.turns {
+			let c = match turn {
+				Turn::Left => 'L',
+				Turn::Right => 'R',
+				Turn::None => 'N',
+				Turn::U => 'U',
+			};
+			write!(f, "{}", c)?;
+		}
+		Ok(())
+	}
+}